@@ -0,0 +1,151 @@
+//! Times loading the 23k-line trumpet OBJ file, from disk with `load_obj` and from memory with
+//! `read_obj`, and with `load_obj_parallel` when the `rayon` feature is enabled. The loader the
+//! crate had before statements were parsed without allocating is kept here as `baseline`, so the
+//! loaders can be compared on the same machine. Run with `cargo bench --bench load_obj`, adding
+//! `--features rayon` for the parallel loader.
+
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufRead, BufReader};
+use std::time::{Duration, Instant};
+
+use nalgebra::{Point3, Unit, Vector3};
+use rasterflow::geometry::polymesh::{PolyMesh, PolygonMesh};
+use regex::Regex;
+
+/// The former OBJ loader, which copies every line into a new `String` and compiles a `Regex` for
+/// each line that is not a vertex or a face. Only vertices, faces and face normals are kept.
+mod baseline {
+    use super::*;
+
+    /// The vertices, faces and face normals read from an OBJ file.
+    #[derive(Default)]
+    pub struct Mesh {
+        pub vertices: Vec<Point3<f32>>,
+        pub faces: Vec<Vec<usize>>,
+        pub face_normals: Vec<Unit<Vector3<f32>>>,
+    }
+
+    /// Reads the coordinates of a `v` statement into the mesh.
+    fn process_vertex(mesh: &mut Mesh, vertex_string: &str) -> Option<&'static str> {
+        let mut point_strings = vertex_string.split_ascii_whitespace();
+        let mut vertex = [0.; 3];
+        for x in &mut vertex {
+            let numeric_string = point_strings.next()?;
+            *x = match numeric_string.parse() {
+                Ok(f) => f,
+                Err(_) => match numeric_string.parse::<i32>() {
+                    Ok(i) => i as f32,
+                    Err(_) => return Some("Failed to parse float."),
+                },
+            };
+        }
+        mesh.vertices.push(Point3::from(vertex));
+        None
+    }
+
+    /// Reads the vertex indices of an `f` statement into the mesh, along with its normal.
+    fn process_face(mesh: &mut Mesh, face_string: &str) -> Option<&'static str> {
+        let mut face = Vec::new();
+        for element in face_string.split_ascii_whitespace() {
+            let index = element.split('/').next()?;
+            let vertex = match index.parse::<usize>() {
+                Ok(i) => i - 1,
+                Err(_) => return Some("Failed to parse integer."),
+            };
+            if vertex >= mesh.vertices.len() {
+                return Some("Vertex not contained in mesh.");
+            }
+            face.push(vertex);
+        }
+        if face.len() < 3 {
+            return Some("Face does not have enough vertices.");
+        }
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[face[i]]);
+        mesh.face_normals
+            .push(Unit::new_normalize((b - a).cross(&(c - a))));
+        mesh.faces.push(face);
+        None
+    }
+
+    /// Loads the OBJ file `filename` as the former loader did.
+    // Compiling the regex for every line is part of the cost being measured.
+    #[allow(clippy::regex_creation_in_loops)]
+    pub fn load_obj(filename: &str) -> Result<Mesh, &'static str> {
+        let mut mesh = Mesh::default();
+        let file = File::open(filename).map_err(|_| "File failed to open.")?;
+        let mut bufread = BufReader::new(file);
+        let mut buffer_string = String::new();
+
+        while bufread
+            .read_line(&mut buffer_string)
+            .map_err(|_| "Could not read next line.")?
+            != 0
+        {
+            buffer_string = buffer_string.trim().to_string();
+            if let Some(vertex) = buffer_string.strip_prefix("v ") {
+                if let Some(error) = process_vertex(&mut mesh, vertex) {
+                    return Err(error);
+                }
+            } else if let Some(face) = buffer_string.strip_prefix("f ") {
+                if let Some(error) = process_face(&mut mesh, face) {
+                    return Err(error);
+                }
+            } else if !(Regex::new(r"(?m)^(?:#|v[tnp]|g|o|s|usemtl|mtllib|l)( +.*)?")
+                .unwrap()
+                .is_match_at(buffer_string.as_str(), 0)
+                || buffer_string.is_empty())
+            {
+                return Err("Invalid file line.");
+            }
+            buffer_string = String::new();
+        }
+
+        Ok(mesh)
+    }
+}
+
+/// The number of timed loads, after one untimed load to warm the caches.
+const ITERATIONS: u32 = 50;
+
+/// Times `load`, printing the mean duration of a load and the lines read per second.
+fn bench(name: &str, lines: usize, load: impl Fn()) {
+    load();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        load();
+    }
+    let mean: Duration = start.elapsed() / ITERATIONS;
+    println!(
+        "{:<10} {:>10.3} ms/load {:>12.0} lines/s",
+        name,
+        mean.as_secs_f64() * 1e3,
+        lines as f64 / mean.as_secs_f64()
+    );
+}
+
+fn main() {
+    let filename = concat!(env!("CARGO_MANIFEST_DIR"), "/test-files/trumpet.obj");
+    let bytes = std::fs::read(filename).unwrap();
+    let lines = bytes.iter().filter(|&&b| b == b'\n').count();
+
+    // Both loaders must read the same mesh for the comparison to mean anything.
+    let mesh = PolygonMesh::load_obj(filename).unwrap();
+    let reference = baseline::load_obj(filename).unwrap();
+    assert_eq!(mesh.get_vertices(), &reference.vertices);
+    assert_eq!(mesh.get_faces(), &reference.faces);
+
+    bench("baseline", lines, || {
+        black_box(baseline::load_obj(filename).unwrap());
+    });
+    bench("load_obj", lines, || {
+        black_box(PolygonMesh::load_obj(filename).unwrap());
+    });
+    bench("read_obj", lines, || {
+        black_box(PolygonMesh::read_obj(&bytes[..]).unwrap());
+    });
+    #[cfg(feature = "rayon")]
+    bench("parallel", lines, || {
+        black_box(PolygonMesh::load_obj_parallel(filename).unwrap());
+    });
+}
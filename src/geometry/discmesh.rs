@@ -1,8 +1,25 @@
 /*! This module contains classes to represent discretized meshes. The `CellMesh` struct represents
 the volume of an object implementing `PolyMesh` comprised of 3-D volume elements (`Cell`). */
-use nalgebra::{vector, Point, Unit};
 
-use crate::{Float, Int, Point3, Uint, UnitVec3, Vec3};
+/// The `quality` module measures the shape of the tetrahedra in a `TetrahedralMesh`.
+pub mod quality;
+
+/// The `gmsh` module reads and writes Gmsh MSH files.
+mod gmsh;
+/// The `tetgen` module reads and writes the TetGen file formats.
+mod tetgen;
+
+pub use gmsh::GmshFormat;
+pub use tetgen::PlcFormat;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use nalgebra::{Matrix3, RealField};
+use simba::scalar::SubsetOf;
+
+use crate::geometry::polymesh::{Attribute, FaceGroup, MeshError};
+use crate::{Float, Point3, Uint, Vec3};
 
 /// The `Cell` trait is used to label structs as valid cells. The set of associated methods for
 /// this trait are as follows:
@@ -12,19 +29,99 @@ pub trait Cell: PartialEq + Clone {}
 /// comprised of cells implementing the `Cell` trait. This cell trait is given by the type
 /// parameter `T`.
 pub trait CellMesh<T: Cell> {
-    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = &'a T> + 'a>;
+    /// A borrowed view of a cell that shares the vertices of the mesh. It can be converted into
+    /// an owned `T`.
+    type View<'a>: Into<T>
+    where
+        Self: 'a;
+
+    /**
+    Retrieves the number of cells in the mesh.
+
+    Returns:
+    - `usize` - The number of cells.
+     */
+    fn get_cell_count(&self) -> usize;
+
+    /**
+    Gets a view of the cell at a given index.
+
+    Parameters:
+    - `idx: usize` - The index of the cell to retrieve.
+
+    Returns:
+    - `Result<Self::View<'_>, MeshError>` - Returns the view if the indexing succeeds, else
+      `MeshError::IndexingError`.
+     */
+    fn get_cell(&self, idx: usize) -> Result<Self::View<'_>, MeshError>;
+
+    /// Iterates over views of the cells of the mesh, in index order.
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = Self::View<'a>> + 'a>;
+
+    /// Iterates over owned copies of the cells of the mesh, in index order.
+    fn cells<'a>(&'a self) -> Box<dyn Iterator<Item = T> + 'a>
+    where
+        T: 'a,
+    {
+        Box::new(self.iter().map(Into::into))
+    }
 }
 
 /// A `Tetrahedron` is a type that implements the `Cell` trait. It is a struct tuple containing a fixed-size array of 4 3D points.
-#[derive(Clone)]
-pub struct Tetrahedron([Point3; 4]);
+#[derive(Clone, Debug)]
+pub struct Tetrahedron<T: RealField + Copy = Float>([Point3<T>; 4]);
+
+/// A `TetrahedronView` borrows a single tetrahedron of a `TetrahedralMesh` without copying its
+/// vertices.
+#[derive(Clone, Copy)]
+pub struct TetrahedronView<'a, T: RealField + Copy = Float> {
+    mesh: &'a TetrahedralMesh<T>,
+    index: Uint,
+}
+
+/// A `BoundaryFace` is a face on the boundary of a `TetrahedralMesh` that was recovered from a face
+/// of the surface mesh it was discretized from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundaryFace {
+    /// The vertices of the face, wound like the surface face it came from.
+    pub vertices: [Uint; 3],
+    /// The index of the surface face this face lies on.
+    pub source: Uint,
+}
+
+/// A named set of cells, such as a Gmsh physical volume.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellGroup {
+    pub name: String,
+    /// The indices of the cells in the set, in ascending order.
+    pub cells: Vec<Uint>,
+}
 
 /// The `TetrahedralMesh` struct represents the collection of connected vertices, edges, and facee comprising a set of tetrahedra.
-pub struct TetrahedralMesh {
-    vertices: Vec<Point3>,
-    faces: Vec<[Uint; 3]>,
+pub struct TetrahedralMesh<T: RealField + Copy = Float> {
+    pub(crate) vertices: Vec<Point3<T>>,
+    pub(crate) faces: Vec<[Uint; 3]>,
+    // Each tetrahedron is stored as four indices into `vertices`, ordered to have positive volume.
+    pub(crate) tetrahedra: Vec<[Uint; 4]>,
+    // The tetrahedron across each face of each tetrahedron, where face `i` is opposite vertex `i`.
+    pub(crate) neighbors: Vec<[Option<Uint>; 4]>,
+    // Boundary faces that are known to lie on a face of the source surface.
+    pub(crate) boundary_faces: Vec<BoundaryFace>,
+    // Named boundary patches, holding indices into `boundary_faces`.
+    pub(crate) patches: Vec<FaceGroup>,
+    // Named regions of the volume, holding indices into `tetrahedra`.
+    pub(crate) regions: Vec<CellGroup>,
+    pub(crate) vertex_attributes: Vec<Attribute>,
+    pub(crate) cell_attributes: Vec<Attribute>,
 }
 
+/// The faces of a tetrahedron, where face `i` is opposite vertex `i`. Each face is wound so its
+/// normal points out of a positively oriented tetrahedron.
+pub(crate) const TET_FACES: [[usize; 3]; 4] = [[1, 2, 3], [0, 3, 2], [0, 1, 3], [0, 2, 1]];
+
+/// The pairs of vertices joined by the six edges of a tetrahedron.
+pub(crate) const TET_EDGES: [[usize; 2]; 6] = [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]];
+
 const PERM4: [[usize; 4]; 24] = [
     [0, 1, 2, 3],
     [0, 1, 3, 2],
@@ -52,17 +149,445 @@ const PERM4: [[usize; 4]; 24] = [
     [3, 2, 1, 0],
 ];
 
-impl PartialEq for Tetrahedron {
+impl<T: RealField + Copy> TetrahedralMesh<T> {
+    /**
+    Builds a `TetrahedralMesh` from its vertices and tetrahedra, checking that every tetrahedron
+    refers to vertices of the mesh and has positive volume. The faces of the mesh are collected
+    from the tetrahedra, with faces shared between two tetrahedra only stored once, and the
+    tetrahedra sharing each face are recorded as neighbours.
+    This method may return `MeshError` if:
+    - Vertex referenced in tetrahedron out of range: `MeshError::IndexingError("Vertex not
+      contained in mesh.")`
+    - Tetrahedron is flat or inverted: `MeshError::FormatError("Tetrahedron does not have
+      positive volume.")`
+    - Face shared by more than two tetrahedra: `MeshError::FormatError("Face shared by more than
+      two tetrahedra.")`
+
+    Parameters:
+    - `vertices: Vec<Point3<T>>` - The vertices of the mesh.
+    - `tetrahedra: Vec<[Uint; 4]>` - The tetrahedra of the mesh, indexing into `vertices`, with
+      vertices ordered as in `Tetrahedron::signed_volume`.
+
+    Returns:
+    - `Result<TetrahedralMesh<T>, MeshError>` - The assembled mesh, else a `MeshError`.
+     */
+    pub fn new(
+        vertices: Vec<Point3<T>>,
+        tetrahedra: Vec<[Uint; 4]>,
+    ) -> Result<TetrahedralMesh<T>, MeshError> {
+        for tet in &tetrahedra {
+            if tet.iter().any(|&v| v >= vertices.len()) {
+                return Err(MeshError::IndexingError("Vertex not contained in mesh."));
+            }
+            if Tetrahedron::new(tet.map(|v| vertices[v])).signed_volume() <= T::zero() {
+                return Err(MeshError::FormatError(
+                    "Tetrahedron does not have positive volume.",
+                ));
+            }
+        }
+        TetrahedralMesh::assemble(vertices, tetrahedra)
+    }
+
+    /**
+    Builds a `TetrahedralMesh` from vertices and tetrahedra that are already known to be in range,
+    as `new` does but without checking the volumes of the tetrahedra.
+    This method may return `MeshError` if:
+    - Face shared by more than two tetrahedra: `MeshError::FormatError("Face shared by more than
+      two tetrahedra.")`
+
+    Parameters:
+    - `vertices: Vec<Point3<T>>` - The vertices of the mesh.
+    - `tetrahedra: Vec<[Uint; 4]>` - The tetrahedra of the mesh, indexing into `vertices`.
+
+    Returns:
+    - `Result<TetrahedralMesh<T>, MeshError>` - The assembled mesh, else a `MeshError`.
+     */
+    pub(crate) fn assemble(
+        vertices: Vec<Point3<T>>,
+        tetrahedra: Vec<[Uint; 4]>,
+    ) -> Result<TetrahedralMesh<T>, MeshError> {
+        let mut seen: HashMap<[Uint; 3], (Uint, usize)> = HashMap::new();
+        let mut faces = Vec::new();
+        let mut neighbors = vec![[None; 4]; tetrahedra.len()];
+        for (t, tet) in tetrahedra.iter().enumerate() {
+            for (i, face) in TET_FACES.iter().enumerate() {
+                let face = face.map(|j| tet[j]);
+                let mut key = face;
+                key.sort_unstable();
+                match seen.entry(key) {
+                    Entry::Vacant(entry) => {
+                        entry.insert((t, i));
+                        faces.push(face);
+                    }
+                    Entry::Occupied(entry) => {
+                        let (u, j) = *entry.get();
+                        // The first tetrahedron already has a neighbour across the face.
+                        if neighbors[u][j].is_some() {
+                            return Err(MeshError::FormatError(
+                                "Face shared by more than two tetrahedra.",
+                            ));
+                        }
+                        neighbors[t][i] = Some(u);
+                        neighbors[u][j] = Some(t);
+                    }
+                }
+            }
+        }
+
+        Ok(TetrahedralMesh {
+            vertices,
+            faces,
+            tetrahedra,
+            neighbors,
+            boundary_faces: Vec::new(),
+            patches: Vec::new(),
+            regions: Vec::new(),
+            vertex_attributes: Vec::new(),
+            cell_attributes: Vec::new(),
+        })
+    }
+
+    /// Returns the vertices of the mesh.
+    pub fn get_vertices(&self) -> &Vec<Point3<T>> {
+        &self.vertices
+    }
+
+    /// Returns every distinct triangular face of the mesh.
+    pub fn get_faces(&self) -> &Vec<[Uint; 3]> {
+        &self.faces
+    }
+
+    /// Returns the tetrahedra of the mesh as indices into the vertices.
+    pub fn get_tetrahedra(&self) -> &Vec<[Uint; 4]> {
+        &self.tetrahedra
+    }
+
+    /// Returns, for each tetrahedron, the tetrahedron sharing each of its faces, where face `i` is
+    /// the one opposite vertex `i`. Faces on the boundary of the mesh have no neighbour.
+    pub fn get_neighbors(&self) -> &Vec<[Option<Uint>; 4]> {
+        &self.neighbors
+    }
+
+    /**
+    Collects the faces belonging to exactly one tetrahedron, which together form the boundary of
+    the mesh. Unlike `get_boundary_faces`, this works for any mesh but does not know which surface
+    face each boundary face came from.
+
+    Returns:
+    - `Vec<[Uint; 3]>` - The boundary faces, wound so their normals point out of the mesh.
+     */
+    pub fn extract_boundary_faces(&self) -> Vec<[Uint; 3]> {
+        self.tetrahedra
+            .iter()
+            .zip(&self.neighbors)
+            .flat_map(|(tet, neighbors)| {
+                TET_FACES
+                    .iter()
+                    .zip(neighbors)
+                    .filter(|(_, neighbor)| neighbor.is_none())
+                    .map(|(face, _)| face.map(|j| tet[j]))
+            })
+            .collect()
+    }
+
+    /// Returns the boundary faces of the mesh that were recovered from faces of the source
+    /// surface. This is only populated by constrained discretization.
+    pub fn get_boundary_faces(&self) -> &Vec<BoundaryFace> {
+        &self.boundary_faces
+    }
+
+    /// Returns the boundary patches of the mesh, carried over from the patches of the source
+    /// surface. Each patch holds indices into `get_boundary_faces`.
+    pub fn get_patches(&self) -> &Vec<FaceGroup> {
+        &self.patches
+    }
+
+    /**
+    Gets the boundary patch called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+
+    Returns:
+    - `Option<&FaceGroup>` - The patch, holding indices into `get_boundary_faces`, or `None` if
+      the mesh has no patch called `name`.
+     */
+    pub fn get_patch(&self, name: &str) -> Option<&FaceGroup> {
+        self.patches.iter().find(|p| p.name == name)
+    }
+
+    /**
+    Collects the boundary faces of the patch called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+
+    Returns:
+    - `Option<Vec<BoundaryFace>>` - The boundary faces of the patch, or `None` if the mesh has no
+      patch called `name`.
+     */
+    pub fn get_patch_faces(&self, name: &str) -> Option<Vec<BoundaryFace>> {
+        self.get_patch(name).map(|patch| {
+            patch
+                .faces
+                .iter()
+                .map(|&f| self.boundary_faces[f])
+                .collect()
+        })
+    }
+
+    /// Returns the named regions of the volume, such as the physical volumes of a Gmsh file.
+    pub fn get_regions(&self) -> &Vec<CellGroup> {
+        &self.regions
+    }
+
+    /**
+    Gets the region called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the region.
+
+    Returns:
+    - `Option<&CellGroup>` - The region, holding indices into `get_tetrahedra`, or `None` if the
+      mesh has no region called `name`.
+     */
+    pub fn get_region(&self, name: &str) -> Option<&CellGroup> {
+        self.regions.iter().find(|r| r.name == name)
+    }
+
+    /// Retrieves the named per-vertex attributes of the mesh, such as the node attributes of a
+    /// TetGen file.
+    pub fn get_vertex_attributes(&self) -> &Vec<Attribute> {
+        &self.vertex_attributes
+    }
+
+    /// Retrieves the named per-tetrahedron attributes of the mesh, such as the region attributes
+    /// of a TetGen file.
+    pub fn get_cell_attributes(&self) -> &Vec<Attribute> {
+        &self.cell_attributes
+    }
+
+    /// Returns the number of tetrahedra in the mesh.
+    pub fn get_tetrahedron_count(&self) -> usize {
+        self.tetrahedra.len()
+    }
+
+    /**
+    Converts the mesh to another scalar type, for example to hand a mesh discretized in double
+    precision to code working in single precision.
+
+    Returns:
+    - `TetrahedralMesh<U>` - A copy of the mesh with every coordinate converted to `U`.
+     */
+    pub fn cast<U: RealField + Copy>(&self) -> TetrahedralMesh<U>
+    where
+        T: SubsetOf<U>,
+    {
+        TetrahedralMesh {
+            vertices: self.vertices.iter().map(|v| v.cast()).collect(),
+            faces: self.faces.clone(),
+            tetrahedra: self.tetrahedra.clone(),
+            neighbors: self.neighbors.clone(),
+            boundary_faces: self.boundary_faces.clone(),
+            patches: self.patches.clone(),
+            regions: self.regions.clone(),
+            vertex_attributes: self.vertex_attributes.clone(),
+            cell_attributes: self.cell_attributes.clone(),
+        }
+    }
+}
+
+impl<T: RealField + Copy> Tetrahedron<T> {
+    /// Creates a tetrahedron from its four corners.
+    pub fn new(points: [Point3<T>; 4]) -> Tetrahedron<T> {
+        Tetrahedron(points)
+    }
+
+    /// Returns the corners of the tetrahedron.
+    pub fn get_points(&self) -> &[Point3<T>; 4] {
+        &self.0
+    }
+
+    /// Returns the Jacobian of the affine map from the reference tetrahedron (the origin and the
+    /// three unit vectors) onto this one, whose columns are the edges leaving the first corner.
+    pub fn jacobian(&self) -> Matrix3<T> {
+        let [a, b, c, d] = &self.0;
+        Matrix3::from_columns(&[b - a, c - a, d - a])
+    }
+
+    /// Returns the volume of the tetrahedron, which is negative if it is inverted.
+    pub fn signed_volume(&self) -> T {
+        self.jacobian().determinant() / nalgebra::convert(6.)
+    }
+
+    /// Returns the volume of the tetrahedron.
+    pub fn volume(&self) -> T {
+        self.signed_volume().abs()
+    }
+
+    /// Returns the centroid of the tetrahedron.
+    pub fn centroid(&self) -> Point3<T> {
+        let [a, b, c, d] = &self.0;
+        Point3::from((a.coords + b.coords + c.coords + d.coords) / nalgebra::convert::<f64, T>(4.))
+    }
+
+    /**
+    Computes the center of the sphere passing through all four corners.
+
+    Returns:
+    - `Option<Point3<T>>` - The circumcenter, or `None` if the tetrahedron is flat.
+     */
+    pub fn circumcenter(&self) -> Option<Point3<T>> {
+        // The circumcenter `c` satisfies `(p - a) . (c - a) = |p - a|^2 / 2` for every corner `p`.
+        let a = self.0[0];
+        let rhs = Vec3::from_fn(|k, _| (self.0[k + 1] - a).norm_squared() / nalgebra::convert(2.));
+        let offset = self.jacobian().transpose().lu().solve(&rhs)?;
+        offset.iter().all(|x| x.is_finite()).then(|| a + offset)
+    }
+
+    /// Returns the radius of the sphere passing through all four corners, or `None` if the
+    /// tetrahedron is flat.
+    pub fn circumradius(&self) -> Option<T> {
+        self.circumcenter().map(|c| (c - self.0[0]).norm())
+    }
+
+    /// Returns the radius of the largest sphere inside the tetrahedron, which is zero if it is
+    /// flat.
+    pub fn inradius(&self) -> T {
+        let area = self
+            .face_areas()
+            .into_iter()
+            .fold(T::zero(), |sum, a| sum + a);
+        if area > T::zero() {
+            self.volume() * nalgebra::convert(3.) / area
+        } else {
+            T::zero()
+        }
+    }
+
+    /// Returns the area of each face, where face `i` is the one opposite corner `i`.
+    pub fn face_areas(&self) -> [T; 4] {
+        TET_FACES.map(|[i, j, k]| {
+            let [a, b, c] = [i, j, k].map(|v| self.0[v]);
+            (b - a).cross(&(c - a)).norm() / nalgebra::convert(2.)
+        })
+    }
+
+    /// Returns the unit normal of each face pointing out of the tetrahedron, where face `i` is the
+    /// one opposite corner `i`. Degenerate faces, and every face of a flat tetrahedron, have a
+    /// zero normal.
+    pub fn face_normals(&self) -> [Vec3<T>; 4] {
+        let volume = self.signed_volume();
+        if volume == T::zero() {
+            return [Vec3::zeros(); 4];
+        }
+        let sign = volume.signum();
+        TET_FACES.map(|[i, j, k]| {
+            let [a, b, c] = [i, j, k].map(|v| self.0[v]);
+            ((b - a).cross(&(c - a)) * sign)
+                .try_normalize(T::zero())
+                .unwrap_or_else(Vec3::zeros)
+        })
+    }
+
+    /// Returns the dihedral angle at each edge in radians, with the edges ordered `(0, 1)`,
+    /// `(0, 2)`, `(0, 3)`, `(1, 2)`, `(1, 3)`, `(2, 3)`.
+    pub fn dihedral_angles(&self) -> [T; 6] {
+        let normals = self.face_normals();
+        // The edge joining two corners is shared by the faces opposite the other two.
+        TET_EDGES.map(|[i, j]| {
+            let mut others = (0..4).filter(|&v| v != i && v != j);
+            let (k, l) = (others.next().unwrap(), others.next().unwrap());
+            T::pi()
+                - normals[k]
+                    .dot(&normals[l])
+                    .clamp(-T::one(), T::one())
+                    .acos()
+        })
+    }
+
+    /**
+    Computes the barycentric coordinates of `p`, which weight the corners so their weighted sum is
+    `p`. All four coordinates are non-negative exactly when `p` lies in the tetrahedron.
+
+    Parameters:
+    - `p: &Point3<T>` - The point to express in terms of the corners.
+
+    Returns:
+    - `Option<[T; 4]>` - The coordinates, or `None` if the tetrahedron is flat.
+     */
+    pub fn barycentric(&self, p: &Point3<T>) -> Option<[T; 4]> {
+        let weights = self.jacobian().lu().solve(&(p - self.0[0]))?;
+        if !weights.iter().all(|x| x.is_finite()) {
+            return None;
+        }
+        Some([T::one() - weights.sum(), weights.x, weights.y, weights.z])
+    }
+
+    /// Returns whether `p` lies inside the tetrahedron or on its boundary. Flat tetrahedra contain
+    /// no points.
+    pub fn contains(&self, p: &Point3<T>) -> bool {
+        self.barycentric(p)
+            .is_some_and(|weights| weights.iter().all(|&w| w >= T::zero()))
+    }
+}
+
+impl<'a, T: RealField + Copy> TetrahedronView<'a, T> {
+    /// Returns the index of the tetrahedron within its mesh.
+    pub fn get_index(&self) -> Uint {
+        self.index
+    }
+
+    /// Returns the indices of the corners of the tetrahedron into the vertices of its mesh.
+    pub fn get_vertices(&self) -> &'a [Uint; 4] {
+        &self.mesh.tetrahedra[self.index]
+    }
+
+    /// Returns the corners of the tetrahedron.
+    pub fn get_points(&self) -> [&'a Point3<T>; 4] {
+        self.get_vertices().map(|v| &self.mesh.vertices[v])
+    }
+
+    /// Returns the tetrahedron sharing each face, where face `i` is the one opposite vertex `i`.
+    pub fn get_neighbors(&self) -> &'a [Option<Uint>; 4] {
+        &self.mesh.neighbors[self.index]
+    }
+}
+
+impl<T: RealField + Copy> From<TetrahedronView<'_, T>> for Tetrahedron<T> {
+    fn from(view: TetrahedronView<'_, T>) -> Tetrahedron<T> {
+        Tetrahedron(view.get_points().map(|p| *p))
+    }
+}
+
+impl<T: RealField + Copy> PartialEq for Tetrahedron<T> {
     fn eq(&self, other: &Self) -> bool {
         PERM4
             .iter()
             .any(|x| (0..4).all(|i| self.0[x[i]].eq(&other.0[i])))
     }
 }
-impl Cell for Tetrahedron {}
+impl<T: RealField + Copy> Cell for Tetrahedron<T> {}
+
+impl<T: RealField + Copy> CellMesh<Tetrahedron<T>> for TetrahedralMesh<T> {
+    type View<'a> = TetrahedronView<'a, T>;
+
+    fn get_cell_count(&self) -> usize {
+        self.tetrahedra.len()
+    }
+
+    fn get_cell(&self, idx: usize) -> Result<TetrahedronView<'_, T>, MeshError> {
+        if idx < self.tetrahedra.len() {
+            Ok(TetrahedronView {
+                mesh: self,
+                index: idx,
+            })
+        } else {
+            Err(MeshError::IndexingError("Indexing failed."))
+        }
+    }
 
-impl CellMesh<Tetrahedron> for TetrahedralMesh {
-    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Tetrahedron> + 'a> {
-        todo!()
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = TetrahedronView<'a, T>> + 'a> {
+        Box::new((0..self.tetrahedra.len()).map(move |index| TetrahedronView { mesh: self, index }))
     }
 }
@@ -0,0 +1,1222 @@
+/*! Reading and writing of Gmsh MSH files, in versions 2.2 and 4.1 and in ASCII or binary. Only
+triangles and tetrahedra are read, along with the physical groups they belong to. Triangles become
+the faces of a `TriangleMesh` or the boundary faces of a `TetrahedralMesh`, and their physical
+groups become patches, while the physical groups of tetrahedra become regions. */
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, Read, Write};
+use std::str::FromStr;
+
+use nalgebra::RealField;
+
+use super::{BoundaryFace, CellGroup, TetrahedralMesh};
+use crate::geometry::polymesh::{
+    open_file, write_file, FaceGroup, MeshError, MutateMesh, TriangleMesh,
+};
+use crate::Point3;
+
+/// The Gmsh element type of a 3-node triangle.
+const TRIANGLE: i32 = 2;
+
+/// The Gmsh element type of a 4-node tetrahedron.
+const TETRAHEDRON: i32 = 4;
+
+/// The version and encoding of an MSH file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GmshFormat {
+    AsciiV2,
+    BinaryV2,
+    AsciiV4,
+    BinaryV4,
+}
+
+/// Returns the number of nodes of an element of `element_type`, for the first-order and common
+/// second-order elements.
+fn node_count(element_type: i32) -> Option<usize> {
+    Some(match element_type {
+        15 => 1,
+        1 => 2,
+        2 | 8 => 3,
+        3 | 4 => 4,
+        7 => 5,
+        6 | 9 => 6,
+        5 | 16 => 8,
+        10 => 9,
+        11 => 10,
+        19 => 13,
+        14 => 14,
+        18 => 15,
+        13 => 18,
+        17 => 20,
+        12 => 27,
+        _ => return None,
+    })
+}
+
+/// A physical group, which names a set of elements of one dimension.
+struct PhysicalGroup {
+    dimension: i32,
+    tag: i32,
+    name: String,
+}
+
+/// The triangles and tetrahedra of an MSH file, with its nodes numbered from zero.
+#[derive(Default)]
+struct Msh {
+    nodes: Vec<[f64; 3]>,
+    triangles: Vec<[usize; 3]>,
+    tetrahedra: Vec<[usize; 4]>,
+    // The physical group of each triangle and tetrahedron, as an index into `groups`.
+    triangle_groups: Vec<Option<usize>>,
+    tetrahedron_groups: Vec<Option<usize>>,
+    groups: Vec<PhysicalGroup>,
+}
+
+impl Msh {
+    /**
+    Numbers the nodes in `used` from zero, in the order they appear in the file.
+
+    Parameters:
+    - `used: impl Iterator<Item = usize>` - The nodes referenced by the elements being kept.
+
+    Returns:
+    - `(Vec<[f64; 3]>, Vec<usize>)` - The coordinates of the used nodes, and the new index of
+      every node of the file, which is only meaningful for used nodes.
+     */
+    fn compact(&self, used: impl Iterator<Item = usize>) -> (Vec<[f64; 3]>, Vec<usize>) {
+        let mut keep = vec![false; self.nodes.len()];
+        for v in used {
+            keep[v] = true;
+        }
+        let mut nodes = Vec::new();
+        let mut index = vec![0; self.nodes.len()];
+        for (v, node) in self.nodes.iter().enumerate() {
+            if keep[v] {
+                index[v] = nodes.len();
+                nodes.push(*node);
+            }
+        }
+        (nodes, index)
+    }
+
+    /// Collects the physical groups of `dimension` as names and the indices of their elements,
+    /// given the group of each element of that dimension.
+    fn groups_of(
+        &self,
+        dimension: i32,
+        element_groups: &[Option<usize>],
+    ) -> Vec<(String, Vec<usize>)> {
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.dimension == dimension)
+            .map(|(g, group)| {
+                let elements = (0..element_groups.len())
+                    .filter(|&e| element_groups[e] == Some(g))
+                    .collect();
+                (group.name.clone(), elements)
+            })
+            .collect()
+    }
+
+    /// Adds a physical group of `dimension` for each of `named` and records it as the group of
+    /// its elements.
+    fn add_groups(&mut self, dimension: i32, named: &[(&str, &[usize])]) {
+        for (name, elements) in named {
+            let g = self.groups.len();
+            self.groups.push(PhysicalGroup {
+                dimension,
+                tag: g as i32 + 1,
+                name: name.to_string(),
+            });
+            let element_groups = if dimension == 2 {
+                &mut self.triangle_groups
+            } else {
+                &mut self.tetrahedron_groups
+            };
+            for &e in *elements {
+                element_groups[e] = Some(g);
+            }
+        }
+    }
+}
+
+/// Returns the error for a section of an MSH file that does not follow the format.
+fn invalid_section() -> MeshError {
+    MeshError::FormatError("Invalid MSH section.")
+}
+
+/// The contents of an MSH file being read, which reads values in the encoding of the file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    binary: bool,
+    big_endian: bool,
+    // Version 2 files store tags as `int`s, and version 4 files as `size_t`s.
+    version2: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Returns whether only whitespace is left to read.
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.offset == self.bytes.len()
+    }
+
+    /// Moves past any whitespace.
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.offset)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.offset += 1;
+        }
+    }
+
+    /// Reads the rest of the current line, without its line ending.
+    fn line(&mut self) -> Result<&'a str, MeshError> {
+        let rest = &self.bytes[self.offset..];
+        if rest.is_empty() {
+            return Err(invalid_section());
+        }
+        let length = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        self.offset += (length + 1).min(rest.len());
+        std::str::from_utf8(&rest[..length])
+            .map(|line| line.trim_end_matches('\r'))
+            .map_err(|_| MeshError::FormatError("Unable to process string."))
+    }
+
+    /// Reads the next word of text, skipping the whitespace before it.
+    fn word(&mut self) -> Result<&'a str, MeshError> {
+        self.skip_whitespace();
+        let start = self.offset;
+        while self
+            .bytes
+            .get(self.offset)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.offset += 1;
+        }
+        if start == self.offset {
+            return Err(invalid_section());
+        }
+        std::str::from_utf8(&self.bytes[start..self.offset])
+            .map_err(|_| MeshError::FormatError("Unable to process string."))
+    }
+
+    /// Parses the next word of text, failing with `error`.
+    fn parse<U: FromStr>(&mut self, error: &'static str) -> Result<U, MeshError> {
+        self.word()?
+            .parse()
+            .map_err(|_| MeshError::FormatError(error))
+    }
+
+    /// Reads `N` bytes of binary data, reordered to be little-endian.
+    fn raw<const N: usize>(&mut self) -> Result<[u8; N], MeshError> {
+        let mut raw: [u8; N] = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .and_then(|raw| raw.try_into().ok())
+            .ok_or_else(invalid_section)?;
+        self.offset += N;
+        if self.big_endian {
+            raw.reverse();
+        }
+        Ok(raw)
+    }
+
+    /// Reads an `int`.
+    fn int(&mut self) -> Result<i32, MeshError> {
+        if self.binary {
+            Ok(i32::from_le_bytes(self.raw()?))
+        } else {
+            self.parse("Failed to parse integer.")
+        }
+    }
+
+    /// Reads a `size_t`, which binary files store in 8 bytes.
+    fn size(&mut self) -> Result<usize, MeshError> {
+        if self.binary {
+            usize::try_from(u64::from_le_bytes(self.raw()?)).map_err(|_| invalid_section())
+        } else {
+            self.parse("Failed to parse integer.")
+        }
+    }
+
+    /// Reads a count stored as an `int`, which may not be negative.
+    fn count(&mut self) -> Result<usize, MeshError> {
+        usize::try_from(self.int()?).map_err(|_| invalid_section())
+    }
+
+    /// Reads a node or element tag.
+    fn tag(&mut self) -> Result<usize, MeshError> {
+        if self.version2 {
+            self.count()
+        } else {
+            self.size()
+        }
+    }
+
+    /// Reads a `double`.
+    fn float(&mut self) -> Result<f64, MeshError> {
+        if self.binary {
+            Ok(f64::from_le_bytes(self.raw()?))
+        } else {
+            self.parse("Failed to parse float.")
+        }
+    }
+
+    /// Reads a count written as text, which version 2 files do even when binary, along with the
+    /// line break after it.
+    fn text_count(&mut self) -> Result<usize, MeshError> {
+        let count = self.parse("Failed to parse integer.")?;
+        self.line()?;
+        Ok(count)
+    }
+
+    /// Reads the line closing the section called `name`.
+    fn end(&mut self, name: &str) -> Result<(), MeshError> {
+        // Binary data is followed by a line break before the end of the section.
+        self.skip_whitespace();
+        match self.line()?.strip_prefix("$End") {
+            Some(end) if end == name => Ok(()),
+            _ => Err(invalid_section()),
+        }
+    }
+}
+
+/// Builds an `Msh` from the sections of a file as they are read.
+struct Parser<'a> {
+    reader: Reader<'a>,
+    msh: Msh,
+    // The index of each node tag.
+    node_index: HashMap<usize, usize>,
+    // The first physical tag of each entity, keyed by its dimension and tag.
+    entities: HashMap<(i32, i32), i32>,
+    // The names of physical groups, keyed by their dimension and tag.
+    names: HashMap<(i32, i32), String>,
+    // The triangles and tetrahedra read so far.
+    elements: Vec<Element>,
+}
+
+/// A triangle or tetrahedron as read from a file, with its nodes numbered from zero.
+struct Element {
+    tag: usize,
+    dimension: i32,
+    physical: i32,
+    nodes: Vec<usize>,
+}
+
+impl Parser<'_> {
+    /// Reads the `$PhysicalNames` section, which is text even in binary files.
+    fn physical_names(&mut self) -> Result<(), MeshError> {
+        let count: usize = self.reader.parse("Failed to parse integer.")?;
+        for _ in 0..count {
+            let dimension = self.reader.parse("Failed to parse integer.")?;
+            let tag = self.reader.parse("Failed to parse integer.")?;
+            let name = self.reader.line()?.trim().trim_matches('"');
+            self.names.insert((dimension, tag), name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Reads the `$Entities` section of a version 4 file, keeping the first physical tag of
+    /// each entity.
+    fn entities(&mut self) -> Result<(), MeshError> {
+        let mut counts = [0; 4];
+        for count in &mut counts {
+            *count = self.reader.size()?;
+        }
+        for (dimension, count) in (0..).zip(counts) {
+            for _ in 0..count {
+                let tag = self.reader.int()?;
+                // Points have a position, and everything else a bounding box.
+                for _ in 0..if dimension == 0 { 3 } else { 6 } {
+                    self.reader.float()?;
+                }
+                let physicals = self.reader.size()?;
+                for p in 0..physicals {
+                    let physical = self.reader.int()?;
+                    if p == 0 {
+                        self.entities.insert((dimension, tag), physical);
+                    }
+                }
+                if dimension > 0 {
+                    for _ in 0..self.reader.size()? {
+                        self.reader.int()?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the node with `tag` at `position`.
+    fn add_node(&mut self, tag: usize, position: [f64; 3]) {
+        self.node_index.insert(tag, self.msh.nodes.len());
+        self.msh.nodes.push(position);
+    }
+
+    /// Reads the `$Nodes` section of a version 2 file.
+    fn nodes_v2(&mut self) -> Result<(), MeshError> {
+        for _ in 0..self.reader.text_count()? {
+            let tag = self.reader.tag()?;
+            let position = [
+                self.reader.float()?,
+                self.reader.float()?,
+                self.reader.float()?,
+            ];
+            self.add_node(tag, position);
+        }
+        Ok(())
+    }
+
+    /// Reads the `$Nodes` section of a version 4 file, where nodes come in blocks that each
+    /// belong to an entity.
+    fn nodes_v4(&mut self) -> Result<(), MeshError> {
+        let blocks = self.reader.size()?;
+        for _ in 0..3 {
+            self.reader.size()?;
+        }
+        for _ in 0..blocks {
+            let dimension = self.reader.int()?;
+            self.reader.int()?;
+            let parametric = self.reader.int()? != 0;
+            let count = self.reader.size()?;
+            let tags = (0..count)
+                .map(|_| self.reader.size())
+                .collect::<Result<Vec<usize>, MeshError>>()?;
+            for tag in tags {
+                let position = [
+                    self.reader.float()?,
+                    self.reader.float()?,
+                    self.reader.float()?,
+                ];
+                // Nodes on curves and surfaces may be followed by their parametric coordinates.
+                if parametric {
+                    for _ in 0..dimension {
+                        self.reader.float()?;
+                    }
+                }
+                self.add_node(tag, position);
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    Adds an element, keeping only triangles and tetrahedra.
+
+    - Element references an unknown node: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+
+    Parameters:
+    - `tag: usize` - The tag of the element.
+    - `element_type: i32` - The Gmsh type of the element.
+    - `physical: i32` - The physical tag of the element, or 0 if it has none.
+    - `nodes: &[usize]` - The tags of the nodes of the element.
+
+    Returns:
+    - `Result<(), MeshError>` - Nothing if the element was added or skipped, else a `MeshError`.
+     */
+    fn add_element(
+        &mut self,
+        tag: usize,
+        element_type: i32,
+        physical: i32,
+        nodes: &[usize],
+    ) -> Result<(), MeshError> {
+        let dimension = match element_type {
+            TRIANGLE => 2,
+            TETRAHEDRON => 3,
+            _ => return Ok(()),
+        };
+        let nodes = nodes
+            .iter()
+            .map(|node| {
+                self.node_index
+                    .get(node)
+                    .copied()
+                    .ok_or(MeshError::IndexingError("Vertex not contained in mesh."))
+            })
+            .collect::<Result<Vec<usize>, MeshError>>()?;
+        self.elements.push(Element {
+            tag,
+            dimension,
+            physical,
+            nodes,
+        });
+        Ok(())
+    }
+
+    /**
+    Sorts the elements by their tags and gathers them into an `Msh`, with the physical groups
+    named. Elements with the same nodes as an earlier one are dropped, which merges the copies
+    version 2 files hold of elements in several physical groups. Physical groups without a name
+    are named after their tag.
+
+    Returns:
+    - `Msh` - The contents of the file.
+     */
+    fn finish(mut self) -> Msh {
+        self.elements.sort_by_key(|element| element.tag);
+        let mut msh = self.msh;
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut group_index: HashMap<(i32, i32), usize> = HashMap::new();
+        for element in self.elements {
+            let mut key = element.nodes.clone();
+            key.sort_unstable();
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let dimension = element.dimension;
+            let group = (element.physical != 0).then(|| {
+                *group_index
+                    .entry((dimension, element.physical))
+                    .or_insert_with(|| {
+                        let name = self.names.get(&(dimension, element.physical));
+                        msh.groups.push(PhysicalGroup {
+                            dimension,
+                            tag: element.physical,
+                            name: name.cloned().unwrap_or(element.physical.to_string()),
+                        });
+                        msh.groups.len() - 1
+                    })
+            });
+            let nodes = element.nodes;
+            if dimension == 2 {
+                msh.triangles.push([nodes[0], nodes[1], nodes[2]]);
+                msh.triangle_groups.push(group);
+            } else {
+                msh.tetrahedra
+                    .push([nodes[0], nodes[1], nodes[2], nodes[3]]);
+                msh.tetrahedron_groups.push(group);
+            }
+        }
+        msh
+    }
+
+    /// Reads the tags and nodes of a version 2 element whose tag, type and tag count are known.
+    fn element_v2(
+        &mut self,
+        tag: usize,
+        element_type: i32,
+        tag_count: usize,
+    ) -> Result<(), MeshError> {
+        let nodes = node_count(element_type)
+            .ok_or(MeshError::FormatError("Unsupported MSH element type."))?;
+        let mut physical = 0;
+        for t in 0..tag_count {
+            let tag = self.reader.int()?;
+            if t == 0 {
+                physical = tag;
+            }
+        }
+        let nodes = (0..nodes)
+            .map(|_| self.reader.tag())
+            .collect::<Result<Vec<usize>, MeshError>>()?;
+        self.add_element(tag, element_type, physical, &nodes)
+    }
+
+    /// Reads the `$Elements` section of a version 2 file. Binary files group elements into
+    /// blocks sharing a header, while text files give the header of every element.
+    fn elements_v2(&mut self) -> Result<(), MeshError> {
+        let count = self.reader.text_count()?;
+        let mut read = 0;
+        while read < count {
+            if self.reader.binary {
+                let element_type = self.reader.int()?;
+                let block = self.reader.count()?;
+                let tag_count = self.reader.count()?;
+                if block == 0 {
+                    return Err(invalid_section());
+                }
+                for _ in 0..block {
+                    let tag = self.reader.tag()?;
+                    self.element_v2(tag, element_type, tag_count)?;
+                }
+                read += block;
+            } else {
+                let tag = self.reader.tag()?;
+                let element_type = self.reader.int()?;
+                let tag_count = self.reader.count()?;
+                self.element_v2(tag, element_type, tag_count)?;
+                read += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the `$Elements` section of a version 4 file, where elements come in blocks that each
+    /// belong to an entity, whose physical group they share.
+    fn elements_v4(&mut self) -> Result<(), MeshError> {
+        let blocks = self.reader.size()?;
+        for _ in 0..3 {
+            self.reader.size()?;
+        }
+        for _ in 0..blocks {
+            let dimension = self.reader.int()?;
+            let entity = self.reader.int()?;
+            let element_type = self.reader.int()?;
+            let count = self.reader.size()?;
+            let nodes = node_count(element_type)
+                .ok_or(MeshError::FormatError("Unsupported MSH element type."))?;
+            let physical = self
+                .entities
+                .get(&(dimension, entity))
+                .copied()
+                .unwrap_or(0);
+            for _ in 0..count {
+                let tag = self.reader.size()?;
+                let nodes = (0..nodes)
+                    .map(|_| self.reader.size())
+                    .collect::<Result<Vec<usize>, MeshError>>()?;
+                self.add_element(tag, element_type, physical, &nodes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Reads the triangles and tetrahedra of an MSH file. Sections other than the mesh format, physical
+names, entities, nodes and elements are skipped, as are elements other than triangles and
+tetrahedra. Elements are ordered by their tags.
+
+- Missing or invalid `$MeshFormat` section: `MeshError::FormatError("Invalid MSH header.")`
+- Version other than 2 or 4.1: `MeshError::FormatError("Unsupported MSH version.")`
+- Sections not matching the format: `MeshError::FormatError("Invalid MSH section.")`
+- Numbers cannot be processed: `MeshError::FormatError("Failed to parse float.")` or
+  `MeshError::FormatError("Failed to parse integer.")`
+- Element type whose node count is unknown: `MeshError::FormatError("Unsupported MSH element
+  type.")`
+- Element references an unknown node: `MeshError::IndexingError("Vertex not contained in mesh.")`
+
+Parameters:
+- `filename: &str` - A string containing the file path to load.
+
+Returns:
+- `Result<Msh, MeshError>` - The contents of the file, else a `MeshError`.
+ */
+fn read_msh(filename: &str) -> Result<Msh, MeshError> {
+    let mut bytes = Vec::new();
+    open_file(filename)?
+        .read_to_end(&mut bytes)
+        .map_err(|e| MeshError::file("Could not read file.", filename, e))?;
+    parse_msh(&bytes)
+}
+
+/// Parses the contents of an MSH file, as `read_msh` does.
+fn parse_msh(bytes: &[u8]) -> Result<Msh, MeshError> {
+    let header = || MeshError::FormatError("Invalid MSH header.");
+    let mut reader = Reader {
+        bytes,
+        offset: 0,
+        binary: false,
+        big_endian: false,
+        version2: false,
+    };
+    if reader.line().ok() != Some("$MeshFormat") {
+        return Err(header());
+    }
+    let version = match reader.line()?.split_ascii_whitespace().collect::<Vec<_>>()[..] {
+        [version, "0", "8"] => version,
+        [version, "1", "8"] => {
+            reader.binary = true;
+            version
+        }
+        _ => return Err(header()),
+    };
+    reader.version2 = match version {
+        "2" | "2.0" | "2.1" | "2.2" => true,
+        "4.1" => false,
+        _ => return Err(MeshError::FormatError("Unsupported MSH version.")),
+    };
+    if reader.binary {
+        // Binary files hold the integer 1, written in the byte order of the rest of the file.
+        let one = reader.raw::<4>()?;
+        reader.big_endian = match (i32::from_le_bytes(one), i32::from_be_bytes(one)) {
+            (1, _) => false,
+            (_, 1) => true,
+            _ => return Err(header()),
+        };
+    }
+    reader.end("MeshFormat").map_err(|_| header())?;
+
+    let mut parser = Parser {
+        reader,
+        msh: Msh::default(),
+        node_index: HashMap::new(),
+        entities: HashMap::new(),
+        names: HashMap::new(),
+        elements: Vec::new(),
+    };
+    while !parser.reader.at_end() {
+        let section = parser.reader.line()?;
+        let name = section.strip_prefix('$').ok_or_else(invalid_section)?;
+        let version2 = parser.reader.version2;
+        match name {
+            "PhysicalNames" => parser.physical_names()?,
+            "Entities" if !version2 => parser.entities()?,
+            "Nodes" if version2 => parser.nodes_v2()?,
+            "Nodes" => parser.nodes_v4()?,
+            "Elements" if version2 => parser.elements_v2()?,
+            "Elements" => parser.elements_v4()?,
+            _ => {
+                let end = format!("$End{}", name);
+                while parser.reader.line()? != end {}
+                continue;
+            }
+        }
+        parser.reader.end(name)?;
+    }
+
+    Ok(parser.finish())
+}
+
+/// An elementary entity of a file being written: the elements of one dimension sharing a physical
+/// group.
+struct Entity {
+    tag: i32,
+    physical: Option<i32>,
+    elements: Vec<usize>,
+}
+
+/// The elements of an entity being written, with their element type and their tags and nodes.
+type Block<'a> = (&'a Entity, i32, Vec<(usize, &'a [usize])>);
+
+/**
+Splits the elements of `dimension` into entities, one for each physical group with elements,
+followed by one for the elements in no group.
+
+Parameters:
+- `msh: &Msh` - The contents of the file.
+- `dimension: i32` - The dimension of the elements, 2 for triangles or 3 for tetrahedra.
+- `element_groups: &[Option<usize>]` - The physical group of each element.
+
+Returns:
+- `Vec<Entity>` - The entities, tagged from 1.
+ */
+fn entities_of(msh: &Msh, dimension: i32, element_groups: &[Option<usize>]) -> Vec<Entity> {
+    let mut groups: Vec<Option<usize>> = (0..msh.groups.len())
+        .filter(|&g| msh.groups[g].dimension == dimension)
+        .map(Some)
+        .collect();
+    groups.push(None);
+
+    let mut entities = Vec::new();
+    for group in groups {
+        let elements: Vec<usize> = (0..element_groups.len())
+            .filter(|&e| element_groups[e] == group)
+            .collect();
+        if !elements.is_empty() {
+            entities.push(Entity {
+                tag: entities.len() as i32 + 1,
+                physical: group.map(|g| msh.groups[g].tag),
+                elements,
+            });
+        }
+    }
+    entities
+}
+
+/// Returns the smallest and largest coordinates of `nodes`, which are zero if there are none.
+fn bounding_box<'a>(nodes: impl Iterator<Item = &'a [f64; 3]>) -> [f64; 6] {
+    let mut bounds: Option<[f64; 6]> = None;
+    for p in nodes {
+        let b = bounds.get_or_insert([p[0], p[1], p[2], p[0], p[1], p[2]]);
+        for k in 0..3 {
+            b[k] = b[k].min(p[k]);
+            b[k + 3] = b[k + 3].max(p[k]);
+        }
+    }
+    bounds.unwrap_or([0.; 6])
+}
+
+/// The contents of an MSH file being written, which lays values out in the requested encoding.
+struct MshWriter {
+    binary: bool,
+    bytes: Vec<u8>,
+    line: Vec<String>,
+}
+
+impl MshWriter {
+    /// Writes a line of text, which every encoding uses for section headers.
+    fn text(&mut self, text: &str) {
+        self.bytes.extend(text.bytes());
+        self.bytes.push(b'\n');
+    }
+
+    /// Writes the line closing the section called `name`.
+    fn end(&mut self, name: &str) {
+        if self.binary {
+            self.bytes.push(b'\n');
+        }
+        self.text(&format!("$End{}", name));
+    }
+
+    /// Writes an `int`.
+    fn int(&mut self, value: i32) {
+        if self.binary {
+            self.bytes.extend(value.to_le_bytes());
+        } else {
+            self.line.push(value.to_string());
+        }
+    }
+
+    /// Writes a `size_t`.
+    fn size(&mut self, value: usize) {
+        if self.binary {
+            self.bytes.extend((value as u64).to_le_bytes());
+        } else {
+            self.line.push(value.to_string());
+        }
+    }
+
+    /// Writes a `double`.
+    fn float(&mut self, value: f64) {
+        if self.binary {
+            self.bytes.extend(value.to_le_bytes());
+        } else {
+            self.line.push(value.to_string());
+        }
+    }
+
+    /// Finishes a line of values, which only text files break into lines.
+    fn end_line(&mut self) {
+        if !self.binary {
+            let line = self.line.join(" ");
+            self.text(&line);
+            self.line.clear();
+        }
+    }
+}
+
+/**
+Writes `msh` to the MSH file `filename`. Each physical group is written as an entity of its own,
+and the elements in no group as one more entity of their dimension. Nodes are tagged from 1 in
+order, as are the triangles followed by the tetrahedra.
+
+Parameters:
+- `filename: &str` - A string containing the filename to save the mesh to.
+- `msh: &Msh` - The contents of the file.
+- `format: GmshFormat` - The version and encoding of the file.
+
+Returns:
+- `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is successful,
+  otherwise `MeshError::FileError`.
+ */
+fn write_msh(filename: &str, msh: &Msh, format: GmshFormat) -> Result<usize, MeshError> {
+    let bytes = encode_msh(msh, format);
+    write_file(filename, |file| file.write_all(&bytes).map(|_| bytes.len()))
+}
+
+/// Encodes the contents of an MSH file in `format`, as `write_msh` writes them.
+fn encode_msh(msh: &Msh, format: GmshFormat) -> Vec<u8> {
+    let version2 = matches!(format, GmshFormat::AsciiV2 | GmshFormat::BinaryV2);
+    let mut writer = MshWriter {
+        binary: matches!(format, GmshFormat::BinaryV2 | GmshFormat::BinaryV4),
+        bytes: Vec::new(),
+        line: Vec::new(),
+    };
+
+    writer.text("$MeshFormat");
+    writer.text(&format!(
+        "{} {} 8",
+        if version2 { "2.2" } else { "4.1" },
+        writer.binary as u8
+    ));
+    if writer.binary {
+        writer.int(1);
+    }
+    writer.end("MeshFormat");
+
+    if !msh.groups.is_empty() {
+        writer.text("$PhysicalNames");
+        writer.text(&msh.groups.len().to_string());
+        for group in &msh.groups {
+            writer.text(&format!(
+                "{} {} \"{}\"",
+                group.dimension, group.tag, group.name
+            ));
+        }
+        writer.end("PhysicalNames");
+    }
+
+    let surfaces = entities_of(msh, 2, &msh.triangle_groups);
+    let mut volumes = entities_of(msh, 3, &msh.tetrahedron_groups);
+    // Version 4 files place every node in a single block, which has to belong to some entity.
+    if !version2 && surfaces.is_empty() && volumes.is_empty() {
+        volumes.push(Entity {
+            tag: 1,
+            physical: None,
+            elements: Vec::new(),
+        });
+    }
+    // Elements are tagged in order, triangles first, so reading the file back restores the order.
+    let blocks: Vec<Block> = surfaces
+        .iter()
+        .map(|s| {
+            let triangles = s.elements.iter().map(|&e| (e + 1, &msh.triangles[e][..]));
+            (s, TRIANGLE, triangles.collect())
+        })
+        .chain(volumes.iter().map(|v| {
+            let offset = msh.triangles.len() + 1;
+            let tetrahedra = v
+                .elements
+                .iter()
+                .map(|&e| (e + offset, &msh.tetrahedra[e][..]));
+            (v, TETRAHEDRON, tetrahedra.collect())
+        }))
+        .collect();
+    let dimension = |element_type: i32| if element_type == TRIANGLE { 2 } else { 3 };
+    let element_count = msh.triangles.len() + msh.tetrahedra.len();
+
+    if version2 {
+        writer.text("$Nodes");
+        writer.text(&msh.nodes.len().to_string());
+        for (v, node) in msh.nodes.iter().enumerate() {
+            writer.int(v as i32 + 1);
+            node.iter().for_each(|&x| writer.float(x));
+            writer.end_line();
+        }
+        writer.end("Nodes");
+
+        // Elements carry their physical tag followed by the tag of their entity.
+        writer.text("$Elements");
+        writer.text(&element_count.to_string());
+        for (entity, element_type, elements) in &blocks {
+            if writer.binary {
+                writer.int(*element_type);
+                writer.int(elements.len() as i32);
+                writer.int(2);
+            }
+            for (tag, nodes) in elements {
+                writer.int(*tag as i32);
+                if !writer.binary {
+                    writer.int(*element_type);
+                    writer.int(2);
+                }
+                writer.int(entity.physical.unwrap_or(0));
+                writer.int(entity.tag);
+                nodes.iter().for_each(|&v| writer.int(v as i32 + 1));
+                writer.end_line();
+            }
+        }
+        writer.end("Elements");
+    } else {
+        writer.text("$Entities");
+        for count in [0, 0, surfaces.len(), volumes.len()] {
+            writer.size(count);
+        }
+        writer.end_line();
+        for (entity, _, elements) in &blocks {
+            writer.int(entity.tag);
+            let nodes = elements
+                .iter()
+                .flat_map(|(_, nodes)| nodes.iter().map(|&v| &msh.nodes[v]));
+            bounding_box(nodes).iter().for_each(|&x| writer.float(x));
+            writer.size(entity.physical.iter().len());
+            entity.physical.iter().for_each(|&p| writer.int(p));
+            writer.size(0);
+            writer.end_line();
+        }
+        writer.end("Entities");
+
+        let (host, element_type, _) = blocks.last().unwrap();
+        writer.text("$Nodes");
+        for size in [1, msh.nodes.len(), 1, msh.nodes.len()] {
+            writer.size(size);
+        }
+        writer.end_line();
+        writer.int(dimension(*element_type));
+        writer.int(host.tag);
+        writer.int(0);
+        writer.size(msh.nodes.len());
+        writer.end_line();
+        for v in 0..msh.nodes.len() {
+            writer.size(v + 1);
+            writer.end_line();
+        }
+        for node in &msh.nodes {
+            node.iter().for_each(|&x| writer.float(x));
+            writer.end_line();
+        }
+        writer.end("Nodes");
+
+        writer.text("$Elements");
+        let filled = blocks
+            .iter()
+            .filter(|(_, _, elements)| !elements.is_empty());
+        for size in [filled.clone().count(), element_count, 1, element_count] {
+            writer.size(size);
+        }
+        writer.end_line();
+        for (entity, element_type, elements) in filled {
+            writer.int(dimension(*element_type));
+            writer.int(entity.tag);
+            writer.int(*element_type);
+            writer.size(elements.len());
+            writer.end_line();
+            for (tag, nodes) in elements {
+                writer.size(*tag);
+                nodes.iter().for_each(|&v| writer.size(v + 1));
+                writer.end_line();
+            }
+        }
+        writer.end("Elements");
+    }
+    writer.bytes
+}
+
+impl TriangleMesh {
+    /**
+    Loads a `TriangleMesh` from the triangles of the MSH file passed in, with coordinates stored
+    as `Float`. Use `load_msh_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh>, MeshError>` - Returns the `Box<TriangleMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_msh(filename: &str) -> Result<Box<TriangleMesh>, MeshError> {
+        TriangleMesh::load_msh_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Loads a `TriangleMesh` from the triangles of the version 2 or 4.1 MSH file passed in, with
+    coordinates stored as `T`. Each physical surface becomes a patch, and nodes used by no
+    triangle are dropped.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh<T>>, MeshError>` - Returns the `Box<TriangleMesh<T>>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_msh_with_precision(filename: &str) -> Result<Box<TriangleMesh<T>>, MeshError> {
+        TriangleMesh::from_msh(read_msh(filename)?)
+    }
+
+    /// Reads a `TriangleMesh` from the contents of an MSH file, as `load_msh_with_precision` does.
+    pub(crate) fn parse_msh(bytes: &[u8]) -> Result<Box<TriangleMesh<T>>, MeshError> {
+        TriangleMesh::from_msh(parse_msh(bytes)?)
+    }
+
+    /// Builds a `TriangleMesh` from the triangles of a parsed MSH file.
+    fn from_msh(msh: Msh) -> Result<Box<TriangleMesh<T>>, MeshError> {
+        let (nodes, index) = msh.compact(msh.triangles.iter().flatten().copied());
+
+        let mut mesh = TriangleMesh {
+            vertices: Vec::with_capacity(nodes.len()),
+            faces: Vec::with_capacity(msh.triangles.len()),
+            face_normals: Vec::with_capacity(msh.triangles.len()),
+            patches: Vec::new(),
+        };
+        for node in nodes {
+            mesh.add_vertex(Point3::from(node.map(nalgebra::convert)));
+        }
+        for triangle in &msh.triangles {
+            mesh.add_face(&triangle.map(|v| index[v]), None)?;
+        }
+        mesh.patches = msh
+            .groups_of(2, &msh.triangle_groups)
+            .into_iter()
+            .map(|(name, faces)| FaceGroup { name, faces })
+            .collect();
+        Ok(Box::new(mesh))
+    }
+
+    /**
+    Writes a `TriangleMesh` to the MSH file passed in, with each patch as a physical surface.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: GmshFormat` - The version and encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_msh(&self, filename: &str, format: GmshFormat) -> Result<usize, MeshError> {
+        write_msh(filename, &self.to_msh(), format)
+    }
+
+    /**
+    Writes a `TriangleMesh` as MSH data to the writer passed in, as `write_msh` does.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the MSH data to.
+    - `format: GmshFormat` - The version and encoding of the data.
+
+    Returns:
+    - `Result<usize, Error>` - Returns the number of bytes written if writing is successful
+      otherwise returns an `std::io::Error`.
+     */
+    pub(crate) fn write_msh_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        format: GmshFormat,
+    ) -> Result<usize, Error> {
+        let bytes = encode_msh(&self.to_msh(), format);
+        writer.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Lays out the mesh as the contents of an MSH file, with each patch as a physical surface.
+    fn to_msh(&self) -> Msh {
+        let mut msh = Msh {
+            nodes: self
+                .vertices
+                .iter()
+                .map(|p| [p.x, p.y, p.z].map(|x| x.to_subset_unchecked()))
+                .collect(),
+            triangles: self.faces.clone(),
+            triangle_groups: vec![None; self.faces.len()],
+            ..Default::default()
+        };
+        let patches: Vec<(&str, &[usize])> = self
+            .patches
+            .iter()
+            .map(|p| (p.name.as_str(), &p.faces[..]))
+            .collect();
+        msh.add_groups(2, &patches);
+        msh
+    }
+}
+
+impl TetrahedralMesh {
+    /**
+    Loads a `TetrahedralMesh` from the MSH file passed in, with coordinates stored as `Float`. Use
+    `load_msh_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TetrahedralMesh>, MeshError>` - Returns the `Box<TetrahedralMesh>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_msh(filename: &str) -> Result<Box<TetrahedralMesh>, MeshError> {
+        TetrahedralMesh::load_msh_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy> TetrahedralMesh<T> {
+    /**
+    Loads a `TetrahedralMesh` from the version 2 or 4.1 MSH file passed in, with coordinates
+    stored as `T`. Tetrahedra are reordered to have positive volume where needed. Triangles become
+    the boundary faces of the mesh, each with its index among the triangles of the file as its
+    source, and physical surfaces and volumes become patches and regions. Nodes used by no
+    triangle or tetrahedron are dropped. The mesh is built by `TetrahedralMesh::new`, so files
+    with a flat tetrahedron fail with `MeshError::FormatError("Tetrahedron does not have positive
+    volume.")`, and those with a face shared by more than two tetrahedra with
+    `MeshError::FormatError("Face shared by more than two tetrahedra.")`.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TetrahedralMesh<T>>, MeshError>` - Returns the `Box<TetrahedralMesh<T>>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_msh_with_precision(filename: &str) -> Result<Box<TetrahedralMesh<T>>, MeshError> {
+        let msh = read_msh(filename)?;
+        let used = msh.tetrahedra.iter().flatten();
+        let (nodes, index) = msh.compact(used.chain(msh.triangles.iter().flatten()).copied());
+
+        let tetrahedra = msh
+            .tetrahedra
+            .iter()
+            .map(|tet| {
+                let [a, b, c, d] = tet.map(|v| nalgebra::Point3::from(msh.nodes[v]));
+                let volume = (b - a).cross(&(c - a)).dot(&(d - a));
+                let tet = tet.map(|v| index[v]);
+                if volume < 0. {
+                    [tet[0], tet[2], tet[1], tet[3]]
+                } else {
+                    tet
+                }
+            })
+            .collect();
+        let vertices = nodes
+            .iter()
+            .map(|p| Point3::from(p.map(nalgebra::convert)))
+            .collect();
+
+        let mut mesh = TetrahedralMesh::new(vertices, tetrahedra)?;
+        mesh.boundary_faces = msh
+            .triangles
+            .iter()
+            .enumerate()
+            .map(|(source, triangle)| BoundaryFace {
+                vertices: triangle.map(|v| index[v]),
+                source,
+            })
+            .collect();
+        mesh.patches = msh
+            .groups_of(2, &msh.triangle_groups)
+            .into_iter()
+            .map(|(name, faces)| FaceGroup { name, faces })
+            .collect();
+        mesh.regions = msh
+            .groups_of(3, &msh.tetrahedron_groups)
+            .into_iter()
+            .map(|(name, cells)| CellGroup { name, cells })
+            .collect();
+        Ok(Box::new(mesh))
+    }
+
+    /**
+    Writes a `TetrahedralMesh` to the MSH file passed in, with its patches as physical surfaces
+    and its regions as physical volumes. The boundary faces of the mesh are written as triangles,
+    or, if it has none, the faces belonging to a single tetrahedron.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: GmshFormat` - The version and encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_msh(&self, filename: &str, format: GmshFormat) -> Result<usize, MeshError> {
+        let triangles = if self.boundary_faces.is_empty() {
+            self.extract_boundary_faces()
+        } else {
+            self.boundary_faces.iter().map(|f| f.vertices).collect()
+        };
+        let mut msh = Msh {
+            nodes: self
+                .vertices
+                .iter()
+                .map(|p| [p.x, p.y, p.z].map(|x| x.to_subset_unchecked()))
+                .collect(),
+            triangle_groups: vec![None; triangles.len()],
+            triangles,
+            tetrahedron_groups: vec![None; self.tetrahedra.len()],
+            tetrahedra: self.tetrahedra.clone(),
+            groups: Vec::new(),
+        };
+        let patches: Vec<(&str, &[usize])> = self
+            .patches
+            .iter()
+            .map(|p| (p.name.as_str(), &p.faces[..]))
+            .collect();
+        msh.add_groups(2, &patches);
+        let regions: Vec<(&str, &[usize])> = self
+            .regions
+            .iter()
+            .map(|r| (r.name.as_str(), &r.cells[..]))
+            .collect();
+        msh.add_groups(3, &regions);
+        write_msh(filename, &msh, format)
+    }
+}
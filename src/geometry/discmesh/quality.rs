@@ -0,0 +1,239 @@
+/*! Shape quality metrics for tetrahedral meshes. Every tetrahedron is measured on its own, and the
+measurements are summarized across the mesh so it can be judged at a glance. */
+
+use nalgebra::RealField;
+
+use crate::geometry::discmesh::{CellMesh, TetrahedralMesh, Tetrahedron};
+use crate::{Float, Uint};
+
+/// The number of bins in every histogram of a `QualityReport`.
+pub const HISTOGRAM_BINS: usize = 10;
+
+/// The quality metrics of a single tetrahedron. Metrics other than `inverted` ignore the
+/// orientation of the tetrahedron.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellQuality<T: RealField + Copy = Float> {
+    /// The longest edge over the inradius, scaled so a regular tetrahedron scores 1. Flat
+    /// tetrahedra score infinity.
+    pub aspect_ratio: T,
+    /// Three times the inradius over the circumradius, which is 1 for a regular tetrahedron and 0
+    /// for a flat one.
+    pub radius_ratio: T,
+    /// The smallest dihedral angle, in degrees.
+    pub min_dihedral: T,
+    /// The largest dihedral angle, in degrees.
+    pub max_dihedral: T,
+    /// How far the volume falls short of a regular tetrahedron with the same circumsphere, from 0
+    /// for a regular tetrahedron to 1 for a flat one.
+    pub skewness: T,
+    /// The largest ratio between the volume of the tetrahedron and that of a neighbour, taken so
+    /// that it is at least 1. Tetrahedra without neighbours score 1.
+    pub volume_ratio: T,
+    /// Whether the tetrahedron has zero or negative signed volume.
+    pub inverted: bool,
+}
+
+/// A histogram of a metric, with `HISTOGRAM_BINS` equally wide bins spanning `min..=max`. Values
+/// outside the span are counted in the first or last bin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram<T: RealField + Copy = Float> {
+    pub min: T,
+    pub max: T,
+    pub counts: Vec<usize>,
+}
+
+/// Summary statistics of a metric over every tetrahedron of a mesh. All values are zero for a
+/// mesh without tetrahedra.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricSummary<T: RealField + Copy = Float> {
+    pub min: T,
+    pub max: T,
+    pub mean: T,
+    pub histogram: Histogram<T>,
+}
+
+/// The quality of every tetrahedron in a mesh, along with summaries of each metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityReport<T: RealField + Copy = Float> {
+    /// The quality of each tetrahedron, in index order.
+    pub cells: Vec<CellQuality<T>>,
+    /// The indices of the inverted tetrahedra.
+    pub inverted: Vec<Uint>,
+    pub aspect_ratio: MetricSummary<T>,
+    pub radius_ratio: MetricSummary<T>,
+    pub min_dihedral: MetricSummary<T>,
+    pub max_dihedral: MetricSummary<T>,
+    pub skewness: MetricSummary<T>,
+    pub volume_ratio: MetricSummary<T>,
+}
+
+/// Converts a constant to the scalar type of a mesh.
+fn constant<T: RealField + Copy>(value: f64) -> T {
+    nalgebra::convert(value)
+}
+
+impl<T: RealField + Copy> Histogram<T> {
+    /**
+    Bins `values` into `HISTOGRAM_BINS` equally wide bins spanning `min..=max`.
+
+    Parameters:
+    - `values: &[T]` - The values to count.
+    - `min: T` - The lower end of the first bin.
+    - `max: T` - The upper end of the last bin.
+
+    Returns:
+    - `Histogram<T>` - The binned values.
+     */
+    pub fn new(values: &[T], min: T, max: T) -> Histogram<T> {
+        let mut counts = vec![0; HISTOGRAM_BINS];
+        let width = (max - min) / constant(HISTOGRAM_BINS as f64);
+        for &value in values {
+            let bin = if width > T::zero() {
+                let bin: f64 = ((value - min) / width).floor().to_subset_unchecked();
+                bin.clamp(0., (HISTOGRAM_BINS - 1) as f64) as usize
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+        Histogram { min, max, counts }
+    }
+}
+
+impl<T: RealField + Copy> MetricSummary<T> {
+    /// Summarizes `values`, binning them over `min..=max`. Either end defaults to the extreme
+    /// finite value when `None`.
+    fn new(values: &[T], min: Option<T>, max: Option<T>) -> MetricSummary<T> {
+        if values.is_empty() {
+            return MetricSummary {
+                min: T::zero(),
+                max: T::zero(),
+                mean: T::zero(),
+                histogram: Histogram::new(values, T::zero(), T::zero()),
+            };
+        }
+
+        let (infinity, zero) = (constant::<T>(f64::INFINITY), T::zero());
+        let finite = values.iter().copied().filter(|v| v.is_finite());
+        let lowest = finite.clone().fold(infinity, T::min);
+        let highest = finite.fold(-infinity, T::max);
+        let span = (
+            min.unwrap_or(if lowest.is_finite() { lowest } else { zero }),
+            max.unwrap_or(if highest.is_finite() { highest } else { zero }),
+        );
+
+        MetricSummary {
+            min: values.iter().copied().fold(infinity, T::min),
+            max: values.iter().copied().fold(-infinity, T::max),
+            mean: values.iter().fold(zero, |sum, &v| sum + v) / constant(values.len() as f64),
+            histogram: Histogram::new(values, span.0, span.1),
+        }
+    }
+}
+
+impl<T: RealField + Copy> CellQuality<T> {
+    /// Measures the tetrahedron `tet`, whose volume ratio to its neighbours is `volume_ratio`.
+    fn new(tet: &Tetrahedron<T>, volume_ratio: T) -> CellQuality<T> {
+        let (zero, one, infinity) = (T::zero(), T::one(), constant::<T>(f64::INFINITY));
+        let points = tet.get_points();
+        let longest = (0..4)
+            .flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+            .map(|(i, j)| (points[j] - points[i]).norm())
+            .fold(zero, T::max);
+        let inradius = tet.inradius();
+        let circumradius = tet.circumradius().filter(|r| r.is_finite() && *r > zero);
+
+        // A regular tetrahedron with edge `a` has inradius `a / (2 sqrt(6))`, and one with
+        // circumradius `r` has volume `8 sqrt(3) r^3 / 27`.
+        let aspect_ratio = if inradius > zero {
+            longest / (constant::<T>(2.) * constant::<T>(6.).sqrt() * inradius)
+        } else {
+            infinity
+        };
+        let (radius_ratio, skewness) = match circumradius {
+            Some(r) if inradius > zero => {
+                let ideal =
+                    constant::<T>(8.) * constant::<T>(3.).sqrt() * r.powi(3) / constant(27.);
+                (
+                    constant::<T>(3.) * inradius / r,
+                    ((ideal - tet.volume()) / ideal).clamp(zero, one),
+                )
+            }
+            _ => (zero, one),
+        };
+
+        // A flat tetrahedron has no face normals, but its angles have collapsed to 0 and 180.
+        let (min_dihedral, max_dihedral) = if tet.volume() > zero {
+            let angles = tet.dihedral_angles().map(|a| a * constant(180.) / T::pi());
+            (
+                angles.iter().copied().fold(infinity, T::min),
+                angles.iter().copied().fold(-infinity, T::max),
+            )
+        } else {
+            (zero, constant(180.))
+        };
+
+        CellQuality {
+            aspect_ratio,
+            radius_ratio,
+            min_dihedral,
+            max_dihedral,
+            skewness,
+            volume_ratio,
+            inverted: tet.signed_volume() <= zero,
+        }
+    }
+}
+
+impl<T: RealField + Copy> TetrahedralMesh<T> {
+    /**
+    Measures the shape quality of every tetrahedron in the mesh and summarizes each metric. The
+    histograms of bounded metrics span their natural range (0 to 1 for the radius ratio and
+    skewness, 0 to 180 degrees for dihedral angles), while the others span from 1 to the largest
+    finite value found.
+
+    Returns:
+    - `QualityReport<T>` - The per-tetrahedron metrics and their summaries.
+     */
+    pub fn quality(&self) -> QualityReport<T> {
+        let (zero, one) = (T::zero(), T::one());
+        let tetrahedra: Vec<Tetrahedron<T>> = self.cells().collect();
+        let volumes: Vec<T> = tetrahedra.iter().map(Tetrahedron::volume).collect();
+
+        let cells: Vec<CellQuality<T>> = tetrahedra
+            .iter()
+            .zip(&self.neighbors)
+            .enumerate()
+            .map(|(t, (tet, neighbors))| {
+                let volume_ratio = neighbors
+                    .iter()
+                    .flatten()
+                    .map(|&n| {
+                        let (a, b) = (volumes[t], volumes[n]);
+                        if a.min(b) > zero {
+                            a.max(b) / a.min(b)
+                        } else if a.max(b) > zero {
+                            constant(f64::INFINITY)
+                        } else {
+                            one
+                        }
+                    })
+                    .fold(one, T::max);
+                CellQuality::new(tet, volume_ratio)
+            })
+            .collect();
+
+        let metric = |f: fn(&CellQuality<T>) -> T| cells.iter().map(f).collect::<Vec<_>>();
+        let straight = Some(constant(180.));
+        QualityReport {
+            inverted: (0..cells.len()).filter(|&t| cells[t].inverted).collect(),
+            aspect_ratio: MetricSummary::new(&metric(|c| c.aspect_ratio), Some(one), None),
+            radius_ratio: MetricSummary::new(&metric(|c| c.radius_ratio), Some(zero), Some(one)),
+            min_dihedral: MetricSummary::new(&metric(|c| c.min_dihedral), Some(zero), straight),
+            max_dihedral: MetricSummary::new(&metric(|c| c.max_dihedral), Some(zero), straight),
+            skewness: MetricSummary::new(&metric(|c| c.skewness), Some(zero), Some(one)),
+            volume_ratio: MetricSummary::new(&metric(|c| c.volume_ratio), Some(one), None),
+            cells,
+        }
+    }
+}
@@ -0,0 +1,714 @@
+/*! Reading and writing of the TetGen file formats. A tetrahedral mesh is spread over `.node`,
+`.ele`, `.face` and `.neigh` files sharing a base name, while a surface to be tetrahedralized is a
+piecewise linear complex (PLC) in a `.poly` or `.smesh` file. Files may number their entries from
+0 or 1, which is told apart by the first node. TetGen has no names for boundary markers or region
+attributes, so patches and regions read from its files are named after their numbers. */
+
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use nalgebra::RealField;
+
+use super::{BoundaryFace, CellGroup, TetrahedralMesh};
+use crate::geometry::polymesh::{
+    ear_clip, open_file, write_file, Attribute, AttributeType, FaceGroup, MeshError, MutateMesh,
+    TriangleMesh,
+};
+use crate::Point3;
+
+/// The file format of a piecewise linear complex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlcFormat {
+    /// A `.poly` file, whose facets may hold several polygons.
+    Poly,
+    /// A `.smesh` file, whose facets are single polygons.
+    Smesh,
+}
+
+/// The name of the vertex attribute holding the boundary markers of the nodes.
+const MARKER: &str = "marker";
+
+/// Returns the error for a header that does not follow the format.
+fn invalid_header() -> MeshError {
+    MeshError::FormatError("Invalid TetGen header.")
+}
+
+/// Returns the error for a record that does not follow the format.
+fn invalid_record() -> MeshError {
+    MeshError::FormatError("Invalid TetGen record.")
+}
+
+/// Parses a word of a record, failing with `error`.
+fn parse<U: FromStr>(word: &str, error: &'static str) -> Result<U, MeshError> {
+    word.parse().map_err(|_| MeshError::FormatError(error))
+}
+
+/// Parses an integer.
+fn int(word: &str) -> Result<i64, MeshError> {
+    parse(word, "Failed to parse integer.")
+}
+
+/// Parses a count, which may not be negative.
+fn count(word: &str) -> Result<usize, MeshError> {
+    parse(word, "Failed to parse integer.")
+}
+
+/// Parses a float.
+fn float(word: &str) -> Result<f64, MeshError> {
+    parse(word, "Failed to parse float.")
+}
+
+/// The records of a TetGen file: its lines with comments removed, split into words. Empty lines
+/// are skipped.
+struct Records {
+    records: std::vec::IntoIter<Vec<String>>,
+}
+
+impl Records {
+    /// Reads the records of the file `filename`.
+    fn open(filename: &str) -> Result<Records, MeshError> {
+        let mut text = String::new();
+        open_file(filename)?
+            .read_to_string(&mut text)
+            .map_err(|e| MeshError::file("Could not read file.", filename, e))?;
+        let records: Vec<Vec<String>> = text
+            .lines()
+            .map(|line| {
+                let content = line.split('#').next().unwrap_or_default();
+                content
+                    .split_ascii_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<String>>()
+            })
+            .filter(|record| !record.is_empty())
+            .collect();
+        Ok(Records {
+            records: records.into_iter(),
+        })
+    }
+
+    /// Takes the next record, which must hold at least `length` words.
+    fn next(&mut self, length: usize) -> Result<Vec<String>, MeshError> {
+        self.records
+            .next()
+            .filter(|record| record.len() >= length)
+            .ok_or_else(invalid_record)
+    }
+
+    /// Takes the next record as a header, which must hold at least `length` words.
+    fn header(&mut self, length: usize) -> Result<Vec<String>, MeshError> {
+        self.next(length).map_err(|_| invalid_header())
+    }
+}
+
+/// The nodes of a `.node` file, or of the first part of a `.poly` or `.smesh` file.
+struct Nodes {
+    points: Vec<[f64; 3]>,
+    // The values of each attribute, with one value per node.
+    attributes: Vec<Vec<f64>>,
+    markers: Option<Vec<f64>>,
+    // The index of the first node, which every index in the files counts from.
+    first: i64,
+}
+
+impl Nodes {
+    /**
+    Resolves a node index of a file into an index into `points`.
+
+    - Index outside the nodes: `MeshError::IndexingError("Vertex not contained in mesh.")`
+
+    Parameters:
+    - `word: &str` - The index as written in the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - The index into `points`, else a `MeshError`.
+     */
+    fn index(&self, word: &str) -> Result<usize, MeshError> {
+        usize::try_from(int(word)? - self.first)
+            .ok()
+            .filter(|&v| v < self.points.len())
+            .ok_or(MeshError::IndexingError("Vertex not contained in mesh."))
+    }
+
+    /// Returns the node attributes and markers as vertex attributes, with the markers last.
+    fn vertex_attributes(self) -> Vec<Attribute> {
+        let attributes = self.attributes.into_iter().enumerate().map(|(a, values)| {
+            let name = format!("attribute{}", a + 1);
+            (name, AttributeType::Float64, values)
+        });
+        let markers = self
+            .markers
+            .map(|values| (MARKER.to_string(), AttributeType::Int32, values));
+        attributes
+            .chain(markers)
+            .map(|(name, value_type, values)| Attribute {
+                name,
+                value_type,
+                values,
+            })
+            .collect()
+    }
+}
+
+/**
+Reads the nodes following a node list header, which gives the node count, the dimension, the
+attribute count and whether the nodes have boundary markers.
+
+- Header not describing 3-D nodes: `MeshError::FormatError("Invalid TetGen header.")`
+- Record with too few values: `MeshError::FormatError("Invalid TetGen record.")`
+
+Parameters:
+- `records: &mut Records` - The records of the file, positioned after the header.
+- `header: &[String]` - The node list header.
+
+Returns:
+- `Result<Nodes, MeshError>` - The nodes, else a `MeshError`.
+ */
+fn read_nodes(records: &mut Records, header: &[String]) -> Result<Nodes, MeshError> {
+    if header.len() < 2 || header[1] != "3" {
+        return Err(invalid_header());
+    }
+    let node_count = count(&header[0])?;
+    let attribute_count = header.get(2).map_or(Ok(0), |w| count(w))?;
+    let has_markers = header.get(3).is_some_and(|w| w != "0");
+
+    let mut nodes = Nodes {
+        points: Vec::new(),
+        attributes: vec![Vec::new(); attribute_count],
+        markers: has_markers.then(Vec::new),
+        first: 0,
+    };
+    for n in 0..node_count {
+        let record = records.next(4 + attribute_count + has_markers as usize)?;
+        if n == 0 {
+            nodes.first = int(&record[0])?;
+        }
+        nodes
+            .points
+            .push([float(&record[1])?, float(&record[2])?, float(&record[3])?]);
+        for (a, values) in nodes.attributes.iter_mut().enumerate() {
+            values.push(float(&record[4 + a])?);
+        }
+        if let Some(markers) = &mut nodes.markers {
+            markers.push(int(&record[4 + attribute_count])? as f64);
+        }
+    }
+    Ok(nodes)
+}
+
+/// Groups elements by a marker or attribute value, in order of first appearance. Elements whose
+/// value is `none` belong to no group, and each group is named after its value.
+fn groups_by_value<V: PartialEq + Copy + Display>(
+    values: &[V],
+    none: V,
+) -> Vec<(String, Vec<usize>)> {
+    let mut keys: Vec<V> = Vec::new();
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for (e, &value) in values.iter().enumerate() {
+        if value == none {
+            continue;
+        }
+        let g = keys.iter().position(|&k| k == value).unwrap_or_else(|| {
+            keys.push(value);
+            groups.push((value.to_string(), Vec::new()));
+            keys.len() - 1
+        });
+        groups[g].1.push(e);
+    }
+    groups
+}
+
+/// Numbers named groups for writing. Groups read from TetGen files keep their numbers, while any
+/// other set of names is numbered from 1 in order.
+fn group_numbers<'a>(names: impl Iterator<Item = &'a String>) -> Vec<i64> {
+    let names: Vec<&String> = names.collect();
+    let numbers: Vec<i64> = names.iter().filter_map(|n| n.parse().ok()).collect();
+    if numbers.len() == names.len() && !numbers.contains(&0) {
+        numbers
+    } else {
+        (1..=names.len() as i64).collect()
+    }
+}
+
+/// Gives each element the number of the group it is in, or 0 if it is in none.
+fn element_numbers(element_count: usize, groups: &[(i64, &[usize])]) -> Vec<i64> {
+    let mut numbers = vec![0; element_count];
+    for (number, elements) in groups {
+        for &e in *elements {
+            numbers[e] = *number;
+        }
+    }
+    numbers
+}
+
+/// Writes `lines` to the file `filename`, returning the number of bytes written.
+fn write_lines(filename: &str, lines: &[String]) -> Result<usize, MeshError> {
+    write_file(filename, |file| {
+        let mut bytes = 0;
+        for line in lines {
+            writeln!(file, "{}", line)?;
+            bytes += line.len() + 1;
+        }
+        Ok(bytes)
+    })
+}
+
+impl TetrahedralMesh {
+    /**
+    Loads a `TetrahedralMesh` from the TetGen files starting with `basename`, with coordinates
+    stored as `Float`. Use `load_tetgen_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `basename: &str` - The path of the files without their extensions.
+
+    Returns:
+    - `Result<Box<TetrahedralMesh>, MeshError>` - Returns the `Box<TetrahedralMesh>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_tetgen(basename: &str) -> Result<Box<TetrahedralMesh>, MeshError> {
+        TetrahedralMesh::load_tetgen_with_precision(basename)
+    }
+}
+
+impl<T: RealField + Copy> TetrahedralMesh<T> {
+    /**
+    Loads a `TetrahedralMesh` from the TetGen files starting with `basename`, such as the
+    `mesh.1.node` and `mesh.1.ele` written by `tetgen mesh.poly`, with coordinates stored as `T`.
+    Tetrahedra are reordered to have positive volume where needed, and only the corners of
+    second-order tetrahedra are kept.
+
+    - Node attributes become vertex attributes named `attribute1`, `attribute2` and so on, and
+      node boundary markers a vertex attribute named `marker`.
+    - Tetrahedron attributes become cell attributes named the same way, and each non-zero value
+      of the first, which TetGen uses for region attributes, becomes a region.
+    - The faces of a `.face` file, if there is one, become the boundary faces of the mesh, each
+      with its index in the file as its source, and each non-zero boundary marker a patch.
+    - The neighbours of a `.neigh` file, if there is one, are checked against the tetrahedra.
+
+    - Header not following the format: `MeshError::FormatError("Invalid TetGen header.")`
+    - Record with too few values: `MeshError::FormatError("Invalid TetGen record.")`
+    - Values cannot be processed: `MeshError::FormatError("Failed to parse float.")` or
+      `MeshError::FormatError("Failed to parse integer.")`
+    - Index outside the nodes: `MeshError::IndexingError("Vertex not contained in mesh.")`
+    - Neighbours not matching the tetrahedra: `MeshError::FormatError("TetGen neighbours do not
+      match the tetrahedra.")`
+    - Flat tetrahedron: `MeshError::FormatError("Tetrahedron does not have positive volume.")`
+    - Face shared by more than two tetrahedra: `MeshError::FormatError("Face shared by more than
+      two tetrahedra.")`
+
+    Parameters:
+    - `basename: &str` - The path of the files without their extensions.
+
+    Returns:
+    - `Result<Box<TetrahedralMesh<T>>, MeshError>` - Returns the `Box<TetrahedralMesh<T>>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_tetgen_with_precision(
+        basename: &str,
+    ) -> Result<Box<TetrahedralMesh<T>>, MeshError> {
+        let mut records = Records::open(&format!("{}.node", basename))?;
+        let header = records.header(2)?;
+        let nodes = read_nodes(&mut records, &header)?;
+
+        let mut records = Records::open(&format!("{}.ele", basename))?;
+        let header = records.header(2)?;
+        let tetrahedron_count = count(&header[0])?;
+        let corners = match header[1].as_str() {
+            "4" => 4,
+            "10" => 10,
+            _ => return Err(invalid_header()),
+        };
+        let attribute_count = header.get(2).map_or(Ok(0), |w| count(w))?;
+        let mut tetrahedra = Vec::new();
+        let mut attributes = vec![Vec::new(); attribute_count];
+        for _ in 0..tetrahedron_count {
+            let record = records.next(1 + corners + attribute_count)?;
+            let mut tet = [0; 4];
+            for (k, v) in tet.iter_mut().enumerate() {
+                *v = nodes.index(&record[1 + k])?;
+            }
+            let [a, b, c, d] = tet.map(|v| nalgebra::Point3::from(nodes.points[v]));
+            if (b - a).cross(&(c - a)).dot(&(d - a)) < 0. {
+                tet.swap(1, 2);
+            }
+            tetrahedra.push(tet);
+            for (a, values) in attributes.iter_mut().enumerate() {
+                values.push(float(&record[1 + corners + a])?);
+            }
+        }
+
+        let faces = format!("{}.face", basename);
+        let mut boundary = Vec::new();
+        let mut face_markers = Vec::new();
+        if Path::new(&faces).exists() {
+            let mut records = Records::open(&faces)?;
+            let header = records.header(1)?;
+            let has_markers = header.get(1).is_some_and(|w| w != "0");
+            for _ in 0..count(&header[0])? {
+                let record = records.next(4 + has_markers as usize)?;
+                let mut face = [0; 3];
+                for (k, v) in face.iter_mut().enumerate() {
+                    *v = nodes.index(&record[1 + k])?;
+                }
+                boundary.push(face);
+                face_markers.push(if has_markers { int(&record[4])? } else { 0 });
+            }
+        }
+
+        let vertices = nodes
+            .points
+            .iter()
+            .map(|p| Point3::from(p.map(nalgebra::convert)))
+            .collect();
+        let mut mesh = TetrahedralMesh::new(vertices, tetrahedra)?;
+
+        let neighbours = format!("{}.neigh", basename);
+        if Path::new(&neighbours).exists() {
+            let mismatch =
+                || MeshError::FormatError("TetGen neighbours do not match the tetrahedra.");
+            let mut records = Records::open(&neighbours)?;
+            let header = records.header(1)?;
+            if count(&header[0])? != mesh.tetrahedra.len() {
+                return Err(mismatch());
+            }
+            // The order of the neighbours depends on that of the corners, which may have been
+            // swapped, so only the sets of neighbours are compared.
+            for expected in &mesh.neighbors {
+                let record = records.next(5)?;
+                let mut found = Vec::new();
+                for word in &record[1..5] {
+                    let t = int(word)?;
+                    found.push((t >= 0).then(|| (t - nodes.first) as usize));
+                }
+                let mut expected = expected.to_vec();
+                found.sort_unstable();
+                expected.sort_unstable();
+                if found != expected {
+                    return Err(mismatch());
+                }
+            }
+        }
+
+        mesh.boundary_faces = boundary
+            .into_iter()
+            .enumerate()
+            .map(|(source, vertices)| BoundaryFace { vertices, source })
+            .collect();
+        mesh.patches = groups_by_value(&face_markers, 0)
+            .into_iter()
+            .map(|(name, faces)| FaceGroup { name, faces })
+            .collect();
+        if let Some(first) = attributes.first() {
+            mesh.regions = groups_by_value(first, 0.)
+                .into_iter()
+                .map(|(name, cells)| CellGroup { name, cells })
+                .collect();
+        }
+        mesh.cell_attributes = attributes
+            .into_iter()
+            .enumerate()
+            .map(|(a, values)| Attribute {
+                name: format!("attribute{}", a + 1),
+                value_type: AttributeType::Float64,
+                values,
+            })
+            .collect();
+        mesh.vertex_attributes = nodes.vertex_attributes();
+        Ok(Box::new(mesh))
+    }
+
+    /**
+    Writes a `TetrahedralMesh` to the TetGen files `basename.node`, `basename.ele`,
+    `basename.face` and `basename.neigh`, numbering entries from 1.
+
+    - A vertex attribute named `marker` gives the node boundary markers, and every other vertex
+      attribute a node attribute.
+    - Cell attributes become tetrahedron attributes. A mesh without cell attributes but with
+      regions gets a single tetrahedron attribute holding the number of the region of each
+      tetrahedron, or 0 for tetrahedra in no region.
+    - The boundary faces are written to the `.face` file, or, if there are none, the faces
+      belonging to a single tetrahedron. Faces are marked with the number of their patch, or 0
+      for faces in no patch.
+
+    Patches and regions whose names are all non-zero integers, such as those read from TetGen
+    files, are numbered by their names, and other patches and regions are numbered from 1 in
+    order.
+
+    Parameters:
+    - `basename: &str` - The path of the files without their extensions.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written to all four files if
+      file-writing is successful, otherwise a `MeshError::FileError` holding the path of the file
+      that could not be written and the `std::io::Error` that stopped it.
+     */
+    pub fn write_tetgen(&self, basename: &str) -> Result<usize, MeshError> {
+        let (markers, attributes): (Vec<&Attribute>, Vec<&Attribute>) = self
+            .vertex_attributes
+            .iter()
+            .partition(|a| a.name == MARKER);
+        let mut lines = vec![format!(
+            "{} 3 {} {}",
+            self.vertices.len(),
+            attributes.len(),
+            markers.len().min(1)
+        )];
+        for (v, p) in self.vertices.iter().enumerate() {
+            let mut line = format!("{} {} {} {}", v + 1, p.x, p.y, p.z);
+            for attribute in attributes.iter().chain(markers.first()) {
+                line.push(' ');
+                line.push_str(&attribute.value_type.to_text(attribute.values[v]));
+            }
+            lines.push(line);
+        }
+        let mut bytes = write_lines(&format!("{}.node", basename), &lines)?;
+
+        let region_numbers = group_numbers(self.regions.iter().map(|r| &r.name));
+        let regions: Vec<(i64, &[usize])> = region_numbers
+            .into_iter()
+            .zip(&self.regions)
+            .map(|(number, region)| (number, &region.cells[..]))
+            .collect();
+        let region_attribute = Attribute {
+            name: String::new(),
+            value_type: AttributeType::Int32,
+            values: element_numbers(self.tetrahedra.len(), &regions)
+                .into_iter()
+                .map(|n| n as f64)
+                .collect(),
+        };
+        let attributes: Vec<&Attribute> = if !self.cell_attributes.is_empty() {
+            self.cell_attributes.iter().collect()
+        } else if !self.regions.is_empty() {
+            vec![&region_attribute]
+        } else {
+            Vec::new()
+        };
+        let mut lines = vec![format!("{} 4 {}", self.tetrahedra.len(), attributes.len())];
+        for (t, tet) in self.tetrahedra.iter().enumerate() {
+            let mut line = format!(
+                "{} {} {} {} {}",
+                t + 1,
+                tet[0] + 1,
+                tet[1] + 1,
+                tet[2] + 1,
+                tet[3] + 1
+            );
+            for attribute in &attributes {
+                line.push(' ');
+                line.push_str(&attribute.value_type.to_text(attribute.values[t]));
+            }
+            lines.push(line);
+        }
+        bytes += write_lines(&format!("{}.ele", basename), &lines)?;
+
+        let faces = if self.boundary_faces.is_empty() {
+            self.extract_boundary_faces()
+        } else {
+            self.boundary_faces.iter().map(|f| f.vertices).collect()
+        };
+        let patch_numbers = group_numbers(self.patches.iter().map(|p| &p.name));
+        let patches: Vec<(i64, &[usize])> = patch_numbers
+            .into_iter()
+            .zip(&self.patches)
+            .map(|(number, patch)| (number, &patch.faces[..]))
+            .collect();
+        let face_markers = element_numbers(faces.len(), &patches);
+        let mut lines = vec![format!("{} 1", faces.len())];
+        for (f, face) in faces.iter().enumerate() {
+            lines.push(format!(
+                "{} {} {} {} {}",
+                f + 1,
+                face[0] + 1,
+                face[1] + 1,
+                face[2] + 1,
+                face_markers[f]
+            ));
+        }
+        bytes += write_lines(&format!("{}.face", basename), &lines)?;
+
+        let mut lines = vec![format!("{} 4", self.tetrahedra.len())];
+        for (t, neighbors) in self.neighbors.iter().enumerate() {
+            let neighbors = neighbors.map(|n| n.map_or(-1, |n| n as i64 + 1));
+            lines.push(format!(
+                "{} {} {} {} {}",
+                t + 1,
+                neighbors[0],
+                neighbors[1],
+                neighbors[2],
+                neighbors[3]
+            ));
+        }
+        bytes += write_lines(&format!("{}.neigh", basename), &lines)?;
+        Ok(bytes)
+    }
+}
+
+impl TriangleMesh {
+    /**
+    Loads a `TriangleMesh` from the piecewise linear complex in the `.poly` or `.smesh` file
+    passed in, with coordinates stored as `Float`. Use `load_plc_with_precision` to load them as
+    another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh>, MeshError>` - Returns the `Box<TriangleMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_plc(filename: &str) -> Result<Box<TriangleMesh>, MeshError> {
+        TriangleMesh::load_plc_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Loads a `TriangleMesh` from the piecewise linear complex in the `.poly` or `.smesh` file
+    passed in, with coordinates stored as `T`. Files ending in `.smesh` are read as `.smesh`
+    files and any other file as a `.poly` file. When the file lists no nodes, they are read from
+    the `.node` file of the same name. Polygons of more than three corners are split into
+    triangles by ear clipping, so they may be concave, and polygons of fewer corners are
+    skipped. Each non-zero facet boundary marker becomes a patch, while the holes and regions
+    of the volume are ignored.
+
+    - Facet with holes: `MeshError::FormatError("TetGen facet holes are not supported.")`
+    - Header or record not following the format: `MeshError::FormatError("Invalid TetGen
+      header.")` or `MeshError::FormatError("Invalid TetGen record.")`
+    - Values cannot be processed: `MeshError::FormatError("Failed to parse float.")` or
+      `MeshError::FormatError("Failed to parse integer.")`
+    - Index outside the nodes: `MeshError::IndexingError("Vertex not contained in mesh.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh<T>>, MeshError>` - Returns the `Box<TriangleMesh<T>>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_plc_with_precision(filename: &str) -> Result<Box<TriangleMesh<T>>, MeshError> {
+        let path = Path::new(filename);
+        let smesh = path.extension().is_some_and(|e| e == "smesh");
+        let mut records = Records::open(filename)?;
+        let header = records.header(2)?;
+        let nodes = if count(&header[0])? == 0 {
+            let node_file = path.with_extension("node");
+            let mut node_records = Records::open(&node_file.to_string_lossy())?;
+            let header = node_records.header(2)?;
+            read_nodes(&mut node_records, &header)?
+        } else {
+            read_nodes(&mut records, &header)?
+        };
+
+        let mut mesh = TriangleMesh {
+            vertices: Vec::with_capacity(nodes.points.len()),
+            faces: Vec::new(),
+            face_normals: Vec::new(),
+            patches: Vec::new(),
+        };
+        for point in &nodes.points {
+            mesh.add_vertex(Point3::from(point.map(nalgebra::convert)));
+        }
+
+        let header = records.header(1)?;
+        let has_markers = header.get(1).is_some_and(|w| w != "0");
+        let mut face_markers = Vec::new();
+        for _ in 0..count(&header[0])? {
+            // A polygon is a record giving its corner count followed by its corners.
+            let polygon = |record: &[String]| -> Result<Vec<usize>, MeshError> {
+                let corners = count(&record[0])?;
+                let words = record.get(1..1 + corners).ok_or_else(invalid_record)?;
+                words.iter().map(|w| nodes.index(w)).collect()
+            };
+            let mut polygons = Vec::new();
+            let marker = if smesh {
+                let record = records.next(1)?;
+                polygons.push(polygon(&record)?);
+                let corners = polygons[0].len();
+                match record.get(1 + corners) {
+                    Some(word) if has_markers => int(word)?,
+                    _ => 0,
+                }
+            } else {
+                let record = records.next(1)?;
+                if record.get(1).map_or(Ok(0), |w| count(w))? > 0 {
+                    return Err(MeshError::FormatError(
+                        "TetGen facet holes are not supported.",
+                    ));
+                }
+                for _ in 0..count(&record[0])? {
+                    polygons.push(polygon(&records.next(1)?)?);
+                }
+                match record.get(2) {
+                    Some(word) if has_markers => int(word)?,
+                    _ => 0,
+                }
+            };
+
+            for polygon in polygons.iter().filter(|p| p.len() >= 3) {
+                for triangle in ear_clip(&mesh.vertices, polygon) {
+                    mesh.add_face(&triangle, None)?;
+                    face_markers.push(marker);
+                }
+            }
+        }
+
+        mesh.patches = groups_by_value(&face_markers, 0)
+            .into_iter()
+            .map(|(name, faces)| FaceGroup { name, faces })
+            .collect();
+        Ok(Box::new(mesh))
+    }
+
+    /**
+    Writes a `TriangleMesh` to the `.poly` or `.smesh` file passed in as a piecewise linear
+    complex, with a facet for every triangle and no holes or regions. Nodes are numbered from 1,
+    and each facet is marked with the number of its patch, or 0 if it is in no patch. Patches
+    are numbered as in `TetrahedralMesh::write_tetgen`.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: PlcFormat` - The format of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_plc(&self, filename: &str, format: PlcFormat) -> Result<usize, MeshError> {
+        let mut lines = vec![
+            "# Part 1 - nodes".to_string(),
+            format!("{} 3 0 0", self.vertices.len()),
+        ];
+        for (v, p) in self.vertices.iter().enumerate() {
+            let [x, y, z]: [f64; 3] = [p.x, p.y, p.z].map(|x| x.to_subset_unchecked());
+            lines.push(format!("{} {} {} {}", v + 1, x, y, z));
+        }
+
+        let patch_numbers = group_numbers(self.patches.iter().map(|p| &p.name));
+        let patches: Vec<(i64, &[usize])> = patch_numbers
+            .into_iter()
+            .zip(&self.patches)
+            .map(|(number, patch)| (number, &patch.faces[..]))
+            .collect();
+        let face_markers = element_numbers(self.faces.len(), &patches);
+        lines.push("# Part 2 - facets".to_string());
+        lines.push(format!("{} 1", self.faces.len()));
+        for (face, marker) in self.faces.iter().zip(face_markers) {
+            let corners = format!("3 {} {} {}", face[0] + 1, face[1] + 1, face[2] + 1);
+            match format {
+                PlcFormat::Poly => {
+                    lines.push(format!("1 0 {}", marker));
+                    lines.push(corners);
+                }
+                PlcFormat::Smesh => lines.push(format!("{} {}", corners, marker)),
+            }
+        }
+        lines.push("# Part 3 - holes".to_string());
+        lines.push("0".to_string());
+        lines.push("# Part 4 - regions".to_string());
+        lines.push("0".to_string());
+        write_lines(filename, &lines)
+    }
+}
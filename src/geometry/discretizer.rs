@@ -1,32 +1,259 @@
-use crate::geometry::discmesh::{Cell, CellMesh, TetrahedralMesh, Tetrahedron};
-use crate::geometry::polymesh::{PolyMesh, TriangleMesh};
+use nalgebra::RealField;
 
-// Define a set of helper functions (but split them into modules
+use crate::geometry::discmesh::{BoundaryFace, Cell, CellMesh, TetrahedralMesh, Tetrahedron};
+use crate::geometry::polymesh::{FaceGroup, MeshError, PolyMesh, TriangleMesh};
+use crate::{Float, Point3};
 
 /// The `delaunay` module provides helper functions
-pub(in crate::geometry) mod delaunay {}
+pub(in crate::geometry) mod delaunay;
+
+/// The `raycast` module classifies points as inside or outside a closed surface.
+pub(in crate::geometry) mod raycast;
+
+/// The `constrained` module recovers the surface triangles within a Delaunay tetrahedralization.
+pub(in crate::geometry) mod constrained;
+
+/// The `refine` module inserts Steiner points until the tetrahedra meet the quality bounds.
+pub(in crate::geometry) mod refine;
+
+use delaunay::DPoint;
+use raycast::RayCaster;
+use refine::QualityBounds;
 
 pub trait DiscretizerConfig {}
 
 pub trait Discretizer<T: PolyMesh, U: Cell, V: CellMesh<U>, W: DiscretizerConfig> {
-    fn discretize(polymesh: &T, config: &W) -> V;
+    fn discretize(polymesh: &T, config: &W) -> Result<V, MeshError>;
 }
 
 pub struct TetrahedralDiscretizer {}
 
-pub struct TetrahedralDiscretizerConfig {
-    pub threshold_angle: f32,
+/// A `VolumeRegion` limits the volume of the tetrahedra whose centroids lie within an
+/// axis-aligned box.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VolumeRegion<T: RealField + Copy = Float> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+    pub max_volume: T,
+}
+
+/// The options of a `TetrahedralDiscretizer`, in the scalar type of the meshes it discretizes.
+pub struct TetrahedralDiscretizerConfig<T: RealField + Copy = Float> {
+    /// The smallest dihedral angle, in degrees, that refinement should leave in any tetrahedron.
+    /// Zero disables the bound. Delaunay refinement cannot guarantee dihedral angles, so large
+    /// values may only be met once `max_steiner_points` runs out.
+    pub threshold_angle: T,
+    /// When set, Steiner points are inserted until every face of the surface is the union of
+    /// faces of the output, and `TetrahedralMesh::get_boundary_faces` records which surface face
+    /// each of them came from. If that cannot be done, `discretize` fails rather than returning a
+    /// mesh that does not match the surface.
+    pub constrained: bool,
+    /// The largest ratio of circumradius to shortest edge allowed in any tetrahedron. Bounds of 2
+    /// or more can always be met away from small angles in the surface.
+    pub max_radius_edge_ratio: Option<T>,
+    /// The largest volume allowed for any tetrahedron.
+    pub max_volume: Option<T>,
+    /// Volume limits that only apply to parts of the domain.
+    pub regions: Vec<VolumeRegion<T>>,
+    /// The most Steiner points refinement may insert. When `None`, the limit grows with the size
+    /// of the surface.
+    pub max_steiner_points: Option<usize>,
+    /// The most Steiner points surface recovery may insert before giving up. When `None`, the
+    /// limit grows with the number of surface triangles.
+    pub max_recovery_points: Option<usize>,
+}
+
+impl<T: RealField + Copy> TetrahedralDiscretizerConfig<T> {
+    /// Returns `true` if any quality bound is set, in which case the output is refined.
+    pub fn refines(&self) -> bool {
+        self.threshold_angle > T::zero()
+            || self.max_radius_edge_ratio.is_some()
+            || self.max_volume.is_some()
+            || !self.regions.is_empty()
+    }
+
+    /// Converts the quality bounds into the form used by refinement.
+    fn quality_bounds(&self) -> Option<QualityBounds> {
+        self.refines().then(|| QualityBounds {
+            radius_edge: self.max_radius_edge_ratio.map(to_f64),
+            min_dihedral: (self.threshold_angle > T::zero())
+                .then(|| to_f64(self.threshold_angle).to_radians()),
+            max_volume: self.max_volume.map(to_f64),
+            regions: self
+                .regions
+                .iter()
+                .map(|r| (to_dpoint(&r.min), to_dpoint(&r.max), to_f64(r.max_volume)))
+                .collect(),
+            steiner_limit: self.max_steiner_points,
+        })
+    }
+}
+
+/// Converts a value to the double precision that tetrahedralization works in.
+fn to_f64<T: RealField + Copy>(x: T) -> f64 {
+    x.to_subset_unchecked()
+}
+
+/// Converts a point to the double precision that tetrahedralization works in.
+fn to_dpoint<T: RealField + Copy>(p: &Point3<T>) -> DPoint {
+    p.map(to_f64)
+}
+
+impl<T: RealField + Copy> Default for TetrahedralDiscretizerConfig<T> {
+    fn default() -> Self {
+        TetrahedralDiscretizerConfig {
+            threshold_angle: T::zero(),
+            constrained: false,
+            max_radius_edge_ratio: None,
+            max_volume: None,
+            regions: Vec::new(),
+            max_steiner_points: None,
+            max_recovery_points: None,
+        }
+    }
 }
 
-impl DiscretizerConfig for TetrahedralDiscretizerConfig {}
+impl<T: RealField + Copy> DiscretizerConfig for TetrahedralDiscretizerConfig<T> {}
 
-impl Discretizer<TriangleMesh, Tetrahedron, TetrahedralMesh, TetrahedralDiscretizerConfig>
-    for TetrahedralDiscretizer
+impl<T: RealField + Copy>
+    Discretizer<
+        TriangleMesh<T>,
+        Tetrahedron<T>,
+        TetrahedralMesh<T>,
+        TetrahedralDiscretizerConfig<T>,
+    > for TetrahedralDiscretizer
 {
+    /**
+    Tetrahedralizes the volume enclosed by `polymesh`. The Delaunay tetrahedralization of the
+    surface vertices is computed, after which every tetrahedron outside the surface is discarded.
+    If `config.constrained` is set, the surface triangles are first recovered by inserting Steiner
+    points, so the boundary of the output matches the surface exactly. If any quality bound is set,
+    the surface is recovered in the same way and the tetrahedra are then refined until they meet
+    the bounds. Surfaces with boundary patches are always recovered, and each patch of the output
+    holds the boundary faces lying on the faces of the matching surface patch. Whatever the scalar
+    type `T` of the surface, the tetrahedralization is computed in double precision and converted
+    back to `T`.
+    The surface must bound a volume, so this method returns `MeshError` if:
+    - Surface is not closed, 2-manifold and consistently oriented, as given by `MeshReport::check`
+    - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+    - Vertices with coordinates that are not finite, when recovering the surface:
+      `MeshError::RecoveryError("Surface could not be tetrahedralized.")`
+    - Surface not recovered within `config.max_recovery_points`:
+      `MeshError::RecoveryError("Surface could not be recovered.")`
+
+    Parameters:
+    - `polymesh: &TriangleMesh<T>` - The closed surface to fill.
+    - `config: &TetrahedralDiscretizerConfig<T>` - Options for the discretization.
+
+    Returns:
+    - `Result<TetrahedralMesh<T>, MeshError>` - A mesh sharing its vertex indices with `polymesh`,
+      followed by any Steiner points, else a `MeshError`. Vertices that are exact duplicates of an
+      earlier vertex are left unreferenced.
+     */
     fn discretize(
-        polymesh: &TriangleMesh,
-        config: &TetrahedralDiscretizerConfig,
-    ) -> TetrahedralMesh {
-        todo!()
+        polymesh: &TriangleMesh<T>,
+        config: &TetrahedralDiscretizerConfig<T>,
+    ) -> Result<TetrahedralMesh<T>, MeshError> {
+        polymesh.validate()?.check()?;
+
+        let points: Vec<DPoint> = polymesh.get_vertices().iter().map(to_dpoint).collect();
+
+        let bounds = config.quality_bounds();
+        if config.constrained || bounds.is_some() || !polymesh.get_patches().is_empty() {
+            let recovery = constrained::tetrahedralize(
+                &points,
+                polymesh.get_faces(),
+                bounds.as_ref(),
+                config.max_recovery_points,
+            )
+            .ok_or(MeshError::RecoveryError(
+                "Surface could not be tetrahedralized.",
+            ))?;
+            // A mesh whose boundary does not match the surface is never returned in place of one
+            // that does.
+            if !recovery.complete {
+                return Err(MeshError::RecoveryError("Surface could not be recovered."));
+            }
+
+            let mut vertices = polymesh.get_vertices().clone();
+            vertices.extend(
+                recovery.points[points.len()..]
+                    .iter()
+                    .map(|p| p.map(nalgebra::convert)),
+            );
+
+            let mut mesh = TetrahedralMesh::assemble(vertices, recovery.tetrahedra)?;
+            mesh.boundary_faces = recovery
+                .boundary
+                .into_iter()
+                .map(|(vertices, source)| BoundaryFace { vertices, source })
+                .collect();
+            mesh.patches = carry_patches(polymesh, &mesh.boundary_faces);
+            return Ok(mesh);
+        }
+
+        let tetrahedra = inside_surface(
+            &points,
+            polymesh.get_faces(),
+            delaunay::tetrahedralize(&points),
+        );
+        TetrahedralMesh::assemble(polymesh.get_vertices().clone(), tetrahedra)
     }
 }
+
+/**
+Carries the boundary patches of `polymesh` over to the boundary faces recovered from it.
+
+Parameters:
+- `polymesh: &TriangleMesh<T>` - The surface that was discretized.
+- `boundary_faces: &[BoundaryFace]` - The boundary faces of the output, each knowing the surface
+  face it lies on.
+
+Returns:
+- `Vec<FaceGroup>` - A patch for each patch of the surface, holding indices into `boundary_faces`.
+ */
+fn carry_patches<T: RealField + Copy>(
+    polymesh: &TriangleMesh<T>,
+    boundary_faces: &[BoundaryFace],
+) -> Vec<FaceGroup> {
+    let mut face_patches = vec![None; polymesh.get_face_count()];
+    for (p, patch) in polymesh.get_patches().iter().enumerate() {
+        for &f in &patch.faces {
+            face_patches[f] = Some(p);
+        }
+    }
+
+    let mut patches: Vec<FaceGroup> = polymesh
+        .get_patches()
+        .iter()
+        .map(|patch| FaceGroup {
+            name: patch.name.clone(),
+            faces: Vec::new(),
+        })
+        .collect();
+    for (b, face) in boundary_faces.iter().enumerate() {
+        if let Some(p) = face_patches[face.source] {
+            patches[p].faces.push(b);
+        }
+    }
+    patches
+}
+
+/// Keeps the tetrahedra whose centroids lie inside the surface made of `triangles`.
+fn inside_surface(
+    points: &[DPoint],
+    triangles: &[[usize; 3]],
+    tetrahedra: Vec<[usize; 4]>,
+) -> Vec<[usize; 4]> {
+    let caster = RayCaster::new(points, triangles);
+    tetrahedra
+        .into_iter()
+        .filter(|tet| {
+            let centroid = tet
+                .iter()
+                .fold(DPoint::origin(), |c, &v| c + points[v].coords);
+            caster.contains(&(centroid / 4.))
+        })
+        .collect()
+}
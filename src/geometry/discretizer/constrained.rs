@@ -0,0 +1,478 @@
+/*! Surface recovery for the Delaunay tetrahedralization. The plain Delaunay tetrahedralization of
+the surface vertices need not contain the edges and triangles of the surface. Steiner points are
+inserted on the missing input edges (segments) and triangles (facets) until every segment is the
+union of triangulation edges and every facet is the union of triangulation faces. Once the surface
+is part of the triangulation, the enclosed tetrahedra are separated by a flood fill. */
+
+use std::collections::{HashMap, HashSet};
+
+use nalgebra::Vector3;
+
+use super::delaunay::{triangulate2d, DPoint, DPoint2, Triangulation};
+use super::refine::{self, QualityBounds};
+
+/// The average number of Steiner points allowed per input facet before recovery gives up.
+const STEINER_PER_FACET: usize = 4;
+
+/// The relative tolerance used when comparing the area of a facet to the faces covering it.
+const AREA_TOLERANCE: f64 = 1e-9;
+
+/// The result of recovering a surface within a Delaunay tetrahedralization.
+pub(in crate::geometry) struct Recovery {
+    /// The input points followed by the Steiner points.
+    pub(in crate::geometry) points: Vec<DPoint>,
+    /// The positively oriented tetrahedra enclosed by the surface. If recovery did not complete,
+    /// this holds every tetrahedron of the triangulation instead.
+    pub(in crate::geometry) tetrahedra: Vec<[usize; 4]>,
+    /// The triangulation faces making up each recovered facet, paired with the index of the input
+    /// triangle they lie on. Each face is wound like its input triangle.
+    pub(in crate::geometry) boundary: Vec<([usize; 3], usize)>,
+    /// Whether every segment and facet was recovered.
+    pub(in crate::geometry) complete: bool,
+}
+
+/// An input edge, split into sub-segments by the Steiner points lying on it.
+pub(super) struct Segment {
+    pub(super) ends: [usize; 2],
+    pub(super) interior: Vec<usize>,
+}
+
+/// An input triangle along with the Steiner points inserted on its interior.
+pub(super) struct Facet {
+    source: usize,
+    pub(super) corners: [usize; 3],
+    pub(super) segments: [usize; 3],
+    pub(super) interior: Vec<usize>,
+    normal: Vector3<f64>,
+    axes: [Vector3<f64>; 2],
+}
+
+/// The triangulation being recovered, along with the segments and facets it must contain.
+pub(super) struct Recoverer {
+    pub(super) triangulation: Triangulation,
+    pub(super) input_count: usize,
+    pub(super) segments: Vec<Segment>,
+    pub(super) facets: Vec<Facet>,
+    // The most Steiner points each pass of recovery may insert.
+    pub(super) budget: usize,
+}
+
+/// Returns the circumcenter of the planar triangle `(a, b, c)`, or `None` if it is degenerate.
+fn circumcenter2d(a: &DPoint2, b: &DPoint2, c: &DPoint2) -> Option<DPoint2> {
+    let (b, c) = (b - a, c - a);
+    let d = 2. * b.perp(&c);
+    if d == 0. {
+        return None;
+    }
+    let (b2, c2) = (b.norm_squared(), c.norm_squared());
+    Some(a + nalgebra::vector![c.y * b2 - b.y * c2, b.x * c2 - c.x * b2] / d)
+}
+
+impl Recoverer {
+    pub(super) fn point(&self, idx: usize) -> &DPoint {
+        self.triangulation.point(idx)
+    }
+
+    /// Returns the points of segment `s` ordered from one end to the other.
+    fn segment_points(&self, s: usize) -> Vec<usize> {
+        let segment = &self.segments[s];
+        let [a, b] = segment.ends.map(|e| *self.point(e));
+        let mut points = segment.interior.clone();
+        points.sort_by(|&p, &q| {
+            let t = |i: usize| (self.point(i) - a).dot(&(b - a));
+            t(p).total_cmp(&t(q))
+        });
+        points.insert(0, segment.ends[0]);
+        points.push(segment.ends[1]);
+        points
+    }
+
+    /// Returns the sub-segments of segment `s`.
+    pub(super) fn sub_segments(&self, s: usize) -> Vec<[usize; 2]> {
+        self.segment_points(s)
+            .windows(2)
+            .map(|w| [w[0], w[1]])
+            .collect()
+    }
+
+    /// Returns every point lying on facet `f`, including its corners and segment points.
+    pub(super) fn facet_points(&self, f: usize) -> Vec<usize> {
+        let facet = &self.facets[f];
+        let mut points: Vec<usize> = facet.corners.to_vec();
+        for &s in &facet.segments {
+            points.extend(&self.segments[s].interior);
+        }
+        points.extend(&facet.interior);
+        points
+    }
+
+    /// Projects `p` into the plane coordinates of facet `f`.
+    pub(super) fn project_to_plane(&self, f: usize, p: &DPoint) -> DPoint2 {
+        let facet = &self.facets[f];
+        let offset = p - self.point(facet.corners[0]);
+        DPoint2::new(offset.dot(&facet.axes[0]), offset.dot(&facet.axes[1]))
+    }
+
+    /// Lifts the plane coordinates `p` of facet `f` back into space.
+    fn lift_from_plane(&self, f: usize, p: &DPoint2) -> DPoint {
+        let facet = &self.facets[f];
+        self.point(facet.corners[0]) + facet.axes[0] * p.x + facet.axes[1] * p.y
+    }
+
+    /// Returns whether the projection of `p` into the plane of facet `f` lies within its corners.
+    pub(super) fn facet_contains(&self, f: usize, p: &DPoint) -> bool {
+        let corners = self.facets[f]
+            .corners
+            .map(|i| self.project_to_plane(f, self.point(i)));
+        let p = self.project_to_plane(f, p);
+        (0..3).all(|k| (corners[(k + 1) % 3] - corners[k]).perp(&(p - corners[k])) >= 0.)
+    }
+
+    /// Returns the faces of the triangulation lying on facet `f`, and whether they cover it.
+    pub(super) fn facet_faces(
+        &self,
+        f: usize,
+        faces: &HashSet<[usize; 3]>,
+    ) -> (Vec<[usize; 3]>, bool) {
+        let mut points = self.facet_points(f);
+        points.sort_unstable();
+        points.dedup();
+
+        let area = |t: &[usize; 3]| {
+            let [a, b, c] = t.map(|i| self.point(i));
+            (b - a).cross(&(c - a)).norm() / 2.
+        };
+
+        let mut found = Vec::new();
+        for (i, &a) in points.iter().enumerate() {
+            for (j, &b) in points.iter().enumerate().skip(i + 1) {
+                for &c in &points[j + 1..] {
+                    if faces.contains(&[a, b, c]) {
+                        found.push([a, b, c]);
+                    }
+                }
+            }
+        }
+
+        let covered: f64 = found.iter().map(area).sum();
+        let total = area(&self.facets[f].corners);
+        (found, (covered - total).abs() <= AREA_TOLERANCE * total)
+    }
+
+    /**
+    Splits the sub-segment `[a, b]` of segment `s` and records the new point as lying on it. A
+    sub-segment with exactly one input vertex as an endpoint is split on the concentric shell
+    (a sphere around that vertex with a power-of-two radius) closest to its midpoint, so segments
+    meeting at a small angle are split at matching distances rather than encroaching on each other
+    forever.
+     */
+    pub(super) fn split_segment(&mut self, s: usize, [a, b]: [usize; 2]) -> bool {
+        let (pa, pb) = (*self.point(a), *self.point(b));
+        let split = match (a < self.input_count, b < self.input_count) {
+            (true, false) | (false, true) => {
+                let (apex, other) = if a < self.input_count {
+                    (pa, pb)
+                } else {
+                    (pb, pa)
+                };
+                let length = (other - apex).norm();
+                let radius = 2f64.powf((length / 2.).log2().round());
+                if radius > 0. && radius < length {
+                    apex + (other - apex) * (radius / length)
+                } else {
+                    nalgebra::center(&pa, &pb)
+                }
+            }
+            _ => nalgebra::center(&pa, &pb),
+        };
+        match self.triangulation.add_point(split) {
+            Some(idx) => {
+                self.segments[s].interior.push(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /**
+    Inserts a Steiner point to help recover facet `f`. The circumcenter of the largest planar
+    Delaunay triangle missing from the triangulation is used, unless it encroaches upon one of the
+    facet's sub-segments (in which case that sub-segment is split) or falls outside the facet (in
+    which case the triangle's centroid is used). If no planar triangle is missing, the facet is
+    over-covered by the faces of a flat tetrahedron spanning cocircular facet points, and the
+    circumcenter of the largest such face is used instead, as it lies inside the flat
+    tetrahedron's circumsphere.
+
+    Returns:
+    - `bool` - Whether a point was inserted.
+     */
+    fn refine_facet(&mut self, f: usize, faces: &HashSet<[usize; 3]>) -> bool {
+        let points = self.facet_points(f);
+        let planar: Vec<DPoint2> = points
+            .iter()
+            .map(|&i| self.project_to_plane(f, self.point(i)))
+            .collect();
+        let area = |[a, b, c]: &[DPoint2; 3]| (b - a).perp(&(c - a)).abs();
+
+        let delaunay: Vec<[usize; 3]> = triangulate2d(&planar)
+            .into_iter()
+            .map(|t| {
+                let mut key = t.map(|i| points[i]);
+                key.sort_unstable();
+                key
+            })
+            .collect();
+        let mut candidates: Vec<[usize; 3]> = delaunay
+            .iter()
+            .filter(|key| !faces.contains(*key))
+            .copied()
+            .collect();
+        if candidates.is_empty() {
+            candidates = self
+                .facet_faces(f, faces)
+                .0
+                .into_iter()
+                .filter(|key| !delaunay.contains(key))
+                .collect();
+        }
+        let largest = candidates
+            .into_iter()
+            .map(|key| key.map(|i| self.project_to_plane(f, self.point(i))))
+            .max_by(|s, t| area(s).total_cmp(&area(t)));
+        let [a, b, c] = match largest {
+            Some(t) => t,
+            None => return false,
+        };
+
+        let center = match circumcenter2d(&a, &b, &c) {
+            Some(center) => self.lift_from_plane(f, &center),
+            None => return false,
+        };
+
+        for s in self.facets[f].segments {
+            for [p, q] in self.sub_segments(s) {
+                let (p3, q3) = (self.point(p), self.point(q));
+                if (center - nalgebra::center(p3, q3)).norm() < (q3 - p3).norm() / 2. {
+                    return self.split_segment(s, [p, q]);
+                }
+            }
+        }
+
+        let inside = self.facet_contains(f, &center);
+
+        let centroid =
+            self.lift_from_plane(f, &DPoint2::from((a.coords + b.coords + c.coords) / 3.));
+        let candidates = if inside {
+            vec![center, centroid]
+        } else {
+            vec![centroid]
+        };
+        for candidate in candidates {
+            if let Some(idx) = self.triangulation.add_point(candidate) {
+                self.facets[f].interior.push(idx);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Collects the edges and faces of the triangulation, each with sorted indices.
+    pub(super) fn skeleton(&self) -> (HashSet<[usize; 2]>, HashSet<[usize; 3]>) {
+        let mut edges = HashSet::new();
+        let mut faces = HashSet::new();
+        for tet in self.triangulation.simplices() {
+            let mut tet = *tet;
+            tet.sort_unstable();
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    edges.insert([tet[i], tet[j]]);
+                }
+            }
+            for skip in 0..4 {
+                let mut face = [0; 3];
+                let mut k = 0;
+                for (i, &v) in tet.iter().enumerate() {
+                    if i != skip {
+                        face[k] = v;
+                        k += 1;
+                    }
+                }
+                faces.insert(face);
+            }
+        }
+        (edges, faces)
+    }
+
+    /**
+    Inserts Steiner points until every segment and facet is recovered or the point budget runs
+    out. Missing segments are always split before any facet is refined.
+
+    Returns:
+    - `bool` - Whether every segment and facet was recovered.
+     */
+    pub(super) fn recover(&mut self) -> bool {
+        let mut inserted = 0;
+
+        loop {
+            let (edges, faces) = self.skeleton();
+            let mut changed = false;
+
+            for s in 0..self.segments.len() {
+                for [a, b] in self.sub_segments(s) {
+                    if inserted < self.budget
+                        && !edges.contains(&[a.min(b), a.max(b)])
+                        && self.split_segment(s, [a, b])
+                    {
+                        changed = true;
+                        inserted += 1;
+                    }
+                }
+            }
+
+            if !changed {
+                for f in 0..self.facets.len() {
+                    if inserted < self.budget
+                        && !self.facet_faces(f, &faces).1
+                        && self.refine_facet(f, &faces)
+                    {
+                        changed = true;
+                        inserted += 1;
+                    }
+                }
+            }
+
+            if !changed {
+                let (_, faces) = self.skeleton();
+                return (0..self.facets.len()).all(|f| self.facet_faces(f, &faces).1);
+            }
+        }
+    }
+
+    /// Converts a triangulation index into an index into `Recovery::points`.
+    fn output_index(&self, idx: usize) -> usize {
+        if idx < self.input_count {
+            idx
+        } else {
+            idx - 4
+        }
+    }
+}
+
+/**
+Tetrahedralizes `points` such that every triangle in `triangles` is the union of faces of the
+result. Points that are exact duplicates of an earlier point are merged with it, and triangles
+that become degenerate as a result are ignored. If `bounds` is given and the surface was
+recovered, the enclosed tetrahedra are then refined until they meet it.
+
+Parameters:
+- `points: &[DPoint]` - The vertices of the surface.
+- `triangles: &[[usize; 3]]` - The triangles of the surface, indexing into `points`.
+- `bounds: Option<&QualityBounds>` - The quality bounds to refine the tetrahedra to, if any.
+- `limit: Option<usize>` - The most Steiner points recovery may insert, or `None` to allow a few
+  for each triangle.
+
+Returns:
+- `Option<Recovery>` - `None` if `points` is empty or contains non-finite coordinates.
+ */
+pub(in crate::geometry) fn tetrahedralize(
+    points: &[DPoint],
+    triangles: &[[usize; 3]],
+    bounds: Option<&QualityBounds>,
+    limit: Option<usize>,
+) -> Option<Recovery> {
+    let triangulation = Triangulation::from_points(points)?;
+
+    let mut first_copy: HashMap<_, usize> = HashMap::new();
+    let canonical: Vec<usize> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| *first_copy.entry(p.coords.map(f64::to_bits)).or_insert(i))
+        .collect();
+
+    let mut recoverer = Recoverer {
+        triangulation,
+        input_count: points.len(),
+        segments: Vec::new(),
+        facets: Vec::new(),
+        budget: 0,
+    };
+
+    let mut segment_of: HashMap<[usize; 2], usize> = HashMap::new();
+    for (source, triangle) in triangles.iter().enumerate() {
+        let corners = triangle.map(|i| canonical[i]);
+        let [a, b, c] = corners.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a));
+        if normal.norm() == 0. {
+            continue;
+        }
+
+        let segments = [0, 1, 2].map(|k| {
+            let (u, v) = (corners[k], corners[(k + 1) % 3]);
+            *segment_of.entry([u.min(v), u.max(v)]).or_insert_with(|| {
+                recoverer.segments.push(Segment {
+                    ends: [u, v],
+                    interior: Vec::new(),
+                });
+                recoverer.segments.len() - 1
+            })
+        });
+
+        let normal = normal.normalize();
+        let first_axis = (b - a).normalize();
+        recoverer.facets.push(Facet {
+            source,
+            corners,
+            segments,
+            interior: Vec::new(),
+            normal,
+            axes: [first_axis, normal.cross(&first_axis)],
+        });
+    }
+
+    recoverer.budget = limit.unwrap_or(STEINER_PER_FACET * recoverer.facets.len().max(1));
+    let mut complete = recoverer.recover();
+    if let (true, Some(bounds)) = (complete, bounds) {
+        complete = refine::refine(&mut recoverer, bounds);
+    }
+
+    let (_, faces) = recoverer.skeleton();
+    let mut walls = HashSet::new();
+    let mut boundary = Vec::new();
+    for f in 0..recoverer.facets.len() {
+        let (found, covered) = recoverer.facet_faces(f, &faces);
+        if !covered {
+            continue;
+        }
+        let facet = &recoverer.facets[f];
+        for face in found {
+            walls.insert(face);
+            let [a, b, c] = face.map(|i| recoverer.point(i));
+            let oriented = if (b - a).cross(&(c - a)).dot(&facet.normal) >= 0. {
+                face
+            } else {
+                [face[0], face[2], face[1]]
+            };
+            boundary.push((oriented.map(|i| recoverer.output_index(i)), facet.source));
+        }
+    }
+
+    let triangulation = &recoverer.triangulation;
+    let tetrahedra = if complete {
+        triangulation.enclosed_by(&walls)
+    } else {
+        triangulation.tetrahedra()
+    };
+
+    Some(Recovery {
+        points: (0..triangulation.point_count())
+            .filter(|&i| !triangulation.is_super_vertex(i))
+            .map(|i| *triangulation.point(i))
+            .collect(),
+        tetrahedra: tetrahedra
+            .into_iter()
+            .map(|t| t.map(|i| recoverer.output_index(i)))
+            .collect(),
+        boundary,
+        complete,
+    })
+}
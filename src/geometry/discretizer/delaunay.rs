@@ -0,0 +1,644 @@
+/*! Incremental Bowyer-Watson Delaunay tetrahedralization. Points are inserted one at a time into a
+triangulation seeded with a large enclosing tetrahedron; every tetrahedron whose circumsphere
+contains the new point is removed, and the resulting star-shaped cavity is re-filled by connecting
+its boundary faces to the new point. All arithmetic is carried out in `f64`, and every decision is
+made with the exact predicates of `crate::geometry::predicates`. */
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::geometry::discmesh::TET_FACES;
+use crate::geometry::predicates::{incircle, insphere, orient2d, orient3d};
+
+/// The point type used throughout the triangulation.
+pub(in crate::geometry) type DPoint = nalgebra::Point3<f64>;
+
+/// How far (relative to the bounding radius of the input) the enclosing tetrahedron extends.
+const SUPER_SCALE: f64 = 100.;
+
+/**
+Computes the center of the sphere through `a`, `b`, `c` and `d`.
+
+Returns:
+- `Option<DPoint>` - The circumcenter, or `None` if the four points are coplanar.
+ */
+pub(in crate::geometry) fn circumcenter(
+    a: &DPoint,
+    b: &DPoint,
+    c: &DPoint,
+    d: &DPoint,
+) -> Option<DPoint> {
+    let (b, c, d) = (b - a, c - a, d - a);
+    let denominator = 2. * b.dot(&c.cross(&d));
+    if denominator == 0. {
+        return None;
+    }
+    let numerator = c.cross(&d) * b.norm_squared()
+        + d.cross(&b) * c.norm_squared()
+        + b.cross(&c) * d.norm_squared();
+    Some(a + numerator / denominator)
+}
+
+/**
+Computes the center of the circle through `a`, `b` and `c`, lying in their plane.
+
+Returns:
+- `Option<DPoint>` - The circumcenter, or `None` if the three points are collinear.
+ */
+pub(in crate::geometry) fn triangle_circumcenter(
+    a: &DPoint,
+    b: &DPoint,
+    c: &DPoint,
+) -> Option<DPoint> {
+    let (b, c) = (b - a, c - a);
+    let normal = b.cross(&c);
+    let denominator = 2. * normal.norm_squared();
+    if denominator == 0. {
+        return None;
+    }
+    Some(a + (c * b.norm_squared() - b * c.norm_squared()).cross(&normal) / denominator)
+}
+
+/// A single tetrahedron in the triangulation. `neighbors[i]` is the tetrahedron sharing the face
+/// opposite `vertices[i]`.
+#[derive(Clone, Debug)]
+struct Tet {
+    vertices: [usize; 4],
+    neighbors: [Option<usize>; 4],
+    alive: bool,
+}
+
+/// A Delaunay tetrahedralization under construction. The four vertices of the enclosing
+/// tetrahedron are stored directly after the input points.
+pub(in crate::geometry) struct Triangulation {
+    points: Vec<DPoint>,
+    tets: Vec<Tet>,
+    super_start: usize,
+    last: usize,
+    // A live tetrahedron incident to each inserted point, or `usize::MAX` if it is not inserted.
+    incident: Vec<usize>,
+}
+
+impl Triangulation {
+    /**
+    Creates a triangulation containing only the enclosing tetrahedron of `points`. None of the
+    points are inserted yet.
+
+    Parameters:
+    - `points: &[DPoint]` - The points that will later be inserted.
+
+    Returns:
+    - `Option<Triangulation>` - `None` if `points` is empty or contains non-finite coordinates.
+     */
+    pub(in crate::geometry) fn new(points: &[DPoint]) -> Option<Triangulation> {
+        let first = points.first()?;
+        let (mut min, mut max) = (first.coords, first.coords);
+        for p in points {
+            if !p.coords.iter().all(|c| c.is_finite()) {
+                return None;
+            }
+            min = min.inf(&p.coords);
+            max = max.sup(&p.coords);
+        }
+        let center = DPoint::from((min + max) / 2.);
+        let radius = ((max - min).norm() / 2.).max(1.);
+
+        let mut corners = [
+            center + nalgebra::vector![1., 1., 1.] * radius * SUPER_SCALE,
+            center + nalgebra::vector![1., -1., -1.] * radius * SUPER_SCALE,
+            center + nalgebra::vector![-1., 1., -1.] * radius * SUPER_SCALE,
+            center + nalgebra::vector![-1., -1., 1.] * radius * SUPER_SCALE,
+        ];
+        if orient3d(&corners[0], &corners[1], &corners[2], &corners[3]) < 0. {
+            corners.swap(2, 3);
+        }
+
+        let super_start = points.len();
+        let mut all = points.to_vec();
+        all.extend_from_slice(&corners);
+        let mut incident = vec![usize::MAX; all.len()];
+        incident[super_start..].fill(0);
+
+        Some(Triangulation {
+            points: all,
+            tets: vec![Tet {
+                vertices: [
+                    super_start,
+                    super_start + 1,
+                    super_start + 2,
+                    super_start + 3,
+                ],
+                neighbors: [None; 4],
+                alive: true,
+            }],
+            super_start,
+            last: 0,
+            incident,
+        })
+    }
+
+    /**
+    Creates the Delaunay tetrahedralization of `points`. The points are inserted along a Z-order
+    curve so consecutive insertions stay close together, which keeps the point-location walk
+    short. Points sharing exact coordinates with an earlier point are skipped, so the tetrahedra
+    only ever reference the first copy.
+
+    Parameters:
+    - `points: &[DPoint]` - The points to tetrahedralize.
+
+    Returns:
+    - `Option<Triangulation>` - `None` if `points` is empty or contains non-finite coordinates.
+     */
+    pub(in crate::geometry) fn from_points(points: &[DPoint]) -> Option<Triangulation> {
+        let mut triangulation = Triangulation::new(points)?;
+
+        let min = points
+            .iter()
+            .fold(points[0], |m, p| DPoint::from(m.coords.inf(&p.coords)));
+        let extent = points
+            .iter()
+            .map(|p| (p - min).amax())
+            .fold(f64::MIN_POSITIVE, f64::max);
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by_key(|&i| morton_code(&points[i], &min, extent));
+
+        let mut seen = HashSet::new();
+        for i in order {
+            if seen.insert(points[i].coords.map(f64::to_bits)) {
+                triangulation.insert(i);
+            }
+        }
+
+        Some(triangulation)
+    }
+
+    /// Returns the coordinates of the point stored at `idx`.
+    pub(in crate::geometry) fn point(&self, idx: usize) -> &DPoint {
+        &self.points[idx]
+    }
+
+    /// Returns the number of points stored, including the vertices of the enclosing tetrahedron.
+    pub(in crate::geometry) fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns the vertices of every live tetrahedron, including those touching the enclosing
+    /// tetrahedron.
+    pub(in crate::geometry) fn simplices(&self) -> impl Iterator<Item = &[usize; 4]> + '_ {
+        self.tets.iter().filter(|t| t.alive).map(|t| &t.vertices)
+    }
+
+    /**
+    Stores `p` after every existing point and inserts it into the triangulation.
+
+    Returns:
+    - `Option<usize>` - The index of the new point, or `None` if it could not be inserted.
+     */
+    pub(in crate::geometry) fn add_point(&mut self, p: DPoint) -> Option<usize> {
+        self.points.push(p);
+        self.incident.push(usize::MAX);
+        let idx = self.points.len() - 1;
+        if self.insert(idx) {
+            Some(idx)
+        } else {
+            self.points.pop();
+            self.incident.pop();
+            None
+        }
+    }
+
+    /**
+    Collects the live tetrahedra incident to the point at `idx` by walking across the faces around
+    it.
+
+    Returns:
+    - `Vec<usize>` - The tetrahedra containing the point, empty if it was never inserted.
+     */
+    pub(in crate::geometry) fn star(&self, idx: usize) -> Vec<usize> {
+        let start = match self.incident.get(idx) {
+            Some(&t) if t != usize::MAX && self.tets[t].alive => t,
+            _ => return Vec::new(),
+        };
+
+        let mut star = vec![start];
+        let mut seen = HashSet::from([start]);
+        let mut k = 0;
+        while k < star.len() {
+            let tet = &self.tets[star[k]];
+            k += 1;
+            for (i, n) in tet.neighbors.iter().enumerate() {
+                // Only the faces containing the point, i.e. those not opposite it, lead around it.
+                if tet.vertices[i] == idx {
+                    continue;
+                }
+                if let Some(n) = *n {
+                    if seen.insert(n) {
+                        star.push(n);
+                    }
+                }
+            }
+        }
+        star
+    }
+
+    /// Returns `true` if `idx` refers to one of the vertices of the enclosing tetrahedron.
+    pub(in crate::geometry) fn is_super_vertex(&self, idx: usize) -> bool {
+        (self.super_start..self.super_start + 4).contains(&idx)
+    }
+
+    /**
+    Finds a live tetrahedron containing `p` by walking towards it from the most recently created
+    tetrahedron, falling back to an exhaustive search if the walk does not terminate.
+
+    Returns:
+    - `Option<usize>` - The tetrahedron containing `p`, or `None` if `p` lies outside the
+      triangulation.
+     */
+    fn locate(&self, p: &DPoint) -> Option<usize> {
+        let mut current = if self.tets[self.last].alive {
+            self.last
+        } else {
+            self.tets.iter().position(|t| t.alive)?
+        };
+
+        'walk: for step in 0..self.tets.len() {
+            let tet = &self.tets[current];
+            for k in 0..4 {
+                // Rotating the starting face stops the walk from cycling on degenerate input.
+                let i = (k + step) % 4;
+                let [a, b, c] = TET_FACES[i].map(|j| &self.points[tet.vertices[j]]);
+                if orient3d(a, b, c, p) > 0. {
+                    current = tet.neighbors[i]?;
+                    continue 'walk;
+                }
+            }
+            return Some(current);
+        }
+
+        // The containing tetrahedron lies outside none of its faces. Should there be none, the
+        // tetrahedron the point is least outside of is taken.
+        let outside = |t: &Tet| {
+            TET_FACES
+                .iter()
+                .map(|f| {
+                    let [a, b, c] = f.map(|j| &self.points[t.vertices[j]]);
+                    orient3d(a, b, c, p)
+                })
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        self.tets
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.alive)
+            .min_by(|(_, s), (_, t)| outside(s).total_cmp(&outside(t)))
+            .map(|(i, _)| i)
+    }
+
+    /// Tests whether `p` lies strictly inside the circumsphere of `tet`.
+    fn in_circumsphere(&self, tet: usize, p: &DPoint) -> bool {
+        let [a, b, c, d] = self.tets[tet].vertices.map(|v| &self.points[v]);
+        insphere(a, b, c, d, p) > 0.
+    }
+
+    /**
+    Finds the tetrahedra whose circumspheres contain `p`, grown outwards from the tetrahedron
+    containing it. These are the tetrahedra that inserting `p` would replace.
+
+    Returns:
+    - `Option<BTreeSet<usize>>` - `None` if `p` lies outside the triangulation or coincides with
+      one of its vertices. The set is ordered so the triangulation does not depend on hashing.
+     */
+    pub(in crate::geometry) fn conflicts(&self, p: &DPoint) -> Option<BTreeSet<usize>> {
+        let start = self.locate(p)?;
+        if self.tets[start]
+            .vertices
+            .iter()
+            .any(|&v| self.points[v] == *p)
+        {
+            return None;
+        }
+
+        let mut cavity = BTreeSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(t) = stack.pop() {
+            for n in self.tets[t].neighbors.into_iter().flatten() {
+                if !cavity.contains(&n) && self.in_circumsphere(n, p) {
+                    cavity.insert(n);
+                    stack.push(n);
+                }
+            }
+        }
+        Some(cavity)
+    }
+
+    /**
+    Inserts the point stored at `idx` into the triangulation.
+
+    Parameters:
+    - `idx: usize` - The index of a point previously passed to `Triangulation::new`.
+
+    Returns:
+    - `bool` - `true` if the point was inserted, `false` if it lies outside the enclosing
+      tetrahedron.
+     */
+    pub(in crate::geometry) fn insert(&mut self, idx: usize) -> bool {
+        let p = self.points[idx];
+        // Grow the cavity over every tetrahedron whose circumsphere contains the point.
+        let mut cavity = match self.conflicts(&p) {
+            Some(cavity) => cavity,
+            None => return false,
+        };
+
+        // On (nearly) cospherical input the cavity may not be star-shaped from the point. Any
+        // boundary face that the point does not strictly see is absorbed into the cavity.
+        let boundary = loop {
+            let mut boundary = Vec::new();
+            let mut grow = Vec::new();
+            for &t in &cavity {
+                let tet = &self.tets[t];
+                for (i, face) in TET_FACES.iter().enumerate() {
+                    let neighbor = tet.neighbors[i];
+                    if neighbor.is_some_and(|n| cavity.contains(&n)) {
+                        continue;
+                    }
+                    let [a, b, c] = face.map(|j| tet.vertices[j]);
+                    if orient3d(&self.points[a], &self.points[b], &self.points[c], &p) < 0. {
+                        boundary.push((t, neighbor, [a, b, c]));
+                    } else {
+                        match neighbor {
+                            Some(n) => grow.push(n),
+                            None => return false,
+                        }
+                    }
+                }
+            }
+            if grow.is_empty() {
+                break boundary;
+            }
+            cavity.extend(grow);
+        };
+
+        for &t in &cavity {
+            self.tets[t].alive = false;
+        }
+
+        // Connect every boundary face to the new point, stitching the new tetrahedra together
+        // along the edges they share.
+        let mut edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for (old, outside, [a, b, c]) in boundary {
+            let new = self.tets.len();
+            self.tets.push(Tet {
+                vertices: [a, c, b, idx],
+                neighbors: [None, None, None, outside],
+                alive: true,
+            });
+
+            if let Some(n) = outside {
+                for slot in self.tets[n].neighbors.iter_mut() {
+                    if *slot == Some(old) {
+                        *slot = Some(new);
+                    }
+                }
+            }
+
+            for (slot, (u, v)) in [(0, (c, b)), (1, (a, b)), (2, (a, c))] {
+                let key = (u.min(v), u.max(v));
+                if let Some((other, other_slot)) = edges.remove(&key) {
+                    self.tets[new].neighbors[slot] = Some(other);
+                    self.tets[other].neighbors[other_slot] = Some(new);
+                } else {
+                    edges.insert(key, (new, slot));
+                }
+            }
+            self.last = new;
+            // Every vertex of a removed tetrahedron lies on the cavity boundary, so it is
+            // incident to one of the new tetrahedra.
+            for v in [a, b, c, idx] {
+                self.incident[v] = new;
+            }
+        }
+
+        true
+    }
+
+    /**
+    Returns the live tetrahedra, excluding every tetrahedron touching the enclosing tetrahedron.
+    All returned tetrahedra are positively oriented.
+     */
+    pub(in crate::geometry) fn tetrahedra(&self) -> Vec<[usize; 4]> {
+        self.tets
+            .iter()
+            .filter(|t| t.alive && !t.vertices.iter().any(|&v| self.is_super_vertex(v)))
+            .map(|t| t.vertices)
+            .collect()
+    }
+
+    /// Returns the number of tetrahedra ever created. Removed tetrahedra keep their index, so an
+    /// index stays valid (though possibly dead) as the triangulation grows.
+    pub(in crate::geometry) fn tet_count(&self) -> usize {
+        self.tets.len()
+    }
+
+    /// Returns the vertices of tetrahedron `t`, or `None` if it has been removed.
+    pub(in crate::geometry) fn tet(&self, t: usize) -> Option<&[usize; 4]> {
+        let tet = &self.tets[t];
+        tet.alive.then_some(&tet.vertices)
+    }
+
+    /// Returns the tetrahedron sharing the face of `t` opposite its `i`th vertex.
+    pub(in crate::geometry) fn neighbor(&self, t: usize, i: usize) -> Option<usize> {
+        self.tets[t].neighbors[i]
+    }
+
+    /**
+    Classifies every tetrahedron against a closed surface made of triangulation faces. Starting
+    outside the convex hull, a flood fill walks across faces and toggles between outside and
+    inside every time it crosses a face in `walls`.
+
+    Parameters:
+    - `walls: &HashSet<[usize; 3]>` - The faces of the surface, each with its indices sorted.
+
+    Returns:
+    - `Vec<bool>` - Whether each tetrahedron, indexed like `Triangulation::tet`, lies inside the
+      surface. Removed tetrahedra are never inside.
+     */
+    pub(in crate::geometry) fn enclosure(&self, walls: &HashSet<[usize; 3]>) -> Vec<bool> {
+        let mut inside: Vec<Option<bool>> = vec![None; self.tets.len()];
+        let mut stack: Vec<usize> = (0..self.tets.len())
+            .filter(|&t| {
+                let tet = &self.tets[t];
+                tet.alive && tet.vertices.iter().any(|&v| self.is_super_vertex(v))
+            })
+            .collect();
+        for &t in &stack {
+            inside[t] = Some(false);
+        }
+
+        while let Some(t) = stack.pop() {
+            let tet = &self.tets[t];
+            for (i, face) in TET_FACES.iter().enumerate() {
+                if let Some(n) = tet.neighbors[i] {
+                    if inside[n].is_some() {
+                        continue;
+                    }
+                    let mut key = face.map(|j| tet.vertices[j]);
+                    key.sort_unstable();
+                    inside[n] = Some(inside[t] != Some(walls.contains(&key)));
+                    stack.push(n);
+                }
+            }
+        }
+
+        self.tets
+            .iter()
+            .zip(inside)
+            .map(|(t, inside)| t.alive && inside == Some(true))
+            .collect()
+    }
+
+    /**
+    Separates the tetrahedra enclosed by a closed surface made of triangulation faces, as
+    classified by `Triangulation::enclosure`.
+
+    Parameters:
+    - `walls: &HashSet<[usize; 3]>` - The faces of the surface, each with its indices sorted.
+
+    Returns:
+    - `Vec<[usize; 4]>` - The positively oriented tetrahedra found inside the surface.
+     */
+    pub(in crate::geometry) fn enclosed_by(&self, walls: &HashSet<[usize; 3]>) -> Vec<[usize; 4]> {
+        self.tets
+            .iter()
+            .zip(self.enclosure(walls))
+            .filter(|(_, inside)| *inside)
+            .map(|(t, _)| t.vertices)
+            .collect()
+    }
+}
+
+/// Interleaves the bits of the quantized coordinates of `p` to give its position along a Z-order
+/// curve.
+fn morton_code(p: &DPoint, min: &DPoint, extent: f64) -> u64 {
+    let mut code = 0;
+    let q = ((p - min) / extent * 1023.).map(|c| c.clamp(0., 1023.) as u64);
+    for bit in 0..10 {
+        for (axis, value) in q.iter().enumerate() {
+            code |= ((value >> bit) & 1) << (3 * bit + axis);
+        }
+    }
+    code
+}
+
+/**
+Computes the Delaunay tetrahedralization of `points`. Points sharing exact coordinates with an
+earlier point are skipped, so the tetrahedra only ever reference the first copy.
+
+Parameters:
+- `points: &[DPoint]` - The points to tetrahedralize.
+
+Returns:
+- `Vec<[usize; 4]>` - Positively oriented tetrahedra indexing into `points`. Empty if the points
+  are all coplanar or there are fewer than four of them.
+ */
+pub(in crate::geometry) fn tetrahedralize(points: &[DPoint]) -> Vec<[usize; 4]> {
+    Triangulation::from_points(points)
+        .map(|t| t.tetrahedra())
+        .unwrap_or_default()
+}
+
+/// The point type used by the planar triangulation.
+pub(in crate::geometry) type DPoint2 = nalgebra::Point2<f64>;
+
+/**
+Computes the Delaunay triangulation of a small set of planar points with the Bowyer-Watson
+algorithm. Every insertion scans all triangles, so this is only intended for the handful of points
+making up a single polygon or facet.
+
+Parameters:
+- `points: &[DPoint2]` - The points to triangulate.
+
+Returns:
+- `Vec<[usize; 3]>` - Counter-clockwise triangles indexing into `points`. Points sharing exact
+  coordinates with an earlier point are left out.
+ */
+pub(in crate::geometry) fn triangulate2d(points: &[DPoint2]) -> Vec<[usize; 3]> {
+    let first = match points.first() {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+    let (mut min, mut max) = (first.coords, first.coords);
+    for p in points {
+        min = min.inf(&p.coords);
+        max = max.sup(&p.coords);
+    }
+    let center = DPoint2::from((min + max) / 2.);
+    let radius = ((max - min).norm() / 2.).max(f64::MIN_POSITIVE) * SUPER_SCALE;
+
+    let n = points.len();
+    let mut all = points.to_vec();
+    all.extend_from_slice(&[
+        center + nalgebra::vector![0., 2.] * radius,
+        center + nalgebra::vector![-3f64.sqrt(), -1.] * radius,
+        center + nalgebra::vector![3f64.sqrt(), -1.] * radius,
+    ]);
+    let mut triangles = vec![[n, n + 1, n + 2]];
+
+    let mut seen = HashSet::new();
+    for (i, p) in points.iter().enumerate() {
+        if !seen.insert(p.coords.map(f64::to_bits)) {
+            continue;
+        }
+
+        let contains =
+            |t: &[usize; 3]| (0..3).all(|k| orient2d(&all[t[k]], &all[t[(k + 1) % 3]], p) >= 0.);
+        let mut cavity: Vec<bool> = triangles
+            .iter()
+            .map(|t| incircle(&all[t[0]], &all[t[1]], &all[t[2]], p) > 0. || contains(t))
+            .collect();
+
+        // As in three dimensions, boundary edges the point does not strictly see are absorbed.
+        let boundary = loop {
+            let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+            for (t, tri) in triangles.iter().enumerate().filter(|(t, _)| cavity[*t]) {
+                for k in 0..3 {
+                    edges.insert((tri[k], tri[(k + 1) % 3]), t);
+                }
+            }
+            let boundary: Vec<(usize, usize)> = edges
+                .keys()
+                .filter(|(a, b)| !edges.contains_key(&(*b, *a)))
+                .copied()
+                .collect();
+            let hidden: Vec<(usize, usize)> = boundary
+                .iter()
+                .filter(|(a, b)| orient2d(&all[*a], &all[*b], p) <= 0.)
+                .copied()
+                .collect();
+            if hidden.is_empty() {
+                break boundary;
+            }
+            let mut grew = false;
+            for (t, tri) in triangles.iter().enumerate() {
+                if !cavity[t] && (0..3).any(|k| hidden.contains(&(tri[(k + 1) % 3], tri[k]))) {
+                    cavity[t] = true;
+                    grew = true;
+                }
+            }
+            if !grew {
+                break boundary;
+            }
+        };
+
+        let mut kept: Vec<[usize; 3]> = triangles
+            .iter()
+            .zip(&cavity)
+            .filter(|(_, &c)| !c)
+            .map(|(t, _)| *t)
+            .collect();
+        kept.extend(boundary.into_iter().map(|(a, b)| [a, b, i]));
+        triangles = kept;
+    }
+
+    triangles.retain(|t| t.iter().all(|&v| v < n));
+    triangles
+}
@@ -0,0 +1,154 @@
+/*! Inside/outside classification of points against a closed triangulated surface. Rays are cast
+along each coordinate axis and the surface crossings are counted; triangles are bucketed in a
+uniform grid over the plane perpendicular to the ray so each ray only visits nearby triangles. */
+
+use super::delaunay::DPoint;
+
+/// Buckets the triangles of a surface for rays cast along a single coordinate axis.
+struct AxisGrid {
+    axis: usize,
+    min: [f64; 2],
+    cell_size: [f64; 2],
+    resolution: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+/// Classifies points as inside or outside a closed triangulated surface.
+pub(in crate::geometry) struct RayCaster<'a> {
+    points: &'a [DPoint],
+    triangles: &'a [[usize; 3]],
+    grids: [AxisGrid; 3],
+}
+
+/// Returns the two axes perpendicular to `axis`.
+fn plane_axes(axis: usize) -> [usize; 2] {
+    [(axis + 1) % 3, (axis + 2) % 3]
+}
+
+impl AxisGrid {
+    fn new(axis: usize, points: &[DPoint], triangles: &[[usize; 3]]) -> AxisGrid {
+        let [u, v] = plane_axes(axis);
+        let resolution = ((triangles.len() as f64).sqrt().ceil() as usize).clamp(1, 256);
+
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for p in points {
+            for (k, a) in [u, v].into_iter().enumerate() {
+                min[k] = min[k].min(p[a]);
+                max[k] = max[k].max(p[a]);
+            }
+        }
+        let cell_size = [0, 1].map(|k| ((max[k] - min[k]) / resolution as f64).max(f64::EPSILON));
+
+        let mut grid = AxisGrid {
+            axis,
+            min,
+            cell_size,
+            resolution,
+            cells: vec![Vec::new(); resolution * resolution],
+        };
+
+        for (t, triangle) in triangles.iter().enumerate() {
+            let corners = triangle.map(|i| [points[i][u], points[i][v]]);
+            let lo = grid.cell_of(
+                corners.iter().map(|c| c[0]).fold(f64::INFINITY, f64::min),
+                corners.iter().map(|c| c[1]).fold(f64::INFINITY, f64::min),
+            );
+            let hi = grid.cell_of(
+                corners
+                    .iter()
+                    .map(|c| c[0])
+                    .fold(f64::NEG_INFINITY, f64::max),
+                corners
+                    .iter()
+                    .map(|c| c[1])
+                    .fold(f64::NEG_INFINITY, f64::max),
+            );
+            for i in lo.0..=hi.0 {
+                for j in lo.1..=hi.1 {
+                    grid.cells[i * resolution + j].push(t);
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, u: f64, v: f64) -> (usize, usize) {
+        let index = |value: f64, k: usize| {
+            (((value - self.min[k]) / self.cell_size[k]).floor().max(0.) as usize)
+                .min(self.resolution - 1)
+        };
+        (index(u, 0), index(v, 1))
+    }
+
+    /// Counts the triangles crossed by the ray leaving `p` in the positive `axis` direction.
+    fn crossings(&self, p: &DPoint, points: &[DPoint], triangles: &[[usize; 3]]) -> usize {
+        let [u, v] = plane_axes(self.axis);
+        let (i, j) = self.cell_of(p[u], p[v]);
+
+        self.cells[i * self.resolution + j]
+            .iter()
+            .filter(|&&t| {
+                let [a, b, c] = triangles[t].map(|i| &points[i]);
+                let edge = |s: &DPoint, e: &DPoint| {
+                    (e[u] - s[u]) * (p[v] - s[v]) - (e[v] - s[v]) * (p[u] - s[u])
+                };
+                // A ray passing exactly through an edge or vertex is nudged by an infinitesimal
+                // `(eps, eps^2)` offset, so it is counted by exactly one of the triangles there.
+                let side = |s: &DPoint, e: &DPoint, w: f64| {
+                    if w != 0. {
+                        w > 0.
+                    } else if e[v] != s[v] {
+                        e[v] < s[v]
+                    } else {
+                        e[u] > s[u]
+                    }
+                };
+                let weights = [edge(b, c), edge(c, a), edge(a, b)];
+                let sides = [
+                    side(b, c, weights[0]),
+                    side(c, a, weights[1]),
+                    side(a, b, weights[2]),
+                ];
+                let total: f64 = weights.iter().sum();
+                if total == 0. || sides[0] != sides[1] || sides[1] != sides[2] {
+                    return false;
+                }
+                let hit = (weights[0] * a[self.axis]
+                    + weights[1] * b[self.axis]
+                    + weights[2] * c[self.axis])
+                    / total;
+                hit > p[self.axis]
+            })
+            .count()
+    }
+}
+
+impl<'a> RayCaster<'a> {
+    /**
+    Prepares a classifier for the surface made of `triangles`.
+
+    Parameters:
+    - `points: &[DPoint]` - The vertices of the surface.
+    - `triangles: &[[usize; 3]]` - The triangles of the surface, indexing into `points`. The
+      surface is assumed to be closed.
+     */
+    pub(in crate::geometry) fn new(points: &'a [DPoint], triangles: &'a [[usize; 3]]) -> Self {
+        RayCaster {
+            points,
+            triangles,
+            grids: [0, 1, 2].map(|axis| AxisGrid::new(axis, points, triangles)),
+        }
+    }
+
+    /// Returns `true` if `p` lies inside the surface. The three axis-aligned rays vote, so a ray
+    /// grazing an edge or vertex of the surface does not decide the result on its own.
+    pub(in crate::geometry) fn contains(&self, p: &DPoint) -> bool {
+        self.grids
+            .iter()
+            .filter(|g| g.crossings(p, self.points, self.triangles) % 2 == 1)
+            .count()
+            >= 2
+    }
+}
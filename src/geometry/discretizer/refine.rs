@@ -0,0 +1,380 @@
+/*! Delaunay refinement of a recovered tetrahedralization, after Ruppert and Shewchuk. The
+circumcenter of every tetrahedron that is badly shaped or too large is inserted, unless it would
+encroach upon the surface: a point encroaches upon a sub-segment if it lies inside its diametral
+sphere and upon a sub-facet if it lies inside its equatorial sphere (or if inserting it would
+remove the sub-facet outright). Encroached sub-segments are split first, then encroached
+sub-facets, which keeps every new point inside the domain and every surface face intact. */
+
+use std::collections::{HashMap, HashSet};
+
+use super::constrained::Recoverer;
+use super::delaunay::{circumcenter, triangle_circumcenter, DPoint};
+use super::raycast::RayCaster;
+use crate::geometry::discmesh::{Tetrahedron, TET_EDGES, TET_FACES};
+
+/// The number of Steiner points refinement may insert per point of the recovered triangulation
+/// when no explicit limit is given.
+const STEINER_PER_POINT: usize = 64;
+
+/// Bounds on the shape and size of the tetrahedra produced by refinement.
+pub(in crate::geometry) struct QualityBounds {
+    /// The largest ratio of circumradius to shortest edge allowed.
+    pub(in crate::geometry) radius_edge: Option<f64>,
+    /// The smallest dihedral angle allowed, in radians.
+    pub(in crate::geometry) min_dihedral: Option<f64>,
+    /// The largest volume allowed anywhere.
+    pub(in crate::geometry) max_volume: Option<f64>,
+    /// Axis-aligned boxes given as `(min, max, max_volume)`, limiting the volume of the
+    /// tetrahedra whose centroids lie inside them.
+    pub(in crate::geometry) regions: Vec<(DPoint, DPoint, f64)>,
+    /// The most Steiner points refinement may insert.
+    pub(in crate::geometry) steiner_limit: Option<usize>,
+}
+
+/// A piece of the surface that a candidate point encroaches upon.
+enum Encroached {
+    SubSegment(usize, [usize; 2]),
+    SubFacet(usize, [usize; 3]),
+}
+
+struct Refiner<'a> {
+    recoverer: &'a mut Recoverer,
+    bounds: &'a QualityBounds,
+    caster: &'a RayCaster<'a>,
+    // Every sub-segment and sub-facet of the surface, keyed by their sorted vertices.
+    sub_segments: HashMap<[usize; 2], usize>,
+    sub_facets: HashMap<[usize; 3], usize>,
+    facet_faces: Vec<Vec<[usize; 3]>>,
+    facets_of_segment: Vec<Vec<usize>>,
+    inside: Vec<bool>,
+}
+
+impl Refiner<'_> {
+    /// Collects the faces of the triangulation lying on facet `f` after points were added to it or
+    /// its segments.
+    fn update_facet(&mut self, f: usize) {
+        for face in self.facet_faces[f].drain(..) {
+            self.sub_facets.remove(&face);
+        }
+
+        // Every point of a facet lies in its plane and within its corners, so a face made of
+        // facet points lies on the facet.
+        let points: HashSet<usize> = self.recoverer.facet_points(f).into_iter().collect();
+        let triangulation = &self.recoverer.triangulation;
+        for &v in &points {
+            for t in triangulation.star(v) {
+                let vertices = triangulation.tet(t).unwrap();
+                for face in TET_FACES {
+                    let mut key = face.map(|j| vertices[j]);
+                    key.sort_unstable();
+                    if key.contains(&v)
+                        && key.iter().all(|u| points.contains(u))
+                        && self.sub_facets.insert(key, f).is_none()
+                    {
+                        self.facet_faces[f].push(key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Classifies the tetrahedra created since the triangulation held `since` of them, and queues
+    /// them for inspection.
+    fn adopt(&mut self, since: usize, queue: &mut Vec<usize>) {
+        let triangulation = &self.recoverer.triangulation;
+        for t in since..triangulation.tet_count() {
+            // Tetrahedra do not straddle the surface while it is recovered, so the centroid
+            // decides. Ray casting keeps this right even when an insertion removed part of the
+            // surface, which the flood fill over surface faces would leak through.
+            let inside = triangulation.tet(t).is_some_and(|vertices| {
+                let centroid = vertices
+                    .iter()
+                    .fold(DPoint::origin(), |c, &v| c + triangulation.point(v).coords);
+                !vertices.iter().any(|&v| triangulation.is_super_vertex(v))
+                    && self.caster.contains(&(centroid / 4.))
+            });
+            self.inside.push(inside);
+            queue.push(t);
+        }
+    }
+
+    /// Returns whether tetrahedron `t` violates any of the quality bounds.
+    fn is_bad(&self, t: usize) -> bool {
+        let triangulation = &self.recoverer.triangulation;
+        let p = match triangulation.tet(t) {
+            Some(vertices) => vertices.map(|v| *triangulation.point(v)),
+            None => return false,
+        };
+
+        let tet = Tetrahedron::new(p);
+        let volume = tet.signed_volume();
+        let centroid = tet.centroid();
+        let too_large = self.bounds.max_volume.is_some_and(|v| volume > v)
+            || self.bounds.regions.iter().any(|(min, max, v)| {
+                volume > *v && (0..3).all(|k| (min[k]..=max[k]).contains(&centroid[k]))
+            });
+        if too_large {
+            return true;
+        }
+
+        if let Some(bound) = self.bounds.radius_edge {
+            let shortest = TET_EDGES
+                .iter()
+                .map(|[i, j]| (p[*i] - p[*j]).norm())
+                .fold(f64::INFINITY, f64::min);
+            match circumcenter(&p[0], &p[1], &p[2], &p[3]) {
+                Some(center) if (center - p[0]).norm() <= bound * shortest => {}
+                _ => return true,
+            }
+        }
+
+        // A flat tetrahedron has no face normals to measure its angles by, so it is always bad.
+        self.bounds
+            .min_dihedral
+            .is_some_and(|bound| volume <= 0. || tet.dihedral_angles().iter().any(|&a| a < bound))
+    }
+
+    /// Finds a sub-segment, or failing that a sub-facet, that inserting `c` would encroach upon.
+    fn encroached(&self, c: &DPoint) -> Option<Option<Encroached>> {
+        let triangulation = &self.recoverer.triangulation;
+        let cavity = triangulation.conflicts(c)?;
+
+        let mut facet = None;
+        for &t in &cavity {
+            let vertices = match triangulation.tet(t) {
+                Some(vertices) => vertices,
+                None => continue,
+            };
+
+            for [i, j] in TET_EDGES {
+                let mut key = [vertices[i], vertices[j]];
+                key.sort_unstable();
+                if let Some(&s) = self.sub_segments.get(&key) {
+                    let [a, b] = key.map(|v| triangulation.point(v));
+                    if (c - nalgebra::center(a, b)).norm() < (b - a).norm() / 2. {
+                        return Some(Some(Encroached::SubSegment(s, key)));
+                    }
+                }
+            }
+
+            if facet.is_some() {
+                continue;
+            }
+            for (i, face) in TET_FACES.iter().enumerate() {
+                let mut key = face.map(|j| vertices[j]);
+                key.sort_unstable();
+                if let Some(&f) = self.sub_facets.get(&key) {
+                    let [a, b, d] = key.map(|v| triangulation.point(v));
+                    let removed = triangulation
+                        .neighbor(t, i)
+                        .is_some_and(|n| cavity.contains(&n));
+                    let inside_sphere = triangle_circumcenter(a, b, d)
+                        .is_some_and(|center| (c - center).norm() < (a - center).norm());
+                    if removed || inside_sphere {
+                        facet = Some(Encroached::SubFacet(f, key));
+                    }
+                }
+            }
+        }
+
+        Some(facet)
+    }
+
+    /// Finds a sub-segment, or failing that a sub-facet, among the edges and faces of `t`.
+    fn surface_of(&self, t: usize) -> Option<Encroached> {
+        let vertices = self.recoverer.triangulation.tet(t)?;
+        let segment = TET_EDGES.iter().find_map(|[i, j]| {
+            let mut key = [vertices[*i], vertices[*j]];
+            key.sort_unstable();
+            let s = *self.sub_segments.get(&key)?;
+            Some(Encroached::SubSegment(s, key))
+        });
+        segment.or_else(|| {
+            TET_FACES.iter().find_map(|face| {
+                let mut key = face.map(|j| vertices[j]);
+                key.sort_unstable();
+                let f = *self.sub_facets.get(&key)?;
+                Some(Encroached::SubFacet(f, key))
+            })
+        })
+    }
+
+    /// Splits an encroached sub-segment or sub-facet.
+    fn split(&mut self, encroached: Encroached) -> bool {
+        match encroached {
+            Encroached::SubSegment(s, key) => self.split_sub_segment(s, key),
+            Encroached::SubFacet(f, key) => self.split_sub_facet(f, key),
+        }
+    }
+
+    /// Splits sub-segment `key` of segment `s`, updating the surface bookkeeping.
+    fn split_sub_segment(&mut self, s: usize, [a, b]: [usize; 2]) -> bool {
+        if !self.recoverer.split_segment(s, [a, b]) {
+            return false;
+        }
+        let m = *self.recoverer.segments[s].interior.last().unwrap();
+        self.sub_segments.remove(&[a, b]);
+        self.sub_segments.insert([a.min(m), a.max(m)], s);
+        self.sub_segments.insert([b.min(m), b.max(m)], s);
+        for f in self.facets_of_segment[s].clone() {
+            self.update_facet(f);
+        }
+        true
+    }
+
+    /**
+    Splits the sub-facet `face` of facet `f` at its circumcenter. If the circumcenter encroaches
+    upon a sub-segment of the facet, that sub-segment is split instead, and if it falls outside the
+    facet the centroid of `face` is used.
+     */
+    fn split_sub_facet(&mut self, f: usize, face: [usize; 3]) -> bool {
+        let [a, b, c] = face.map(|v| *self.recoverer.point(v));
+        let centroid = DPoint::from((a.coords + b.coords + c.coords) / 3.);
+        let center = triangle_circumcenter(&a, &b, &c)
+            .filter(|center| self.recoverer.facet_contains(f, center))
+            .unwrap_or(centroid);
+
+        for s in self.recoverer.facets[f].segments {
+            for [p, q] in self.recoverer.sub_segments(s) {
+                let (p3, q3) = (self.recoverer.point(p), self.recoverer.point(q));
+                if (center - nalgebra::center(p3, q3)).norm() < (q3 - p3).norm() / 2. {
+                    return self.split_sub_segment(s, [p.min(q), p.max(q)]);
+                }
+            }
+        }
+
+        match self.recoverer.triangulation.add_point(center) {
+            Some(idx) => {
+                self.recoverer.facets[f].interior.push(idx);
+                self.update_facet(f);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/**
+Refines the tetrahedra enclosed by the recovered surface of `recoverer` until each of them meets
+`bounds`, or until the Steiner point limit is reached. The surface must already be fully recovered.
+Splitting the surface can remove faces of neighbouring facets, so refinement alternates with
+surface recovery until neither inserts a point.
+
+Parameters:
+- `recoverer: &mut Recoverer` - The recovered triangulation to refine.
+- `bounds: &QualityBounds` - The bounds every enclosed tetrahedron should meet.
+
+Returns:
+- `bool` - Whether the surface is still fully recovered afterwards.
+ */
+pub(super) fn refine(recoverer: &mut Recoverer, bounds: &QualityBounds) -> bool {
+    let mut budget = bounds
+        .steiner_limit
+        .unwrap_or(STEINER_PER_POINT * recoverer.triangulation.point_count());
+
+    let points: Vec<DPoint> = (0..recoverer.input_count)
+        .map(|i| *recoverer.point(i))
+        .collect();
+    let triangles: Vec<[usize; 3]> = recoverer.facets.iter().map(|f| f.corners).collect();
+    let caster = RayCaster::new(&points, &triangles);
+
+    loop {
+        refine_round(recoverer, bounds, &caster, &mut budget);
+        let before = recoverer.triangulation.point_count();
+        if !recoverer.recover() {
+            return false;
+        }
+        if recoverer.triangulation.point_count() == before || budget == 0 {
+            return true;
+        }
+    }
+}
+
+/// Refines the enclosed tetrahedra until they all meet `bounds` or `budget` runs out, without
+/// checking the surface for faces removed along the way.
+fn refine_round(
+    recoverer: &mut Recoverer,
+    bounds: &QualityBounds,
+    caster: &RayCaster,
+    budget: &mut usize,
+) {
+    let mut facets_of_segment = vec![Vec::new(); recoverer.segments.len()];
+    for (f, facet) in recoverer.facets.iter().enumerate() {
+        for &s in &facet.segments {
+            facets_of_segment[s].push(f);
+        }
+    }
+    let sub_segments = (0..recoverer.segments.len())
+        .flat_map(|s| {
+            recoverer
+                .sub_segments(s)
+                .into_iter()
+                .map(move |[a, b]| ([a.min(b), a.max(b)], s))
+        })
+        .collect();
+
+    let (_, faces) = recoverer.skeleton();
+    let mut sub_facets = HashMap::new();
+    let mut facet_faces = Vec::with_capacity(recoverer.facets.len());
+    for f in 0..recoverer.facets.len() {
+        let (found, _) = recoverer.facet_faces(f, &faces);
+        for &face in &found {
+            sub_facets.insert(face, f);
+        }
+        facet_faces.push(found);
+    }
+    let walls: HashSet<[usize; 3]> = sub_facets.keys().copied().collect();
+    let inside = recoverer.triangulation.enclosure(&walls);
+
+    let mut refiner = Refiner {
+        recoverer,
+        bounds,
+        caster,
+        sub_segments,
+        sub_facets,
+        facet_faces,
+        facets_of_segment,
+        inside,
+    };
+
+    let mut queue: Vec<usize> = (0..refiner.inside.len())
+        .filter(|&t| refiner.inside[t])
+        .collect();
+    while let Some(t) = queue.pop() {
+        if *budget == 0 {
+            break;
+        }
+        if !refiner.inside[t] || !refiner.is_bad(t) {
+            continue;
+        }
+
+        let triangulation = &refiner.recoverer.triangulation;
+        let p = match triangulation.tet(t) {
+            Some(vertices) => vertices.map(|v| *triangulation.point(v)),
+            None => continue,
+        };
+        let center = circumcenter(&p[0], &p[1], &p[2], &p[3]);
+
+        // A sliver lying against the surface may have its circumcenter far outside the
+        // triangulation, in which case the surface it lies on is split instead.
+        let since = triangulation.tet_count();
+        let split = match center.and_then(|c| Some((c, refiner.encroached(&c)?))) {
+            Some((center, None)) => refiner.recoverer.triangulation.add_point(center).is_some(),
+            Some((_, Some(encroached))) => refiner.split(encroached),
+            None => match refiner.surface_of(t) {
+                Some(encroached) => refiner.split(encroached),
+                None => false,
+            },
+        };
+        if !split {
+            continue;
+        }
+
+        *budget -= 1;
+        refiner.adopt(since, &mut queue);
+        // The tetrahedron survives if the surface was split somewhere outside its circumsphere.
+        if refiner.recoverer.triangulation.tet(t).is_some() {
+            queue.push(t);
+        }
+    }
+}
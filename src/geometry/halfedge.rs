@@ -0,0 +1,296 @@
+/*! Half-edge connectivity of polygon meshes. Every face is bounded by a loop of half-edges, each
+running from one vertex of the face to the next, and every half-edge is paired with a twin running
+the other way along the same edge. Edges on the boundary of the mesh get a twin that belongs to no
+face, and those twins are linked into loops around the holes of the mesh, so that the next,
+previous and twin half-edges, and the faces and vertices around one another, are found in constant
+time each. Edges shared by more than two faces, or by two faces winding the same way along them,
+cannot be paired; they are reported as non-manifold and treated as boundary edges of each face. */
+
+use std::collections::HashMap;
+
+use crate::geometry::polymesh::{MeshError, PolyMesh};
+
+/// The connectivity of a polygon mesh as half-edges. Vertices, faces and half-edges are referred
+/// to by index, with vertices and faces numbered as in the mesh the structure was built from.
+#[derive(Clone, Debug, Default)]
+pub struct HalfEdgeMesh {
+    // The vertex each half-edge starts at.
+    origins: Vec<usize>,
+    // The face each half-edge bounds, or `None` for half-edges on the boundary of the mesh.
+    faces: Vec<Option<usize>>,
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    twins: Vec<usize>,
+    // A half-edge leaving each vertex, on the boundary if the vertex is, or `None` for vertices
+    // that no face uses.
+    vertex_edges: Vec<Option<usize>>,
+    // The half-edge from the first vertex of each face to its second.
+    first_edges: Vec<usize>,
+    non_manifold_edges: Vec<[usize; 2]>,
+}
+
+impl HalfEdgeMesh {
+    /**
+    Builds the half-edge connectivity of any mesh. Edges that cannot be paired are listed by
+    `get_non_manifold_edges`, rather than stopping the construction.
+    This method may return `MeshError` if:
+    - Face with less than 3 vertices: `MeshError::FormatError("Face does not have enough
+      vertices.")`
+    - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+
+    Parameters:
+    - `mesh: &M where M: PolyMesh` - The mesh to build the connectivity of.
+
+    Returns:
+    - `Result<HalfEdgeMesh, MeshError>` - The connectivity, else a `MeshError`.
+     */
+    pub fn new<M: PolyMesh>(mesh: &M) -> Result<HalfEdgeMesh, MeshError> {
+        let vertex_count = mesh.get_vertex_count();
+        let faces = mesh.get_faces();
+        let mut half_edges = HalfEdgeMesh {
+            vertex_edges: vec![None; vertex_count],
+            first_edges: Vec::with_capacity(faces.len()),
+            ..Default::default()
+        };
+
+        // The half-edges of each face, in order, followed by the twins of those on the boundary.
+        for (index, face) in faces.iter().enumerate() {
+            let face = &face[..];
+            if face.len() < 3 {
+                return Err(MeshError::FormatError(
+                    "Face does not have enough vertices.",
+                ));
+            }
+            if face.iter().any(|&v| v >= vertex_count) {
+                return Err(MeshError::IndexingError("Vertex not contained in mesh."));
+            }
+            let first = half_edges.origins.len();
+            half_edges.first_edges.push(first);
+            for (i, &vertex) in face.iter().enumerate() {
+                half_edges.origins.push(vertex);
+                half_edges.faces.push(Some(index));
+                half_edges.next.push(first + (i + 1) % face.len());
+                half_edges
+                    .prev
+                    .push(first + (i + face.len() - 1) % face.len());
+            }
+        }
+        let interior = half_edges.origins.len();
+        half_edges.twins = vec![usize::MAX; interior];
+
+        // Half-edges are grouped by the edge they lie along. An edge is manifold when it is used
+        // once, or twice in opposite directions.
+        let mut edges: HashMap<[usize; 2], Vec<usize>> = HashMap::with_capacity(interior);
+        for h in 0..interior {
+            let [a, b] = [half_edges.origins[h], half_edges.destination(h)];
+            edges.entry([a.min(b), a.max(b)]).or_default().push(h);
+        }
+        for (edge, uses) in &edges {
+            match uses[..] {
+                [h, g] if half_edges.origins[h] != half_edges.origins[g] => {
+                    half_edges.twins[h] = g;
+                    half_edges.twins[g] = h;
+                }
+                [_] => {}
+                _ => half_edges.non_manifold_edges.push(*edge),
+            }
+        }
+        half_edges.non_manifold_edges.sort_unstable();
+
+        // Each unpaired half-edge is given a twin on the boundary.
+        for h in 0..interior {
+            if half_edges.twins[h] == usize::MAX {
+                let twin = half_edges.origins.len();
+                half_edges.origins.push(half_edges.destination(h));
+                half_edges.faces.push(None);
+                half_edges.twins[h] = twin;
+                half_edges.twins.push(h);
+            }
+        }
+        let count = half_edges.origins.len();
+        half_edges.next.resize(count, usize::MAX);
+        half_edges.prev.resize(count, usize::MAX);
+
+        // A boundary half-edge ending at a vertex is followed by the boundary half-edge leaving it
+        // around the same fan of faces. It is found by turning about the vertex from the interior
+        // twin, across paired edges, until an unpaired one is reached.
+        for b in interior..count {
+            let mut h = half_edges.twins[b];
+            while half_edges.twins[half_edges.prev[h]] < interior {
+                h = half_edges.twins[half_edges.prev[h]];
+            }
+            let next = half_edges.twins[half_edges.prev[h]];
+            half_edges.next[b] = next;
+            half_edges.prev[next] = b;
+        }
+
+        // Vertices on the boundary start from a boundary half-edge, so that turning about them
+        // visits all of their fan.
+        for h in 0..count {
+            let vertex = half_edges.origins[h];
+            if half_edges.vertex_edges[vertex].is_none() || h >= interior {
+                half_edges.vertex_edges[vertex] = Some(h);
+            }
+        }
+
+        Ok(half_edges)
+    }
+
+    /// Returns the number of vertices, including those that no face uses.
+    pub fn get_vertex_count(&self) -> usize {
+        self.vertex_edges.len()
+    }
+
+    /// Returns the number of faces.
+    pub fn get_face_count(&self) -> usize {
+        self.first_edges.len()
+    }
+
+    /// Returns the number of half-edges, including those on the boundary.
+    pub fn get_half_edge_count(&self) -> usize {
+        self.origins.len()
+    }
+
+    /// Returns the edges, as pairs of vertices in increasing order, that are shared by more than
+    /// two faces or by two faces winding the same way along them.
+    pub fn get_non_manifold_edges(&self) -> &Vec<[usize; 2]> {
+        &self.non_manifold_edges
+    }
+
+    /// Whether every edge is shared by at most two faces, winding opposite ways along it.
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+    }
+
+    /// Returns the half-edge following `half_edge` around its face or boundary loop.
+    pub fn next(&self, half_edge: usize) -> usize {
+        self.next[half_edge]
+    }
+
+    /// Returns the half-edge preceding `half_edge` around its face or boundary loop.
+    pub fn prev(&self, half_edge: usize) -> usize {
+        self.prev[half_edge]
+    }
+
+    /// Returns the half-edge running the other way along the edge of `half_edge`.
+    pub fn twin(&self, half_edge: usize) -> usize {
+        self.twins[half_edge]
+    }
+
+    /// Returns the vertex `half_edge` starts at.
+    pub fn origin(&self, half_edge: usize) -> usize {
+        self.origins[half_edge]
+    }
+
+    /// Returns the vertex `half_edge` ends at.
+    pub fn destination(&self, half_edge: usize) -> usize {
+        self.origins[self.next[half_edge]]
+    }
+
+    /// Returns the face `half_edge` bounds, or `None` if it is on the boundary of the mesh.
+    pub fn face(&self, half_edge: usize) -> Option<usize> {
+        self.faces[half_edge]
+    }
+
+    /// Whether `half_edge` is on the boundary of the mesh, bounding no face.
+    pub fn is_boundary(&self, half_edge: usize) -> bool {
+        self.faces[half_edge].is_none()
+    }
+
+    /// Returns a half-edge leaving `vertex`, on the boundary if the vertex is, or `None` if no
+    /// face uses the vertex.
+    pub fn vertex_edge(&self, vertex: usize) -> Option<usize> {
+        self.vertex_edges[vertex]
+    }
+
+    /// Returns the half-edge from the first vertex of `face` to its second.
+    pub fn face_edge(&self, face: usize) -> usize {
+        self.first_edges[face]
+    }
+
+    /// Whether `vertex` is on the boundary of the mesh.
+    pub fn is_boundary_vertex(&self, vertex: usize) -> bool {
+        self.vertex_edges[vertex].is_some_and(|h| self.is_boundary(h))
+    }
+
+    /**
+    Iterates over the half-edges leaving `vertex`, turning about it from `vertex_edge`. Vertices
+    where several fans of faces meet only at the vertex are turned about within one fan.
+
+    Parameters:
+    - `vertex: usize` - The vertex to turn about.
+
+    Returns:
+    - `impl Iterator<Item = usize>` - The half-edges leaving the vertex.
+     */
+    pub fn outgoing_edges(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.vertex_edges[vertex];
+        std::iter::successors(start, move |&h| {
+            Some(self.twins[self.prev[h]]).filter(|&next| Some(next) != start)
+        })
+    }
+
+    /// Iterates over the vertices joined to `vertex` by an edge, in the order of `outgoing_edges`.
+    pub fn vertex_neighbors(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing_edges(vertex).map(|h| self.destination(h))
+    }
+
+    /// Iterates over the faces around `vertex`, in the order of `outgoing_edges`.
+    pub fn vertex_faces(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing_edges(vertex).filter_map(|h| self.faces[h])
+    }
+
+    /**
+    Iterates over the half-edges of a face or boundary loop, starting from `half_edge`.
+
+    Parameters:
+    - `half_edge: usize` - The half-edge to start from.
+
+    Returns:
+    - `impl Iterator<Item = usize>` - The half-edges of the loop, in order.
+     */
+    pub fn loop_edges(&self, half_edge: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(half_edge), move |&h| {
+            Some(self.next[h]).filter(|&next| next != half_edge)
+        })
+    }
+
+    /// Iterates over the half-edges of `face`, in the order of its vertices.
+    pub fn face_edges(&self, face: usize) -> impl Iterator<Item = usize> + '_ {
+        self.loop_edges(self.first_edges[face])
+    }
+
+    /// Iterates over the vertices of `face`, in order.
+    pub fn face_vertices(&self, face: usize) -> impl Iterator<Item = usize> + '_ {
+        self.face_edges(face).map(|h| self.origins[h])
+    }
+
+    /// Iterates over the faces across each edge of `face`, in order, with `None` for edges on the
+    /// boundary.
+    pub fn face_neighbors(&self, face: usize) -> impl Iterator<Item = Option<usize>> + '_ {
+        self.face_edges(face).map(|h| self.faces[self.twins[h]])
+    }
+
+    /**
+    Finds the loops of boundary half-edges around the holes of the mesh. Each loop runs the same
+    way as the twins of its half-edges, that is, against the winding of the faces it borders.
+
+    Returns:
+    - `Vec<Vec<usize>>` - The half-edges of each boundary loop, in order.
+     */
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.origins.len()];
+        let mut loops = Vec::new();
+        for h in 0..self.origins.len() {
+            if self.is_boundary(h) && !visited[h] {
+                let boundary: Vec<usize> = self.loop_edges(h).collect();
+                for &b in &boundary {
+                    visited[b] = true;
+                }
+                loops.push(boundary);
+            }
+        }
+        loops
+    }
+}
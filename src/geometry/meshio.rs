@@ -0,0 +1,558 @@
+/*! Loading and saving of surface meshes without naming their format. A `MeshRegistry` holds the
+readers and writers of the formats it supports, and picks one for a file from its extension or
+from its leading bytes. The default registry reads and writes OBJ, STL, PLY and Gmsh MSH files and
+writes VTK files, and other formats can be added by implementing `MeshReader` and `MeshWriter`. */
+
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use nalgebra::RealField;
+
+use crate::geometry::discmesh::GmshFormat;
+#[cfg(feature = "rayon")]
+use crate::geometry::polymesh::map_file;
+use crate::geometry::polymesh::{
+    detect_stl, open_file, write_error, write_file, MeshError, MutateMesh, PlyFormat, PolyMesh,
+    PolygonMesh, StlFormat, TriangleMesh,
+};
+use crate::geometry::vtk::{LegacyVtkFormat, VtkEncoding, VtkFields};
+use crate::Float;
+
+/// A reader of a mesh file format, which can be registered with a `MeshRegistry`. Readers produce
+/// meshes with coordinates stored as `T`.
+pub trait MeshReader<T: RealField + Copy = Float> {
+    /// The file extensions of the format, in lower case and without the leading dot.
+    fn extensions(&self) -> &[&str];
+
+    /**
+    Checks whether data looks like a file of this format, for data whose extension is unknown or
+    does not match its contents. Formats without a recognisable start should return `false`.
+
+    Parameters:
+    - `bytes: &[u8]` - The data to check.
+
+    Returns:
+    - `bool` - Whether the data looks like a file of this format.
+     */
+    fn detect(&self, bytes: &[u8]) -> bool;
+
+    /**
+    Reads a mesh from `reader`.
+
+    Parameters:
+    - `reader: &mut dyn Read` - The reader to read the mesh from.
+    - `path: Option<&Path>` - The path of the file being read, for formats that refer to other
+      files, or `None` if the data is not read from a file.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the reading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    fn read(
+        &self,
+        reader: &mut dyn Read,
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError>;
+
+    /**
+    Reads a mesh from data already in memory, such as a whole file. This reads the data through
+    `read` unless a reader can make use of having all of the data at once.
+
+    Parameters:
+    - `bytes: &[u8]` - The data to read the mesh from.
+    - `path: Option<&Path>` - The path of the file being read, for formats that refer to other
+      files, or `None` if the data is not read from a file.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the reading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    fn read_bytes(
+        &self,
+        bytes: &[u8],
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        self.read(&mut &bytes[..], path)
+    }
+}
+
+/// A writer of a mesh file format, which can be registered with a `MeshRegistry`. Writers take
+/// meshes with coordinates stored as `T`.
+pub trait MeshWriter<T: RealField + Copy = Float> {
+    /// The file extensions of the format, in lower case and without the leading dot.
+    fn extensions(&self) -> &[&str];
+
+    /**
+    Writes a mesh to `writer`.
+
+    Parameters:
+    - `mesh: &PolygonMesh<T>` - The mesh to write.
+    - `writer: &mut dyn Write` - The writer to write the mesh to.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, such as a `MeshError::FileError` holding the
+      `std::io::Error` that stopped it.
+     */
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError>;
+}
+
+/// Reads the whole of `reader`, translating failures into `MeshError::FileError`.
+fn read_bytes(reader: &mut dyn Read) -> Result<Vec<u8>, MeshError> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|source| MeshError::FileError {
+            message: "Could not read file.",
+            path: None,
+            source,
+        })?;
+    Ok(bytes)
+}
+
+/// The Wavefront OBJ format. Meshes read from files load the material libraries they reference.
+/// With the `rayon` feature, data in memory is parsed on every core, as `load_obj_parallel` does.
+pub struct Obj;
+
+impl<T: RealField + Copy + FromStr> MeshReader<T> for Obj {
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+
+    /// OBJ files have no header, so they are recognised by their first statement.
+    fn detect(&self, bytes: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+        let statement = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+        statement
+            .and_then(|line| line.split_ascii_whitespace().next())
+            .is_some_and(|keyword| {
+                ["v", "vt", "vn", "f", "g", "o", "s", "mtllib", "usemtl"].contains(&keyword)
+            })
+    }
+
+    fn read(
+        &self,
+        reader: &mut dyn Read,
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        PolygonMesh::parse_obj(BufReader::new(reader), path)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn read_bytes(
+        &self,
+        bytes: &[u8],
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        PolygonMesh::parse_obj_parallel(bytes, path)
+    }
+}
+
+impl<T: RealField + Copy> MeshWriter<T> for Obj {
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_obj_to(writer).map_err(write_error)
+    }
+}
+
+/// The STL format. Both encodings are read, and meshes are written in the encoding given.
+pub struct Stl(pub StlFormat);
+
+impl<T: RealField + Copy + FromStr> MeshReader<T> for Stl {
+    fn extensions(&self) -> &[&str] {
+        &["stl"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        detect_stl(bytes)
+    }
+
+    fn read(
+        &self,
+        reader: &mut dyn Read,
+        _: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        PolygonMesh::parse_stl(&read_bytes(reader)?)
+    }
+}
+
+impl<T: RealField + Copy> MeshWriter<T> for Stl {
+    fn extensions(&self) -> &[&str] {
+        &["stl"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_stl_to(writer, self.0)
+    }
+}
+
+/// The PLY format. All encodings are read, and meshes are written in the encoding given.
+pub struct Ply(pub PlyFormat);
+
+impl<T: RealField + Copy> MeshReader<T> for Ply {
+    fn extensions(&self) -> &[&str] {
+        &["ply"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"ply\n") || bytes.starts_with(b"ply\r\n")
+    }
+
+    fn read(
+        &self,
+        reader: &mut dyn Read,
+        _: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        PolygonMesh::parse_ply(&read_bytes(reader)?)
+    }
+}
+
+impl<T: RealField + Copy> MeshWriter<T> for Ply {
+    fn extensions(&self) -> &[&str] {
+        &["ply"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_ply_to(writer, self.0)
+    }
+}
+
+/// The Gmsh MSH format. The triangles of a file are read, with each physical surface as a patch,
+/// and meshes are split into triangles and written in the version and encoding given.
+pub struct Msh(pub GmshFormat);
+
+/**
+Converts a `TriangleMesh` into a `PolygonMesh` with the same faces, normals and patches.
+
+Parameters:
+- `triangles: &TriangleMesh<T>` - The mesh to convert.
+
+Returns:
+- `Result<PolygonMesh<T>, MeshError>` - The converted mesh, else a `MeshError`.
+ */
+fn to_polygon_mesh<T: RealField + Copy>(
+    triangles: &TriangleMesh<T>,
+) -> Result<PolygonMesh<T>, MeshError> {
+    let mut mesh = PolygonMesh::default();
+    for &vertex in triangles.get_vertices() {
+        mesh.add_vertex(vertex);
+    }
+    for (face, &normal) in triangles.get_faces().iter().zip(triangles.get_normals()) {
+        mesh.add_face(face, Some(normal))?;
+    }
+    for patch in triangles.get_patches() {
+        mesh.set_patch(&patch.name, &patch.faces)?;
+    }
+    Ok(mesh)
+}
+
+impl<T: RealField + Copy> MeshReader<T> for Msh {
+    fn extensions(&self) -> &[&str] {
+        &["msh"]
+    }
+
+    fn detect(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"$MeshFormat")
+    }
+
+    fn read(
+        &self,
+        reader: &mut dyn Read,
+        _: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let triangles = TriangleMesh::parse_msh(&read_bytes(reader)?)?;
+        Ok(Box::new(to_polygon_mesh(&triangles)?))
+    }
+}
+
+impl<T: RealField + Copy> MeshWriter<T> for Msh {
+    fn extensions(&self) -> &[&str] {
+        &["msh"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.to_triangle_mesh()?
+            .write_msh_to(writer, self.0)
+            .map_err(write_error)
+    }
+}
+
+/// The legacy VTK format, which meshes are written to as `POLYDATA` in the encoding given, along
+/// with their attributes. VTK files are not read.
+pub struct Vtk(pub LegacyVtkFormat);
+
+impl<T: RealField + Copy> MeshWriter<T> for Vtk {
+    fn extensions(&self) -> &[&str] {
+        &["vtk"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_vtk_to(writer, &VtkFields::default(), self.0)
+    }
+}
+
+/// The XML VTK `PolyData` format, which meshes are written to with their attributes and data
+/// arrays in the encoding given. VTK files are not read.
+pub struct Vtp(pub VtkEncoding);
+
+impl<T: RealField + Copy> MeshWriter<T> for Vtp {
+    fn extensions(&self) -> &[&str] {
+        &["vtp"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_xml_to(writer, &VtkFields::default(), true, self.0)
+    }
+}
+
+/// The XML VTK `UnstructuredGrid` format, which meshes are written to with their attributes and
+/// data arrays in the encoding given. VTK files are not read.
+pub struct Vtu(pub VtkEncoding);
+
+impl<T: RealField + Copy> MeshWriter<T> for Vtu {
+    fn extensions(&self) -> &[&str] {
+        &["vtu"]
+    }
+
+    fn write(&self, mesh: &PolygonMesh<T>, writer: &mut dyn Write) -> Result<usize, MeshError> {
+        mesh.write_xml_to(writer, &VtkFields::default(), false, self.0)
+    }
+}
+
+/// The readers and writers of the mesh formats that meshes with coordinates stored as `T` can be
+/// loaded from and saved to.
+pub struct MeshRegistry<T: RealField + Copy = Float> {
+    readers: Vec<Box<dyn MeshReader<T>>>,
+    writers: Vec<Box<dyn MeshWriter<T>>>,
+}
+
+/// Returns the extension of `path` in lower case, if it has one.
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+}
+
+impl Default for MeshRegistry {
+    /// Creates a registry of the formats this crate supports, holding meshes with coordinates
+    /// stored as `Float`. Use `MeshRegistry::default_with_precision` for another scalar type.
+    fn default() -> MeshRegistry {
+        MeshRegistry::default_with_precision()
+    }
+}
+
+impl MeshRegistry {
+    /// Creates a registry without any formats, holding meshes with coordinates stored as `Float`.
+    /// Use `MeshRegistry::default` for one of the formats this crate supports, and
+    /// `MeshRegistry::new_with_precision` for another scalar type.
+    pub fn new() -> MeshRegistry {
+        MeshRegistry::new_with_precision()
+    }
+}
+
+impl<T: RealField + Copy + FromStr> MeshRegistry<T> {
+    /// Creates a registry of the OBJ, STL, PLY and MSH formats and the VTK writers, holding meshes
+    /// with coordinates stored as `T`. It writes binary STL, little-endian binary PLY, binary
+    /// version 4.1 MSH and binary VTK files, with the arrays of XML VTK files appended raw.
+    pub fn default_with_precision() -> MeshRegistry<T> {
+        let mut registry = MeshRegistry::new_with_precision();
+        registry.register_reader(Obj);
+        registry.register_writer(Obj);
+        registry.register_reader(Stl(StlFormat::Binary));
+        registry.register_writer(Stl(StlFormat::Binary));
+        registry.register_reader(Ply(PlyFormat::BinaryLittleEndian));
+        registry.register_writer(Ply(PlyFormat::BinaryLittleEndian));
+        registry.register_reader(Msh(GmshFormat::BinaryV4));
+        registry.register_writer(Msh(GmshFormat::BinaryV4));
+        registry.register_writer(Vtk(LegacyVtkFormat::Binary));
+        registry.register_writer(Vtp(VtkEncoding::Appended));
+        registry.register_writer(Vtu(VtkEncoding::Appended));
+        registry
+    }
+}
+
+impl<T: RealField + Copy> MeshRegistry<T> {
+    /// Creates a registry without any formats, holding meshes with coordinates stored as `T`. Use
+    /// `MeshRegistry::default_with_precision` for one of the formats this crate supports.
+    pub fn new_with_precision() -> MeshRegistry<T> {
+        MeshRegistry {
+            readers: Vec::new(),
+            writers: Vec::new(),
+        }
+    }
+
+    /// Registers a reader. Readers registered later take precedence over earlier ones for the
+    /// same extension, so built-in formats can be replaced.
+    pub fn register_reader(&mut self, reader: impl MeshReader<T> + 'static) {
+        self.readers.push(Box::new(reader));
+    }
+
+    /// Registers a writer. Writers registered later take precedence over earlier ones for the
+    /// same extension, so built-in formats can be replaced.
+    pub fn register_writer(&mut self, writer: impl MeshWriter<T> + 'static) {
+        self.writers.push(Box::new(writer));
+    }
+
+    /**
+    Picks the reader for data with the extension passed in. A reader of the extension that
+    recognises the data is preferred, then any reader that recognises it, so that files with a
+    misleading extension are still read, and lastly a reader of the extension.
+
+    Parameters:
+    - `bytes: &[u8]` - The data to read.
+    - `extension: Option<&str>` - The extension of the data, if it is known.
+
+    Returns:
+    - `Option<&dyn MeshReader<T>>` - The reader, or `None` if no reader suits the data.
+     */
+    fn reader_for(&self, bytes: &[u8], extension: Option<&str>) -> Option<&dyn MeshReader<T>> {
+        let extension = extension.map(str::to_ascii_lowercase);
+        let matches = |reader: &&dyn MeshReader<T>| {
+            extension
+                .as_deref()
+                .is_some_and(|e| reader.extensions().contains(&e))
+        };
+        let readers = || self.readers.iter().rev().map(|r| r.as_ref());
+        readers()
+            .filter(matches)
+            .find(|r| r.detect(bytes))
+            .or_else(|| readers().find(|r| r.detect(bytes)))
+            .or_else(|| readers().find(matches))
+    }
+
+    /**
+    Reads a mesh from `reader`, picking its format from the extension passed in or from its
+    leading bytes.
+
+    - Data of no registered format: `MeshError::FormatError("Unrecognised mesh format.")`
+
+    Parameters:
+    - `reader: &mut dyn Read` - The reader to read the mesh from.
+    - `extension: Option<&str>` - The file extension of the data without the leading dot, if it
+      is known.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the reading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn read(
+        &self,
+        reader: &mut dyn Read,
+        extension: Option<&str>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let bytes = read_bytes(reader)?;
+        self.read_bytes(&bytes, extension, None)
+    }
+
+    /// Reads a mesh from data already in memory, as `read` does.
+    fn read_bytes(
+        &self,
+        bytes: &[u8],
+        extension: Option<&str>,
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let reader = self
+            .reader_for(bytes, extension)
+            .ok_or(MeshError::FormatError("Unrecognised mesh format."))?;
+        let mesh = reader.read_bytes(bytes, path);
+        match path {
+            Some(path) => mesh.map_err(|error| error.in_file(path)),
+            None => mesh,
+        }
+    }
+
+    /**
+    Loads a mesh from the file passed in, picking its format from the extension of the file or
+    from its leading bytes.
+
+    - Files of no registered format: `MeshError::FormatError("Unrecognised mesh format.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load(&self, filename: &str) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let path = Path::new(filename);
+        let extension = extension_of(path);
+        let mut file = open_file(filename)?;
+        // With the `rayon` feature the file is mapped rather than copied into memory, as
+        // `load_obj_parallel` does.
+        #[cfg(feature = "rayon")]
+        if let Some(bytes) = map_file(&file) {
+            return self.read_bytes(&bytes, extension.as_deref(), Some(path));
+        }
+        let bytes = read_bytes(&mut file).map_err(|error| error.in_file(path))?;
+        self.read_bytes(&bytes, extension.as_deref(), Some(path))
+    }
+
+    /// Picks the writer for the extension passed in, in lower case.
+    fn writer_for(&self, extension: &str) -> Result<&dyn MeshWriter<T>, MeshError> {
+        self.writers
+            .iter()
+            .rev()
+            .map(|w| w.as_ref())
+            .find(|w| w.extensions().contains(&extension))
+            .ok_or(MeshError::FormatError("Unsupported mesh file extension."))
+    }
+
+    /**
+    Writes a mesh to `writer` in the format of the extension passed in.
+
+    - Extension of no registered format: `MeshError::FormatError("Unsupported mesh file
+      extension.")`
+
+    Parameters:
+    - `mesh: &PolygonMesh<T>` - The mesh to write.
+    - `writer: &mut dyn Write` - The writer to write the mesh to.
+    - `extension: &str` - The file extension of the format, without the leading dot.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn write(
+        &self,
+        mesh: &PolygonMesh<T>,
+        writer: &mut dyn Write,
+        extension: &str,
+    ) -> Result<usize, MeshError> {
+        self.writer_for(&extension.to_ascii_lowercase())?
+            .write(mesh, writer)
+    }
+
+    /**
+    Saves a mesh to the file passed in, in the format of the extension of the file. The mesh is
+    written in full before the file is created, so a mesh the writer rejects leaves any file
+    already there untouched.
+
+    - Extension of no registered format: `MeshError::FormatError("Unsupported mesh file
+      extension.")`
+
+    Parameters:
+    - `mesh: &PolygonMesh<T>` - The mesh to save.
+    - `filename: &str` - A string containing the filename to save the mesh to.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the path of the file.
+     */
+    pub fn save(&self, mesh: &PolygonMesh<T>, filename: &str) -> Result<usize, MeshError> {
+        let extension = extension_of(Path::new(filename)).unwrap_or_default();
+        let mesh_writer = self.writer_for(&extension)?;
+        // The mesh is written to memory first, so that a mesh the writer rejects leaves any file
+        // already at `filename` as it was.
+        let mut bytes = Vec::new();
+        mesh_writer.write(mesh, &mut bytes)?;
+        write_file(filename, |file| file.write_all(&bytes).map(|_| bytes.len()))
+    }
+}
@@ -1,3 +1,7 @@
 pub mod polymesh;
 pub mod discretizer;
 pub mod discmesh;
+pub mod predicates;
+pub mod vtk;
+pub mod meshio;
+pub mod halfedge;
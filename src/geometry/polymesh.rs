@@ -3,25 +3,59 @@ processing functionality for Meshes. `PolygonMesh` represents the surface of a m
 boundaries can be defined using polygons. */
 
 use std::convert::{AsMut, AsRef};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error, ErrorKind, Write};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Write};
 use std::ops::{Index, RangeFull};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
-use nalgebra::{vector, Unit};
+use nalgebra::{RealField, Unit};
 use regex::Regex;
-
-use crate::{Float, Int, Point3, Uint, UnitVec3, Vec3};
+use simba::scalar::SubsetOf;
+
+use crate::{Float, Int, Point3, UnitVec3, Vec3};
+
+/// The `cleanup` module welds nearby vertices and removes faces and vertices that add nothing.
+mod cleanup;
+/// The `mtl` module reads and writes the material libraries referenced by OBJ files.
+mod mtl;
+/// The `parallel` module loads OBJ files on every core, with the `rayon` feature.
+#[cfg(feature = "rayon")]
+mod parallel;
+/// The `ply` module reads and writes ASCII and binary PLY files.
+mod ply;
+/// The `stl` module reads and writes ASCII and binary STL files.
+mod stl;
+/// The `triangulate` module splits the polygons of a `PolygonMesh` into triangles.
+mod triangulate;
+/// The `validate` module checks that a surface is closed, 2-manifold and consistently oriented.
+mod validate;
+
+pub use cleanup::CleanupReport;
+pub use mtl::{load_mtl, Material};
+#[cfg(feature = "rayon")]
+pub(crate) use parallel::map_file;
+pub use ply::PlyFormat;
+pub(crate) use stl::detect_stl;
+pub use stl::StlFormat;
+pub(crate) use triangulate::ear_clip;
+pub use triangulate::Triangulation;
+pub use validate::MeshReport;
 
 pub trait PolyMesh {
     type FaceType: Index<RangeFull, Output = [usize]>;
+    /// The scalar type of the vertex coordinates and normals.
+    type Scalar: RealField + Copy;
 
-    fn get_vertices(&self) -> &Vec<Point3>;
+    fn get_vertices(&self) -> &Vec<Point3<Self::Scalar>>;
     fn get_faces(&self) -> &Vec<Self::FaceType>;
-    fn get_normals(&self) -> &Vec<UnitVec3>;
+    fn get_normals(&self) -> &Vec<UnitVec3<Self::Scalar>>;
 
-    fn take_mut_vertices(&mut self) -> &mut Vec<Point3>;
+    fn take_mut_vertices(&mut self) -> &mut Vec<Point3<Self::Scalar>>;
     fn take_mut_faces(&mut self) -> &mut Vec<Self::FaceType>;
-    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3>;
+    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3<Self::Scalar>>;
 
     /**
     Retrieves the number of vertices in the mesh.
@@ -52,10 +86,10 @@ pub trait PolyMesh {
     - `idx: usize` - The index of the vertex to retrieve.
 
     Returns:
-    - `Result<&Point3, MeshError>` - Returns `&Point3` if the indexing succeeds, else
-    `MeshError::IndexingError`.
+    - `Result<&Point3<Self::Scalar>, MeshError>` - Returns `&Point3<Self::Scalar>` if the indexing
+      succeeds, else `MeshError::IndexingError`.
      */
-    fn get_vertex(&self, idx: usize) -> Result<&Point3, MeshError> {
+    fn get_vertex(&self, idx: usize) -> Result<&Point3<Self::Scalar>, MeshError> {
         self.get_vertices()
             .get(idx)
             .ok_or(MeshError::IndexingError("Indexing failed."))
@@ -69,7 +103,7 @@ pub trait PolyMesh {
 
     Returns:
     - `Result<&[usize], MeshError>` - Returns `&[usize]` if the indexing succeeds, else
-    `MeshError::IndexingError`.
+      `MeshError::IndexingError`.
      */
     fn get_face(&self, index: usize) -> Result<&[usize], MeshError> {
         self.get_faces()
@@ -85,28 +119,44 @@ pub trait PolyMesh {
     - `idx: usize` - The index of the face normal to retrieve.
 
     Returns:
-    - `Result<&UnitVec3, MeshError>` - Returns `&UnitVec3` if the indexing succeeds, else
-    `MeshError::IndexingError`.
+    - `Result<&UnitVec3<Self::Scalar>, MeshError>` - Returns `&UnitVec3<Self::Scalar>` if the
+      indexing succeeds, else `MeshError::IndexingError`.
      */
-    fn get_normal(&self, idx: usize) -> Result<&UnitVec3, MeshError> {
+    fn get_normal(&self, idx: usize) -> Result<&UnitVec3<Self::Scalar>, MeshError> {
         self.get_normals()
             .get(idx)
             .ok_or(MeshError::IndexingError("Indexing failed."))
     }
+
+    /**
+    Checks whether the mesh is a closed, 2-manifold and consistently oriented surface, along with
+    the other defects listed in `MeshReport`.
+
+    Returns:
+    - `Result<MeshReport, MeshError>` - The report, else `MeshError::IndexingError` if a face
+      references a vertex that does not exist.
+     */
+    fn validate(&self) -> Result<MeshReport, MeshError>
+    where
+        Self: Sized,
+    {
+        MeshReport::new(self)
+    }
 }
 
 pub(crate) trait MutateMesh: PolyMesh {
     /**
-    Adds `vertex: Point3` to the mesh and returns the index where the vertex will reside.
+    Adds `vertex: Point3<Self::Scalar>` to the mesh and returns the index where the vertex will
+    reside.
 
     Parameters:
-    - `vertex: Point3` - The vertex to be added.
+    - `vertex: Point3<Self::Scalar>` - The vertex to be added.
 
     Returns:
     - `usize` - The index at which the vertex is added.
 
      */
-    fn add_vertex(&mut self, vertex: Point3) -> usize;
+    fn add_vertex(&mut self, vertex: Point3<Self::Scalar>) -> usize;
 
     /**
     Attempts to add a face (and optionally the associated normal) to the mesh. If a normal is not
@@ -116,16 +166,16 @@ pub(crate) trait MutateMesh: PolyMesh {
 
     Parameters:
     - `face: &[usize]` - The vertices to be added as a face.
-    - `face_normal: Option<UnitVec3>` - The normal for the face.
+    - `face_normal: Option<UnitVec3<Self::Scalar>>` - The normal for the face.
 
     Returns:
     - `Result<usize, MeshError>` - Returns the index at which the face and its associated normal were
-    added.
+      added.
      */
     fn add_face(
         &mut self,
         face: &[usize],
-        face_normal: Option<UnitVec3>,
+        face_normal: Option<UnitVec3<Self::Scalar>>,
     ) -> Result<usize, MeshError>;
 
     /**
@@ -135,12 +185,16 @@ pub(crate) trait MutateMesh: PolyMesh {
 
     Parameters:
     - `face: &[usize]` - The vertices to be added as a face.
-    - `face_normal: Option<UnitVec3>` - The normal for the face.
+    - `face_normal: Option<UnitVec3<Self::Scalar>>` - The normal for the face.
 
     Returns:
     - `Option<MeshError>` - Returns errors if any happen (primarily, afaik, just MeshError::IndexingError)
      */
-    fn add_normals(&mut self, face: &[usize], face_normal: Option<UnitVec3>) -> Option<MeshError> {
+    fn add_normals(
+        &mut self,
+        face: &[usize],
+        face_normal: Option<UnitVec3<Self::Scalar>>,
+    ) -> Option<MeshError> {
         if let Some(normal) = face_normal {
             self.take_mut_normals().push(normal);
         } else {
@@ -153,29 +207,462 @@ pub(crate) trait MutateMesh: PolyMesh {
     }
 }
 
-/// `PolygonMesh` describes the input geometries pre-discretization for simulations.
-pub struct PolygonMesh {
-    pub(crate) vertices: Vec<Point3>,
+/// `PolygonMesh` describes the input geometries pre-discretization for simulations. Coordinates
+/// are stored as `T`, which defaults to the crate's `Float`.
+pub struct PolygonMesh<T: RealField + Copy = Float> {
+    pub(crate) vertices: Vec<Point3<T>>,
     pub(crate) faces: Vec<Vec<usize>>,
     // A vector of a vector of indices representing a set of vertices.
-    pub(crate) face_normals: Vec<UnitVec3>, // A vector of UnitVector3s
+    pub(crate) face_normals: Vec<UnitVec3<T>>, // A vector of UnitVector3s
+    pub(crate) vertex_attributes: Vec<Attribute>,
+    pub(crate) face_attributes: Vec<Attribute>,
+    pub(crate) texture_coords: Vec<Vec3<T>>,
+    pub(crate) vertex_normals: Vec<Vec3<T>>,
+    // For each face, the texture coordinate and vertex normal of each of its vertices, if given.
+    pub(crate) face_texture_coords: Vec<Option<Vec<usize>>>,
+    pub(crate) face_vertex_normals: Vec<Option<Vec<usize>>>,
+    pub(crate) groups: Vec<FaceGroup>,
+    pub(crate) objects: Vec<FaceGroup>,
+    pub(crate) smoothing_groups: Vec<Option<u32>>,
+    pub(crate) materials: Vec<Material>,
+    pub(crate) material_libraries: Vec<String>,
+    pub(crate) face_materials: Vec<Option<usize>>,
+    // Named boundary patches, each face belonging to at most one.
+    pub(crate) patches: Vec<FaceGroup>,
+    // The vertices of each polyline, such as those of OBJ `l` statements.
+    pub(crate) polylines: Vec<Vec<usize>>,
+}
+
+/// A named set of faces, such as an OBJ group or object, or a boundary patch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FaceGroup {
+    pub name: String,
+    /// The indices of the faces in the set, in ascending order.
+    pub faces: Vec<usize>,
+}
+
+impl<T: RealField + Copy> Default for PolygonMesh<T> {
+    fn default() -> Self {
+        PolygonMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+            face_normals: Vec::new(),
+            vertex_attributes: Vec::new(),
+            face_attributes: Vec::new(),
+            texture_coords: Vec::new(),
+            vertex_normals: Vec::new(),
+            face_texture_coords: Vec::new(),
+            face_vertex_normals: Vec::new(),
+            groups: Vec::new(),
+            objects: Vec::new(),
+            smoothing_groups: Vec::new(),
+            materials: Vec::new(),
+            material_libraries: Vec::new(),
+            face_materials: Vec::new(),
+            patches: Vec::new(),
+            polylines: Vec::new(),
+        }
+    }
+}
+
+/// The types that attribute values are stored as in mesh files. Every type converts to `f64`
+/// exactly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+/// A named array holding one value per vertex or per face of a mesh, such as a colour channel or
+/// a scalar field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    /// The type the values are written as.
+    pub value_type: AttributeType,
+    pub values: Vec<f64>,
 }
 
 /// `TriangleMesh` represents a PolygonMesh where all faces have exactly 3 vertices.
-pub struct TriangleMesh {
-    pub(crate) vertices: Vec<Point3>,
+pub struct TriangleMesh<T: RealField + Copy = Float> {
+    pub(crate) vertices: Vec<Point3<T>>,
     pub(crate) faces: Vec<[usize; 3]>,
     // A vector of a vector of indices representing a set of vertices.
-    pub(crate) face_normals: Vec<UnitVec3>, // A vector of UnitVector3s
+    pub(crate) face_normals: Vec<UnitVec3<T>>, // A vector of UnitVector3s
+    // Named boundary patches, each face belonging to at most one.
+    pub(crate) patches: Vec<FaceGroup>,
 }
 
-/// An enum containing error messages for PolygonMesh
-#[derive(Eq, PartialEq)]
+/// An enum containing error messages for PolygonMesh. Errors from reading files may also carry the
+/// file, the position in it and the `std::io::Error` that caused them.
+#[derive(Debug)]
 pub enum MeshError {
-    IOError(&'static str),
     FormatError(&'static str),
     IndexingError(&'static str),
     InvalidTriangle(&'static str),
+    /// The mesh is not a surface that bounds a volume, as found by `PolyMesh::validate`.
+    InvalidSurface(&'static str),
+    /// The surface could not be recovered within a tetrahedralization, so the boundary of the
+    /// volume mesh would not match it.
+    RecoveryError(&'static str),
+    /// A file could not be opened, read or written, along with the `std::io::Error` that caused it.
+    FileError {
+        message: &'static str,
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    /// An error found on a line of a file. The line and column count from 1, and `token` is the
+    /// text at the column that the error was found in.
+    LineError {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        token: String,
+        source: Box<MeshError>,
+    },
+    /// An error found in a record of a binary file, such as a vertex of a PLY file. `element` is
+    /// the kind of record and `record` counts from 1 among the records of that kind.
+    RecordError {
+        path: Option<PathBuf>,
+        element: String,
+        record: usize,
+        source: Box<MeshError>,
+    },
+}
+
+impl MeshError {
+    /**
+    Creates a `MeshError::FileError` for a failure to open, read or write the file `path`.
+
+    Parameters:
+    - `message: &'static str` - The message describing what failed.
+    - `path: &str` - The path of the file.
+    - `source: std::io::Error` - The error that caused the failure.
+
+    Returns:
+    - `MeshError` - The `MeshError::FileError`.
+     */
+    pub(crate) fn file(message: &'static str, path: &str, source: std::io::Error) -> Self {
+        MeshError::FileError {
+            message,
+            path: Some(PathBuf::from(path)),
+            source,
+        }
+    }
+
+    /// Attaches `path` to a `MeshError::FileError` or `MeshError::LineError` that was made
+    /// without the path of its file, leaving every other error as it is.
+    pub(crate) fn in_file(self, path: &Path) -> Self {
+        match self {
+            MeshError::FileError {
+                message,
+                path: None,
+                source,
+            } => MeshError::FileError {
+                message,
+                path: Some(path.to_path_buf()),
+                source,
+            },
+            MeshError::LineError {
+                path: None,
+                line,
+                column,
+                token,
+                source,
+            } => MeshError::LineError {
+                path: Some(path.to_path_buf()),
+                line,
+                column,
+                token,
+                source,
+            },
+            MeshError::RecordError {
+                path: None,
+                element,
+                record,
+                source,
+            } => MeshError::RecordError {
+                path: Some(path.to_path_buf()),
+                element,
+                record,
+                source,
+            },
+            error => error,
+        }
+    }
+
+    /// The error message, without the file or position it was found at.
+    pub fn message(&self) -> &'static str {
+        match self {
+            MeshError::FormatError(message)
+            | MeshError::IndexingError(message)
+            | MeshError::InvalidTriangle(message)
+            | MeshError::InvalidSurface(message)
+            | MeshError::RecoveryError(message)
+            | MeshError::FileError { message, .. } => message,
+            MeshError::LineError { source, .. } | MeshError::RecordError { source, .. } => {
+                source.message()
+            }
+        }
+    }
+
+    /// The error at the root of this one, with any line it was found on removed.
+    pub fn root_cause(&self) -> &MeshError {
+        match self {
+            MeshError::LineError { source, .. } | MeshError::RecordError { source, .. } => {
+                source.root_cause()
+            }
+            _ => self,
+        }
+    }
+
+    /// The file the error was found in, if it is known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            MeshError::FileError { path, .. }
+            | MeshError::LineError { path, .. }
+            | MeshError::RecordError { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The line the error was found on, counting from 1, if it was found on a line.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            MeshError::LineError { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// The column of the line the error was found at, counting from 1, if it was found on a line.
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            MeshError::LineError { column, .. } => Some(*column),
+            _ => None,
+        }
+    }
+
+    /// The text the error was found in, if it was found on a line.
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            MeshError::LineError { token, .. } => Some(token),
+            _ => None,
+        }
+    }
+
+    /// The kind of record the error was found in and its number among the records of that kind,
+    /// counting from 1, if it was found in a record of a binary file.
+    pub fn record(&self) -> Option<(&str, usize)> {
+        match self {
+            MeshError::RecordError {
+                element, record, ..
+            } => Some((element, *record)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::FileError {
+                message,
+                path: Some(path),
+                ..
+            } => write!(f, "{}: {}", path.display(), message),
+            MeshError::LineError {
+                path,
+                line,
+                column,
+                token,
+                source,
+            } => {
+                if let Some(path) = path {
+                    write!(f, "{}:", path.display())?;
+                }
+                write!(f, "{}:{}: {}", line, column, source)?;
+                if !token.is_empty() {
+                    write!(f, " Found `{}`.", token)?;
+                }
+                Ok(())
+            }
+            MeshError::RecordError {
+                path,
+                element,
+                record,
+                source,
+            } => {
+                if let Some(path) = path {
+                    write!(f, "{}: ", path.display())?;
+                }
+                write!(f, "{} {}: {}", element, record, source)
+            }
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    /// The `std::io::Error` behind the error, if there is one. The error a `MeshError::LineError`
+    /// wraps is part of its message, so its own source is returned instead.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshError::FileError { source, .. } => Some(source),
+            MeshError::LineError { source, .. } | MeshError::RecordError { source, .. } => {
+                source.source()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Errors are equal if they have the same message and position. The `std::io::Error`s of file
+/// errors are compared by their kind.
+impl PartialEq for MeshError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (MeshError::FormatError(a), MeshError::FormatError(b))
+            | (MeshError::IndexingError(a), MeshError::IndexingError(b))
+            | (MeshError::InvalidTriangle(a), MeshError::InvalidTriangle(b))
+            | (MeshError::InvalidSurface(a), MeshError::InvalidSurface(b))
+            | (MeshError::RecoveryError(a), MeshError::RecoveryError(b)) => a == b,
+            (
+                MeshError::FileError {
+                    message,
+                    path,
+                    source,
+                },
+                MeshError::FileError {
+                    message: other_message,
+                    path: other_path,
+                    source: other_source,
+                },
+            ) => {
+                message == other_message
+                    && path == other_path
+                    && source.kind() == other_source.kind()
+            }
+            (
+                MeshError::LineError {
+                    path,
+                    line,
+                    column,
+                    token,
+                    source,
+                },
+                MeshError::LineError {
+                    path: other_path,
+                    line: other_line,
+                    column: other_column,
+                    token: other_token,
+                    source: other_source,
+                },
+            ) => {
+                path == other_path
+                    && line == other_line
+                    && column == other_column
+                    && token == other_token
+                    && source == other_source
+            }
+            (
+                MeshError::RecordError {
+                    path,
+                    element,
+                    record,
+                    source,
+                },
+                MeshError::RecordError {
+                    path: other_path,
+                    element: other_element,
+                    record: other_record,
+                    source: other_source,
+                },
+            ) => {
+                path == other_path
+                    && element == other_element
+                    && record == other_record
+                    && source == other_source
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MeshError {}
+
+/// Lets `?` turn a `std::io::Error` into a `MeshError::FileError` of an unknown file.
+impl From<std::io::Error> for MeshError {
+    fn from(source: std::io::Error) -> Self {
+        let message = match source.kind() {
+            ErrorKind::NotFound => "File not found.",
+            ErrorKind::PermissionDenied => "Insufficient permissions.",
+            _ => "Input or output failed.",
+        };
+        MeshError::FileError {
+            message,
+            path: None,
+            source,
+        }
+    }
+}
+
+/**
+Opens `filename` for reading, translating failures into `MeshError::FileError`.
+
+Parameters:
+- `filename: &str` - A string containing the file path to open.
+
+Returns:
+- `Result<File, MeshError>` - Returns the opened `File`, else `MeshError::FileError`.
+ */
+pub(crate) fn open_file(filename: &str) -> Result<File, MeshError> {
+    File::open(filename).map_err(|e| {
+        let message = match e.kind() {
+            ErrorKind::NotFound => "File not found.",
+            ErrorKind::PermissionDenied => "Insufficient permissions.",
+            _ => "File failed to open.",
+        };
+        MeshError::file(message, filename, e)
+    })
+}
+
+/// Translates a failure to write mesh data into `MeshError::FileError`, without a path.
+pub(crate) fn write_error(source: Error) -> MeshError {
+    MeshError::FileError {
+        message: "Could not write file.",
+        path: None,
+        source,
+    }
+}
+
+/**
+Creates `filename` and writes to it through a buffer, translating failures into
+`MeshError::FileError`.
+
+Parameters:
+- `filename: &str` - A string containing the filename to write.
+- `write: impl FnOnce(&mut BufWriter<File>) -> Result<usize, Error>` - Writes the contents of the
+  file, returning the number of bytes written.
+
+Returns:
+- `Result<usize, MeshError>` - Returns the number of bytes written, else `MeshError::FileError`.
+ */
+pub(crate) fn write_file(
+    filename: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<usize, Error>,
+) -> Result<usize, MeshError> {
+    let file = File::create(filename)
+        .map_err(|e| MeshError::file("Could not create file.", filename, e))?;
+    let mut file = BufWriter::new(file);
+    write(&mut file)
+        .and_then(|bytes| file.flush().map(|_| bytes))
+        .map_err(|e| MeshError::file("Could not write file.", filename, e))
 }
 
 /**
@@ -189,10 +676,10 @@ Parameters:
 - `&[usize]` - The slice containing the indices of the vertices comprising this face.
 
 Returns:
-- `Result<UnitVec3, MeshError>` - Returns `Ok(UnitVec3)` if the method succeeds, else
-`MeshError::IndexingError` if the method cannot retrieve the necessary vertices.
+- `Result<UnitVec3<T::Scalar>, MeshError>` - Returns `Ok(UnitVec3<T::Scalar>)` if the method
+  succeeds, else `MeshError::IndexingError` if the method cannot retrieve the necessary vertices.
  */
-fn get_face_normal<T>(polymesh: &T, face: &[usize]) -> Result<UnitVec3, MeshError>
+fn get_face_normal<T>(polymesh: &T, face: &[usize]) -> Result<UnitVec3<T::Scalar>, MeshError>
 where
     T: PolyMesh + ?Sized,
 {
@@ -206,262 +693,1321 @@ where
 }
 
 /**
-A helper method to process strings from OBJ files into vertices.
-This method may return `Some(MeshError)` if:
+A helper method to parse the coordinates of `v`, `vt` and `vn` lines in OBJ files. Coordinates
+after the third are ignored, and missing optional coordinates are set to zero.
+This method may return `MeshError` if:
 - Float cannot be processed: `MeshError::FormatError("Failed to parse float.")`
-- Substrings could not generate: `MeshError::FormatError("Unable to process string.")`
+- Fewer than `required` coordinates: `MeshError::FormatError("Unable to process string.")`
 
 Parameters:
-- `polymesh: &mut PolygonMesh` - Reference to `PolygonMesh` object ot add vertices to.
-- `vertex_string: &str` - String slice to process.
+- `coordinate_string: &str` - String slice to process.
+- `required: usize` - The number of coordinates that must be given.
 
 Returns:
-- `Option<MeshReadError>` - If a failure occurred within the method. (Returns `None` if method
-succeeded)
+- `Result<[T; 3], ObjError>` - The coordinates, else a `MeshError::FormatError` and the
+  coordinate it was found at.
  */
-fn process_obj_vertices(polymesh: &mut PolygonMesh, vertex_string: &str) -> Option<MeshError> {
-    let mut point_strings = vertex_string.split_ascii_whitespace();
+fn parse_obj_coordinates<T>(
+    coordinate_string: &str,
+    required: usize,
+) -> Result<[T; 3], ObjError<'_>>
+where
+    T: RealField + Copy + FromStr,
+{
+    let mut point_strings = coordinate_string.split_ascii_whitespace();
 
-    let mut vertex_array: [Float; 3] = [0., 0., 0.];
-    for i in &mut vertex_array {
+    let mut coordinates: [T; 3] = [T::zero(); 3];
+    for (n, i) in coordinates.iter_mut().enumerate() {
         if let Some(numeric_string) = point_strings.next() {
             *i = match numeric_string.parse() {
                 Ok(f) => f,
-                Err(_) => {
-                    (match numeric_string.parse::<Int>() {
-                        Ok(f) => f,
-                        Err(_) => {
-                            return Some(MeshError::FormatError("Failed to parse float."));
-                        }
-                    }) as Float
-                }
+                Err(_) => nalgebra::convert(match numeric_string.parse::<Int>() {
+                    Ok(f) => f64::from(f),
+                    Err(_) => {
+                        return Err((
+                            MeshError::FormatError("Failed to parse float."),
+                            numeric_string,
+                        ));
+                    }
+                }),
             };
-        } else {
-            return Some(MeshError::FormatError("Unable to process string."));
+        } else if n < required {
+            // The missing coordinate is reported at the end of the line.
+            return Err((
+                MeshError::FormatError("Unable to process string."),
+                &coordinate_string[coordinate_string.len()..],
+            ));
         }
     }
-    polymesh.add_vertex(Point3::from(vertex_array));
 
-    None
+    Ok(coordinates)
+}
+
+/**
+Resolves an index from an OBJ face element. Positive indices count from 1 at the start of the file,
+and negative indices count back from -1 at the last element defined so far.
+
+- Index is zero or out of range: `MeshError::IndexingError(missing)`
+
+Parameters:
+- `index: isize` - The index to resolve.
+- `count: usize` - The number of elements defined so far.
+- `missing: &'static str` - The message to report if the index is out of range.
+
+Returns:
+- `Result<usize, MeshError>` - The zero-based index, else a `MeshError`.
+ */
+fn resolve_obj_index(
+    index: isize,
+    count: usize,
+    missing: &'static str,
+) -> Result<usize, MeshError> {
+    let resolved = if index < 0 {
+        count.checked_sub(index.unsigned_abs())
+    } else {
+        index.unsigned_abs().checked_sub(1)
+    };
+    resolved
+        .filter(|&i| i < count)
+        .ok_or(MeshError::IndexingError(missing))
+}
+
+/// Matches the OBJ statements that are recognised but not kept: comments and parameter space
+/// vertices. The expression is compiled once and shared by every load.
+fn obj_ignored_line() -> &'static Regex {
+    static IGNORED_LINE: OnceLock<Regex> = OnceLock::new();
+    IGNORED_LINE.get_or_init(|| Regex::new(r"(?m)^(?:#|vp)( +.*)?").unwrap())
+}
+
+/// The groups, object, smoothing group and material that faces read from an OBJ file are assigned
+/// to.
+#[derive(Default)]
+struct ObjState {
+    groups: Vec<usize>,
+    object: Option<usize>,
+    smoothing: Option<u32>,
+    material: Option<usize>,
+}
+
+/// An error found on a line of an OBJ file, along with the token of the line it was found at.
+type ObjError<'a> = (MeshError, &'a str);
+
+/// The indices of an OBJ face statement as written, before they are resolved against the mesh.
+struct ObjFace<'a> {
+    // The vertex, texture coordinate and vertex normal index of each vertex in turn, with `None`
+    // for indices that are not given.
+    indices: Vec<Option<isize>>,
+    // The text of the whole face, which errors in the face as a whole are reported at.
+    text: &'a str,
+    // The error of an index that could not be parsed, which ends `indices`. It is reported once
+    // the indices before it are resolved, as they are checked first.
+    error: Option<ObjError<'a>>,
+}
+
+/// A statement of an OBJ file, parsed without looking at the mesh it is read into. Statements can
+/// thus be parsed in any order, as long as they are applied to the mesh in file order.
+enum ObjStatement<'a, T> {
+    Vertex([T; 3]),
+    TextureCoord([T; 3]),
+    VertexNormal([T; 3]),
+    Face(ObjFace<'a>),
+    /// Any other statement, as its whole line.
+    Other(&'a str),
+}
+
+/**
+Parses the indices of an OBJ face statement. Each vertex of a face may also name a texture
+coordinate and a vertex normal, as `v/vt/vn`, `v//vn` or `v/vt`.
+
+Parameters:
+- `face_string: &str` - String slice to process.
+
+Returns:
+- `ObjFace` - The indices of the face, up to the first that could not be parsed.
+ */
+fn parse_obj_face(face_string: &str) -> ObjFace<'_> {
+    let mut face = ObjFace {
+        // A vertex takes at least two bytes of the text, so this is enough for any face.
+        indices: Vec::with_capacity(face_string.len().div_ceil(2) * 3),
+        text: face_string,
+        error: None,
+    };
+    for i in face_string.split_ascii_whitespace() {
+        let mut indices = i.split('/');
+        let vertex = Some(indices.next().unwrap_or_default());
+        let others = [indices.next(), indices.next()].map(|index| index.filter(|i| !i.is_empty()));
+        for index in [vertex, others[0], others[1]] {
+            match index.map(str::parse::<isize>) {
+                Some(Ok(index)) => face.indices.push(Some(index)),
+                Some(Err(_)) => {
+                    face.error = Some((MeshError::FormatError("Failed to parse integer."), i));
+                    return face;
+                }
+                None => face.indices.push(None),
+            }
+        }
+    }
+    face
+}
+
+/// The text of the vertex of an OBJ face at `index`, such as `1/2/3`, which errors in its indices
+/// are reported at.
+fn obj_face_element(face_string: &str, index: usize) -> &str {
+    face_string
+        .split_ascii_whitespace()
+        .nth(index)
+        .unwrap_or(face_string)
+}
+
+/**
+Parses a line of an OBJ file into a statement. The coordinates of `v`, `vt` and `vn` statements
+and the indices of `f` statements are parsed, while any other statement is kept as its line.
+This method may return `MeshError` if:
+- Float cannot be processed: `MeshError::FormatError("Failed to parse float.")`
+- Too few coordinates: `MeshError::FormatError("Unable to process string.")`
+
+Parameters:
+- `line: &str` - The line to parse, without surrounding whitespace.
+
+Returns:
+- `Result<ObjStatement<T>, ObjError>` - The statement, else a `MeshError::FormatError` and the
+  token it was found at.
+ */
+fn parse_obj_statement<T>(line: &str) -> Result<ObjStatement<'_, T>, ObjError<'_>>
+where
+    T: RealField + Copy + FromStr,
+{
+    let (keyword, rest) = line
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((line, &line[line.len()..]));
+    let rest = rest.trim();
+    Ok(match keyword {
+        "v" => ObjStatement::Vertex(parse_obj_coordinates(rest, 3)?),
+        // Only the first texture coordinate is required.
+        "vt" => ObjStatement::TextureCoord(parse_obj_coordinates(rest, 1)?),
+        "vn" => ObjStatement::VertexNormal(parse_obj_coordinates(rest, 3)?),
+        "f" => ObjStatement::Face(parse_obj_face(rest)),
+        _ => ObjStatement::Other(line),
+    })
 }
 
 /**
-A helper method to process strings from OBJ files into faces.
+A helper method to add a parsed OBJ face to a mesh, resolving its indices. Every vertex of a face
+must name the same kinds of index. The face joins the groups, object, smoothing group and material
+in `state`.
 This method may return `Some(MeshError)` if:
 - Integers cannot be processed: `MeshError::FormatError("Failed to parse integer.")`
-- Substrings could not generate: `MeshError::FormatError("Failed to retrieve substring of face
-element.")`
+- Vertices name different kinds: `MeshError::FormatError("Inconsistent face element.")`
 - Face has less than 3 vertices: `MeshError::FormatError("Face does not have enough vertices.")`
 - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in mesh")`
+- Texture coordinate out of range: `MeshError::IndexingError("Texture coordinate not contained in
+  mesh.")`
+- Vertex normal out of range: `MeshError::IndexingError("Vertex normal not contained in mesh.")`
 - Vertex referenced in face could not be found: `MeshError::IndexingError("Could not load vertex.")`
 
 Parameters:
-- `polymesh: &mut PolygonMesh` - Reference to `PolygonMesh` object ot add faces (and normals) to.
-- `face_string: &str` - String slice to process.
+- `polymesh: &mut PolygonMesh<T>` - Reference to `PolygonMesh` object ot add faces (and normals)
+  to.
+- `parsed: ObjFace` - The indices of the face.
+- `state: &ObjState` - The groups, object, smoothing group and material the face belongs to.
 
 Returns:
-- `Option<MeshReadError>` - If a failure occurred within the method. (Returns `None` if method
-succeeded)
+- `Option<ObjError>` - If a failure occurred within the method, along with the token it was found
+  at. (Returns `None` if method succeeded)
  */
-fn process_obj_faces(polymesh: &mut PolygonMesh, face_string: &str) -> Option<MeshError> {
-    let face_strings = face_string.split_ascii_whitespace();
-
-    let mut face: Vec<usize> = Vec::new();
-    for i in face_strings {
-        if let Some(index) = i.split('/').next() {
-            let vertex = match index.parse::<Uint>() {
-                Ok(i) => i,
-                Err(_) => {
-                    return Some(MeshError::FormatError("Failed to parse integer."));
-                }
-            } - 1;
-            if vertex < polymesh.get_vertex_count() {
-                face.push(vertex);
-            } else {
-                return Some(MeshError::IndexingError("Vertex not contained in mesh."));
+fn process_obj_faces<'a, T: RealField + Copy>(
+    polymesh: &mut PolygonMesh<T>,
+    parsed: ObjFace<'a>,
+    state: &ObjState,
+) -> Option<ObjError<'a>> {
+    let mut face: Vec<usize> = Vec::with_capacity(parsed.indices.len() / 3);
+    let mut texture_coords: Vec<usize> = Vec::new();
+    let mut vertex_normals: Vec<usize> = Vec::new();
+    for (i, index) in parsed.indices.into_iter().enumerate() {
+        let (count, missing, resolved) = match i % 3 {
+            0 => (
+                polymesh.get_vertex_count(),
+                "Vertex not contained in mesh.",
+                &mut face,
+            ),
+            1 => (
+                polymesh.texture_coords.len(),
+                "Texture coordinate not contained in mesh.",
+                &mut texture_coords,
+            ),
+            _ => (
+                polymesh.vertex_normals.len(),
+                "Vertex normal not contained in mesh.",
+                &mut vertex_normals,
+            ),
+        };
+        if let Some(index) = index {
+            match resolve_obj_index(index, count, missing) {
+                Ok(index) => resolved.push(index),
+                Err(e) => return Some((e, obj_face_element(parsed.text, i / 3))),
             }
-        } else {
-            return Some(MeshError::FormatError(
-                "Failed to retrieve\
-                                                        substring of face element.",
-            ));
         }
     }
+    if let Some(error) = parsed.error {
+        return Some(error);
+    }
 
     if face.len() < 3 {
-        return Some(MeshError::FormatError(
-            "Face does not have enough verticies.",
+        return Some((
+            MeshError::FormatError("Face does not have enough verticies."),
+            parsed.text,
         ));
     }
 
-    match polymesh.add_face(face.as_slice(), None) {
-        Ok(_) => {}
-        Err(e) => return Some(e),
+    // Each kind of index must be given for every vertex of the face or for none of them.
+    let mut per_face = [None, None];
+    for (indices, resolved) in [texture_coords, vertex_normals]
+        .into_iter()
+        .zip(&mut per_face)
+    {
+        if indices.len() == face.len() {
+            *resolved = Some(indices);
+        } else if !indices.is_empty() {
+            return Some((
+                MeshError::FormatError("Inconsistent face element."),
+                parsed.text,
+            ));
+        }
+    }
+
+    let index = match polymesh.push_face(face, None) {
+        Ok(index) => index,
+        Err(e) => return Some((e, parsed.text)),
+    };
+    let [texture_coords, vertex_normals] = per_face;
+    polymesh.face_texture_coords[index] = texture_coords;
+    polymesh.face_vertex_normals[index] = vertex_normals;
+    polymesh.smoothing_groups[index] = state.smoothing;
+    polymesh.face_materials[index] = state.material;
+    for &group in &state.groups {
+        polymesh.groups[group].faces.push(index);
+    }
+    if let Some(object) = state.object {
+        polymesh.objects[object].faces.push(index);
     }
 
     None
 }
 
-impl PolygonMesh {
-    /**
-    Loads a `PolygonMesh` from the filename passed in.
-
-    Parameters:
-    - `filename: &str` - A string containing the file path to load.
+/**
+Applies a parsed OBJ statement to a mesh. Vertices, texture coordinates, vertex normals and faces
+are added to the mesh, while the other statements change the groups, object, smoothing group and
+material in `state`, or load material libraries. Statements must be applied in file order.
+This method may return `Some(MeshError)` if:
+- Unknown statement: `MeshError::FormatError("Invalid file line.")`
+- Smoothing group cannot be processed: `MeshError::FormatError("Failed to parse integer.")`
+- Face cannot be added, as given by `process_obj_faces`
+- Material library cannot be loaded, as given by `load_mtl`
 
-    Returns:
-    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the loading
-    succeeded, otherwise a `MeshError` of some form, depending on the error.
-     */
-    pub fn load_obj(filename: &str) -> Result<Box<PolygonMesh>, MeshError> {
-        let mut polymesh = PolygonMesh {
-            vertices: Vec::with_capacity(4),
-            faces: Vec::with_capacity(4),
-            face_normals: Vec::with_capacity(4),
-        };
+Parameters:
+- `polymesh: &mut PolygonMesh<T>` - Reference to `PolygonMesh` object to apply the statement to.
+- `state: &mut ObjState` - The groups, object, smoothing group and material of later faces.
+- `statement: ObjStatement<T>` - The statement to apply.
+- `directory: Option<&Path>` - The directory of the OBJ file, if it is a file.
 
-        // To implement file processing
-        let file: File;
-        match File::open(filename) {
-            Ok(f) => file = f,
-            Err(e) => {
-                return match e.kind() {
-                    ErrorKind::NotFound => Err(MeshError::IOError("File not found.")),
-                    ErrorKind::PermissionDenied => {
-                        Err(MeshError::IOError("Insufficient permissions."))
+Returns:
+- `Option<ObjError>` - If a failure occurred within the method, along with the token it was found
+  at. (Returns `None` if method succeeded)
+ */
+fn apply_obj_statement<'a, T: RealField + Copy>(
+    polymesh: &mut PolygonMesh<T>,
+    state: &mut ObjState,
+    statement: ObjStatement<'a, T>,
+    directory: Option<&Path>,
+) -> Option<ObjError<'a>> {
+    let line = match statement {
+        ObjStatement::Vertex(vertex) => {
+            polymesh.add_vertex(Point3::from(vertex));
+            return None;
+        }
+        ObjStatement::TextureCoord(coordinates) => {
+            polymesh.texture_coords.push(Vec3::from(coordinates));
+            return None;
+        }
+        // The normals are kept as given, without normalizing them.
+        ObjStatement::VertexNormal(normal) => {
+            polymesh.vertex_normals.push(Vec3::from(normal));
+            return None;
+        }
+        ObjStatement::Face(face) => return process_obj_faces(polymesh, face, state),
+        ObjStatement::Other(line) => line,
+    };
+
+    let (keyword, rest) = line
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((line, &line[line.len()..]));
+    let rest = rest.trim();
+    match keyword {
+        // A `g` line without names returns faces to the default group, which isn't kept.
+        "g" => {
+            state.groups = rest
+                .split_ascii_whitespace()
+                .map(|name| find_or_add_group(&mut polymesh.groups, name))
+                .collect();
+            None
+        }
+        "mtllib" => process_obj_material_libraries(polymesh, rest, directory),
+        // Materials the libraries do not define are kept by name, without properties.
+        "usemtl" => {
+            state.material = Some(
+                match polymesh.materials.iter().position(|m| m.name == rest) {
+                    Some(index) => index,
+                    None => {
+                        polymesh.materials.push(Material {
+                            name: rest.to_string(),
+                            ..Default::default()
+                        });
+                        polymesh.materials.len() - 1
                     }
-                    _ => Err(MeshError::IOError("File failed to open.")),
-                }
-            }
+                },
+            );
+            None
         }
-
-        // Assists with processing files using a buffer (to save us from the catastrophe that large
-        // files can cause)
-        let mut bufread = BufReader::new(file);
-        let mut buffer_string = String::new();
-
-        while match bufread.read_line(&mut buffer_string) {
-            Ok(t) => t != 0,
-            Err(_) => {
-                return Err(MeshError::IOError("Could not read next line."));
+        "o" => {
+            state.object = Some(find_or_add_group(&mut polymesh.objects, rest));
+            None
+        }
+        "l" => process_obj_polyline(polymesh, rest),
+        "s" => match rest {
+            "off" | "0" => {
+                state.smoothing = None;
+                None
             }
-        } {
-            buffer_string = buffer_string.trim().to_string();
-            if buffer_string.starts_with("v ") {
-                if let Some(error) =
-                    process_obj_vertices(&mut polymesh, buffer_string.trim_start_matches("v "))
-                {
-                    return Err(error);
-                }
-                buffer_string = String::new();
-                continue;
-            } else if buffer_string.starts_with("f ") {
-                if let Some(error) =
-                    process_obj_faces(&mut polymesh, buffer_string.trim_start_matches("f "))
-                {
-                    return Err(error);
+            _ => match rest.parse::<u32>() {
+                Ok(group) => {
+                    state.smoothing = Some(group);
+                    None
                 }
-                buffer_string = String::new();
-                continue;
-            } else if Regex::new(r"(?m)^(?:#|v[tnp]|g|o|s|usemtl|mtllib|l)( +.*)?")
-                .unwrap()
-                .is_match_at(buffer_string.as_str(), 0)
-                || buffer_string.is_empty()
-            {
-                buffer_string = String::new();
-                continue;
-            } else {
-                return Err(MeshError::FormatError("Invalid file line."));
-            }
-        }
-
-        Ok(Box::new(polymesh))
+                Err(_) => Some((MeshError::FormatError("Failed to parse integer."), rest)),
+            },
+        },
+        _ if line.is_empty() || obj_ignored_line().is_match_at(line, 0) => None,
+        _ => Some((MeshError::FormatError("Invalid file line."), keyword)),
     }
+}
 
-    /**
-    Writes a `PolygonMesh` to the filename passed in.
-
-    Parameters:
-    - `filename: &str` - A string containing the filename to save them mesh to.
+/**
+A helper method to add the polyline of an OBJ `l` statement to a mesh. Only the vertex of each
+element is kept, so texture coordinates given as `v/vt` are dropped.
+This method may return `Some(MeshError)` if:
+- Integers cannot be processed: `MeshError::FormatError("Failed to parse integer.")`
+- Polyline has less than 2 vertices: `MeshError::FormatError("Polyline does not have enough
+  vertices.")`
+- Vertex referenced in polyline out of range: `MeshError::IndexingError("Vertex not contained in
+  mesh.")`
 
-    Returns:
-    - `Result<usize, Error>` - Returns the number of bytes written if file-writing is successful
-    otherwise returns an `std::io::Error`, given by the methods called in this method.
-     */
-    pub fn write_obj(&self, filename: &str) -> Result<usize, Error> {
-        let mut file = File::create(filename)?;
-        let mut bytes: usize = 0;
+Parameters:
+- `polymesh: &mut PolygonMesh<T>` - Reference to `PolygonMesh` object to add the polyline to.
+- `line_string: &str` - String slice listing the elements of the polyline.
 
-        for vertex in &self.vertices {
-            let string = format!("v {} {} {}", vertex.x, vertex.y, vertex.z);
-            writeln!(file, "{}", string)?;
-            bytes += string.len() + 1;
+Returns:
+- `Option<ObjError>` - If a failure occurred within the method, along with the token it was found
+  at. (Returns `None` if method succeeded)
+ */
+fn process_obj_polyline<'a, T: RealField + Copy>(
+    polymesh: &mut PolygonMesh<T>,
+    line_string: &'a str,
+) -> Option<ObjError<'a>> {
+    let mut polyline = Vec::new();
+    for element in line_string.split_ascii_whitespace() {
+        let index = element.split('/').next().unwrap_or_default();
+        let vertex = match index.parse::<isize>() {
+            Ok(index) => index,
+            Err(_) => return Some((MeshError::FormatError("Failed to parse integer."), element)),
+        };
+        match resolve_obj_index(
+            vertex,
+            polymesh.vertices.len(),
+            "Vertex not contained in mesh.",
+        ) {
+            Ok(vertex) => polyline.push(vertex),
+            Err(error) => return Some((error, element)),
         }
+    }
+    if polyline.len() < 2 {
+        return Some((
+            MeshError::FormatError("Polyline does not have enough vertices."),
+            line_string,
+        ));
+    }
+    polymesh.polylines.push(polyline);
+    None
+}
 
-        for face in &self.faces {
-            let string: Vec<String> = face.iter().map(|f| (f + 1).to_string()).collect();
-            let string = format!("f {}", string.join(" "));
-            writeln!(file, "{}", string)?;
-            bytes += string.len() + 1;
-        }
+/**
+Loads the MTL libraries named on an `mtllib` line into `polymesh`, resolving them relative to the
+directory of the OBJ file. Libraries that do not exist are skipped, so that the names given by
+`usemtl` are still kept, and materials already known by name are replaced by the library's
+definition. OBJ data read from outside a file has no directory, so only the names of its libraries
+are kept.
 
-        Ok(bytes)
-    }
+Parameters:
+- `polymesh: &mut PolygonMesh<T>` - Reference to `PolygonMesh` object to add materials to.
+- `library_string: &str` - String slice listing the libraries.
+- `directory: Option<&Path>` - The directory of the OBJ file being loaded, if it is a file.
 
-    pub fn to_triangle_mesh(&self) -> Result<TriangleMesh, MeshError> {
-        let mut mesh = TriangleMesh {
-            vertices: self.vertices.clone(),
-            faces: Vec::with_capacity(4),
-            face_normals: Vec::with_capacity(4),
+Returns:
+- `Option<ObjError>` - If a failure occurred within the method, along with the library it was
+  found in. (Returns `None` if method succeeded)
+ */
+fn process_obj_material_libraries<'a, T: RealField + Copy>(
+    polymesh: &mut PolygonMesh<T>,
+    library_string: &'a str,
+    directory: Option<&Path>,
+) -> Option<ObjError<'a>> {
+    for library in library_string.split_ascii_whitespace() {
+        polymesh.material_libraries.push(library.to_string());
+        let Some(directory) = directory else {
+            continue;
         };
-
-        for (face, normal) in self.faces.iter().zip(self.face_normals.iter()) {
-            let mut center_of_face: Vec3 = vector![0., 0., 0.];
-            for vertex in face {
-                let t = self.get_vertex(*vertex)?;
-                center_of_face
-                    .clone()
-                    .add_to(&t.coords.xyz(), &mut center_of_face);
+        let materials = match load_mtl(&directory.join(library).to_string_lossy()) {
+            Ok(materials) => materials,
+            Err(MeshError::FileError { source, .. }) if source.kind() == ErrorKind::NotFound => {
+                continue
+            }
+            Err(e) => return Some((e, library)),
+        };
+        for material in materials {
+            match polymesh
+                .materials
+                .iter()
+                .position(|m| m.name == material.name)
+            {
+                Some(index) => polymesh.materials[index] = material,
+                None => polymesh.materials.push(material),
             }
+        }
+    }
+
+    None
+}
 
-            center_of_face /= face.len() as Float;
+/**
+Finds the set called `name` in `sets`, adding an empty one if there is none.
 
-            let center = Point3::from(center_of_face);
-            let center_vertex_index = mesh.add_vertex(center);
+Parameters:
+- `sets: &mut Vec<FaceGroup>` - The sets to search.
+- `name: &str` - The name of the set.
 
-            for i in 0..face.len() {
-                mesh.add_face(
-                    vec![center_vertex_index, face[i], face[i + 1 % face.len()]].as_slice(),
-                    Some(*normal),
-                )?;
-            }
+Returns:
+- `usize` - The index of the set in `sets`.
+ */
+fn find_or_add_group(sets: &mut Vec<FaceGroup>, name: &str) -> usize {
+    match sets.iter().position(|g| g.name == name) {
+        Some(index) => index,
+        None => {
+            sets.push(FaceGroup {
+                name: name.to_string(),
+                faces: Vec::new(),
+            });
+            sets.len() - 1
         }
-
-        Ok(mesh)
     }
 }
 
-impl MutateMesh for PolygonMesh {
-    fn add_vertex(&mut self, vertex: Point3) -> usize {
+/**
+Wraps an error found at a token of a text file in a `MeshError::LineError`, finding the line and
+column of the token from where it lies in the text.
+
+Parameters:
+- `error: MeshError` - The error found at the token.
+- `text: &str` - The whole text of the file.
+- `token: &str` - The token the error was found at, which must be a slice of `text`.
+
+Returns:
+- `MeshError` - The `MeshError::LineError`, without a path.
+ */
+pub(crate) fn locate_text_error(error: MeshError, text: &str, token: &str) -> MeshError {
+    let offset = token.as_ptr() as usize - text.as_ptr() as usize;
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    MeshError::LineError {
+        path: None,
+        line: text[..start].bytes().filter(|&b| b == b'\n').count() + 1,
+        column: text[start..offset].chars().count() + 1,
+        token: token.to_string(),
+        source: Box::new(error),
+    }
+}
+
+/**
+Wraps an error found on a line of an OBJ file in a `MeshError::LineError`, giving its position.
+
+Parameters:
+- `error: ObjError` - The error and the token it was found at, which must be a slice of `line`.
+- `line: &str` - The line the error was found on.
+- `number: usize` - The number of the line, counting from 1.
+- `path: Option<&Path>` - The path of the OBJ file, if it is a file.
+
+Returns:
+- `MeshError` - The `MeshError::LineError`.
+ */
+fn locate_obj_error(error: ObjError, line: &str, number: usize, path: Option<&Path>) -> MeshError {
+    let (error, token) = error;
+    // The token is a slice of the line, so its offset in the line gives its column.
+    let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+    MeshError::LineError {
+        path: path.map(Path::to_path_buf),
+        line: number,
+        column: line[..offset].chars().count() + 1,
+        token: token.to_string(),
+        source: Box::new(error),
+    }
+}
+
+/**
+Creates the error for a line of an OBJ file that could not be read.
+
+Parameters:
+- `source: std::io::Error` - The error that stopped the line being read.
+- `number: usize` - The number of the line, counting from 1.
+- `path: Option<&Path>` - The path of the OBJ file, if it is a file.
+
+Returns:
+- `MeshError` - A `MeshError::LineError` wrapping `MeshError::FileError("Could not read next
+  line.")`.
+ */
+fn obj_read_error(source: Error, number: usize, path: Option<&Path>) -> MeshError {
+    MeshError::LineError {
+        path: path.map(Path::to_path_buf),
+        line: number,
+        column: 1,
+        token: String::new(),
+        source: Box::new(MeshError::FileError {
+            message: "Could not read next line.",
+            path: None,
+            source,
+        }),
+    }
+}
+
+impl PolygonMesh {
+    /**
+    Loads a `PolygonMesh` from the filename passed in, with coordinates stored as `Float`. Use
+    `load_obj_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_obj(filename: &str) -> Result<Box<PolygonMesh>, MeshError> {
+        PolygonMesh::load_obj_with_precision(filename)
+    }
+
+    /**
+    Reads a `PolygonMesh` from OBJ data in any buffered reader, such as an in-memory buffer,
+    standard input or a decompressing stream, with coordinates stored as `Float`. Use
+    `read_obj_with_precision` to read them as another scalar type.
+
+    Parameters:
+    - `reader: R where R: BufRead` - The reader to read the OBJ data from.
+
+    Returns:
+    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the reading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn read_obj<R: BufRead>(reader: R) -> Result<Box<PolygonMesh>, MeshError> {
+        PolygonMesh::read_obj_with_precision(reader)
+    }
+}
+
+impl<T: RealField + Copy + FromStr> PolygonMesh<T> {
+    /**
+    Loads a `PolygonMesh` from the filename passed in, with coordinates parsed directly as `T`.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_obj_with_precision(filename: &str) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        // To implement file processing
+        let file = open_file(filename)?;
+
+        // Assists with processing files using a buffer (to save us from the catastrophe that large
+        // files can cause)
+        PolygonMesh::parse_obj(
+            BufReader::with_capacity(1 << 16, file),
+            Some(Path::new(filename)),
+        )
+    }
+
+    /**
+    Reads a `PolygonMesh` from OBJ data in any buffered reader, with coordinates parsed directly
+    as `T`. The data is read a line at a time, so it need not fit in memory as text. Data read
+    this way has no directory to find material libraries in, so only the names of its libraries
+    are kept, along with the materials named by `usemtl`.
+
+    Parameters:
+    - `reader: R where R: BufRead` - The reader to read the OBJ data from.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the reading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn read_obj_with_precision<R: BufRead>(
+        reader: R,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        PolygonMesh::parse_obj(reader, None)
+    }
+
+    /**
+    Parses a `PolygonMesh` from OBJ data, with coordinates parsed directly as `T`. Errors found
+    on a line are returned as a `MeshError::LineError` giving the line.
+
+    Parameters:
+    - `bufread: R where R: BufRead` - The reader to parse the OBJ data from.
+    - `path: Option<&Path>` - The path of the OBJ file, whose directory material libraries are
+      loaded from, or `None` if the data is not read from a file.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the parsing
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub(crate) fn parse_obj<R: BufRead>(
+        mut bufread: R,
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let mut polymesh = PolygonMesh {
+            vertices: Vec::with_capacity(4),
+            faces: Vec::with_capacity(4),
+            face_normals: Vec::with_capacity(4),
+            ..Default::default()
+        };
+
+        // A single line buffer is reused for the whole file, rather than allocated for every line.
+        let mut buffer_string = String::new();
+        let mut state = ObjState::default();
+        let directory = path.map(|p| p.parent().unwrap_or(Path::new("")));
+        let mut number = 0;
+
+        while match bufread.read_line(&mut buffer_string) {
+            Ok(t) => t != 0,
+            Err(e) => {
+                return Err(obj_read_error(e, number + 1, path));
+            }
+        } {
+            number += 1;
+            if let Some(error) = match parse_obj_statement(buffer_string.trim()) {
+                Ok(statement) => {
+                    apply_obj_statement(&mut polymesh, &mut state, statement, directory)
+                }
+                Err(e) => Some(e),
+            } {
+                return Err(locate_obj_error(error, &buffer_string, number, path));
+            }
+            buffer_string.clear();
+        }
+
+        Ok(Box::new(polymesh))
+    }
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Writes a `PolygonMesh` to the filename passed in, along with its texture coordinates, vertex
+    normals, groups, objects, smoothing groups, materials and polylines. The material libraries the
+    mesh was loaded with are referenced but not written; use `write_mtl` to write the materials
+    themselves.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save them mesh to.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_obj(&self, filename: &str) -> Result<usize, MeshError> {
+        write_file(filename, |file| self.write_obj_to(file))
+    }
+
+    /**
+    Writes a `PolygonMesh` as OBJ data to the writer passed in, as `write_obj` does.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the OBJ data to.
+
+    Returns:
+    - `Result<usize, Error>` - Returns the number of bytes written if writing is successful
+      otherwise returns an `std::io::Error`, given by the methods called in this method.
+     */
+    pub(crate) fn write_obj_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<usize, Error> {
+        let mut bytes: usize = 0;
+        let mut line = |string: String| -> Result<(), Error> {
+            writeln!(writer, "{}", string)?;
+            bytes += string.len() + 1;
+            Ok(())
+        };
+
+        if !self.material_libraries.is_empty() {
+            line(format!("mtllib {}", self.material_libraries.join(" ")))?;
+        }
+        for vertex in &self.vertices {
+            line(format!("v {} {} {}", vertex.x, vertex.y, vertex.z))?;
+        }
+        for texture in &self.texture_coords {
+            line(if texture.z == T::zero() {
+                format!("vt {} {}", texture.x, texture.y)
+            } else {
+                format!("vt {} {} {}", texture.x, texture.y, texture.z)
+            })?;
+        }
+        for normal in &self.vertex_normals {
+            line(format!("vn {} {} {}", normal.x, normal.y, normal.z))?;
+        }
+
+        // The groups and object of each face, which are written whenever they change.
+        let mut face_groups: Vec<Vec<&str>> = vec![Vec::new(); self.faces.len()];
+        for group in &self.groups {
+            for &f in &group.faces {
+                face_groups[f].push(group.name.as_str());
+            }
+        }
+        let mut face_objects: Vec<Option<&str>> = vec![None; self.faces.len()];
+        for object in &self.objects {
+            for &f in &object.faces {
+                face_objects[f] = Some(object.name.as_str());
+            }
+        }
+
+        let mut state: (Option<&str>, &[&str], Option<u32>, Option<usize>) =
+            (None, &[], None, None);
+        for (f, face) in self.faces.iter().enumerate() {
+            if face_objects[f].is_some() && face_objects[f] != state.0 {
+                line(format!("o {}", face_objects[f].unwrap()))?;
+                state.0 = face_objects[f];
+            }
+            if face_groups[f] != state.1 {
+                line(
+                    format!("g {}", face_groups[f].join(" "))
+                        .trim_end()
+                        .to_string(),
+                )?;
+                state.1 = &face_groups[f];
+            }
+            if self.smoothing_groups[f] != state.2 {
+                line(match self.smoothing_groups[f] {
+                    Some(group) => format!("s {}", group),
+                    None => "s off".to_string(),
+                })?;
+                state.2 = self.smoothing_groups[f];
+            }
+            if self.face_materials[f].is_some() && self.face_materials[f] != state.3 {
+                let material = &self.materials[self.face_materials[f].unwrap()];
+                line(format!("usemtl {}", material.name))?;
+                state.3 = self.face_materials[f];
+            }
+
+            let texture_coords = self.face_texture_coords[f].as_ref();
+            let vertex_normals = self.face_vertex_normals[f].as_ref();
+            let string: Vec<String> = (0..face.len())
+                .map(|i| {
+                    let vertex = face[i] + 1;
+                    match (texture_coords, vertex_normals) {
+                        (None, None) => vertex.to_string(),
+                        (Some(t), None) => format!("{}/{}", vertex, t[i] + 1),
+                        (None, Some(n)) => format!("{}//{}", vertex, n[i] + 1),
+                        (Some(t), Some(n)) => format!("{}/{}/{}", vertex, t[i] + 1, n[i] + 1),
+                    }
+                })
+                .collect();
+            line(format!("f {}", string.join(" ")))?;
+        }
+
+        for polyline in &self.polylines {
+            let string: Vec<String> = polyline.iter().map(|v| (v + 1).to_string()).collect();
+            line(format!("l {}", string.join(" ")))?;
+        }
+
+        Ok(bytes)
+    }
+
+    /**
+    Splits every face into triangles by clipping ears, which handles concave faces and adds no
+    vertices. Use `triangulate` to choose another method or to find the face each triangle was cut
+    from. Each boundary patch covers the triangles of its faces.
+
+    Returns:
+    - `Result<TriangleMesh<T>, MeshError>` - The triangulated mesh, else a `MeshError` if a face
+      references a vertex that does not exist.
+     */
+    pub fn to_triangle_mesh(&self) -> Result<TriangleMesh<T>, MeshError> {
+        self.triangulate(Triangulation::EarClipping)
+            .map(|(mesh, _)| mesh)
+    }
+
+    /**
+    Converts the mesh to another scalar type, for example to hand a mesh loaded in double
+    precision to code working in single precision. Normals are renormalized after conversion.
+
+    Returns:
+    - `PolygonMesh<U>` - A copy of the mesh with every coordinate converted to `U`.
+     */
+    pub fn cast<U: RealField + Copy>(&self) -> PolygonMesh<U>
+    where
+        T: SubsetOf<U>,
+    {
+        PolygonMesh {
+            vertices: self.vertices.iter().map(|v| v.cast()).collect(),
+            faces: self.faces.clone(),
+            face_normals: self.face_normals.iter().map(cast_normal).collect(),
+            vertex_attributes: self.vertex_attributes.clone(),
+            face_attributes: self.face_attributes.clone(),
+            texture_coords: self.texture_coords.iter().map(|t| t.cast()).collect(),
+            vertex_normals: self.vertex_normals.iter().map(|n| n.cast()).collect(),
+            face_texture_coords: self.face_texture_coords.clone(),
+            face_vertex_normals: self.face_vertex_normals.clone(),
+            groups: self.groups.clone(),
+            objects: self.objects.clone(),
+            smoothing_groups: self.smoothing_groups.clone(),
+            materials: self.materials.clone(),
+            material_libraries: self.material_libraries.clone(),
+            face_materials: self.face_materials.clone(),
+            patches: self.patches.clone(),
+            polylines: self.polylines.clone(),
+        }
+    }
+
+    /// Retrieves the texture coordinates of the mesh. Coordinates given with fewer than three
+    /// components have the rest set to zero.
+    pub fn get_texture_coords(&self) -> &Vec<Vec3<T>> {
+        &self.texture_coords
+    }
+
+    /// Retrieves the vertex normals of the mesh, as given in the file they were loaded from.
+    pub fn get_vertex_normals(&self) -> &Vec<Vec3<T>> {
+        &self.vertex_normals
+    }
+
+    /// Retrieves, for each face, the index of the texture coordinate of each of its vertices, or
+    /// `None` for faces without texture coordinates.
+    pub fn get_face_texture_coords(&self) -> &Vec<Option<Vec<usize>>> {
+        &self.face_texture_coords
+    }
+
+    /// Retrieves, for each face, the index of the vertex normal of each of its vertices, or `None`
+    /// for faces without vertex normals.
+    pub fn get_face_vertex_normals(&self) -> &Vec<Option<Vec<usize>>> {
+        &self.face_vertex_normals
+    }
+
+    /// Retrieves the groups of the mesh. A face may belong to any number of groups.
+    pub fn get_groups(&self) -> &Vec<FaceGroup> {
+        &self.groups
+    }
+
+    /// Retrieves the objects of the mesh. A face belongs to at most one object.
+    pub fn get_objects(&self) -> &Vec<FaceGroup> {
+        &self.objects
+    }
+
+    /// Retrieves the smoothing group of each face, or `None` for faces with smoothing off.
+    pub fn get_smoothing_groups(&self) -> &Vec<Option<u32>> {
+        &self.smoothing_groups
+    }
+
+    /// Retrieves the materials of the mesh, from its material libraries and `usemtl` statements.
+    pub fn get_materials(&self) -> &Vec<Material> {
+        &self.materials
+    }
+
+    /// Retrieves the names of the material libraries the mesh was loaded with.
+    pub fn get_material_libraries(&self) -> &Vec<String> {
+        &self.material_libraries
+    }
+
+    /// Retrieves the index into `get_materials` of the material of each face, or `None` for faces
+    /// without a material.
+    pub fn get_face_materials(&self) -> &Vec<Option<usize>> {
+        &self.face_materials
+    }
+
+    /**
+    Gets the material called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the material.
+
+    Returns:
+    - `Option<&Material>` - The material, or `None` if the mesh has no material called `name`.
+     */
+    pub fn get_material(&self, name: &str) -> Option<&Material> {
+        self.materials.iter().find(|m| m.name == name)
+    }
+
+    /**
+    Writes the materials of the mesh to the MTL library passed in.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the materials to.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_mtl(&self, filename: &str) -> Result<usize, MeshError> {
+        mtl::write_mtl(filename, &self.materials)
+    }
+
+    /**
+    Gets the group called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the group.
+
+    Returns:
+    - `Option<&FaceGroup>` - The group, or `None` if the mesh has no group called `name`.
+     */
+    pub fn get_group(&self, name: &str) -> Option<&FaceGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Retrieves the boundary patches of the mesh. A face belongs to at most one patch.
+    pub fn get_patches(&self) -> &Vec<FaceGroup> {
+        &self.patches
+    }
+
+    /**
+    Gets the boundary patch called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+
+    Returns:
+    - `Option<&FaceGroup>` - The patch, or `None` if the mesh has no patch called `name`.
+     */
+    pub fn get_patch(&self, name: &str) -> Option<&FaceGroup> {
+        self.patches.iter().find(|p| p.name == name)
+    }
+
+    /**
+    Assigns faces to the boundary patch called `name`, creating the patch if it does not exist.
+    Faces are taken out of any other patch they belonged to.
+
+    - Face out of range: `MeshError::IndexingError("Face not contained in mesh.")`
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+    - `faces: &[usize]` - The faces to assign to the patch.
+
+    Returns:
+    - `Result<(), MeshError>` - Returns `Ok(())` if the faces were assigned, else
+      `MeshError::IndexingError`.
+     */
+    pub fn set_patch(&mut self, name: &str, faces: &[usize]) -> Result<(), MeshError> {
+        check_patch_faces(faces, self.faces.len())?;
+        assign_patch_faces(&mut self.patches, name, faces, self.faces.len());
+        Ok(())
+    }
+
+    /// Retrieves the polylines of the mesh, such as those of OBJ `l` statements, as the vertices
+    /// along each line.
+    pub fn get_polylines(&self) -> &Vec<Vec<usize>> {
+        &self.polylines
+    }
+
+    /**
+    Adds a polyline through the vertices passed in, which is written to OBJ files as an `l`
+    statement.
+
+    - Polyline has less than 2 vertices: `MeshError::FormatError("Polyline does not have enough
+      vertices.")`
+    - Vertex out of range: `MeshError::IndexingError("Vertex not contained in mesh.")`
+
+    Parameters:
+    - `vertices: &[usize]` - The vertices along the polyline.
+
+    Returns:
+    - `Result<usize, MeshError>` - The index of the polyline, else a `MeshError`.
+     */
+    pub fn add_polyline(&mut self, vertices: &[usize]) -> Result<usize, MeshError> {
+        if vertices.len() < 2 {
+            return Err(MeshError::FormatError(
+                "Polyline does not have enough vertices.",
+            ));
+        }
+        if vertices.iter().any(|&v| v >= self.vertices.len()) {
+            return Err(MeshError::IndexingError("Vertex not contained in mesh."));
+        }
+        self.polylines.push(vertices.to_vec());
+        Ok(self.polylines.len() - 1)
+    }
+
+    /// Creates a boundary patch for every group of the mesh, named after the group. Faces in more
+    /// than one group end up in the patch of the last of them.
+    pub fn patches_from_groups(&mut self) {
+        for group in &self.groups {
+            assign_patch_faces(
+                &mut self.patches,
+                &group.name,
+                &group.faces,
+                self.faces.len(),
+            );
+        }
+    }
+
+    /// Creates a boundary patch for every material of the mesh, such as `usemtl inlet`, holding
+    /// the faces that use it.
+    pub fn patches_from_materials(&mut self) {
+        for (m, material) in self.materials.iter().enumerate() {
+            let faces: Vec<usize> = (0..self.faces.len())
+                .filter(|&f| self.face_materials[f] == Some(m))
+                .collect();
+            assign_patch_faces(&mut self.patches, &material.name, &faces, self.faces.len());
+        }
+    }
+
+    /// Retrieves the named per-vertex attributes of the mesh, in the order they were added.
+    pub fn get_vertex_attributes(&self) -> &Vec<Attribute> {
+        &self.vertex_attributes
+    }
+
+    /// Retrieves the named per-face attributes of the mesh, in the order they were added.
+    pub fn get_face_attributes(&self) -> &Vec<Attribute> {
+        &self.face_attributes
+    }
+
+    /**
+    Gets the per-vertex attribute called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the attribute.
+
+    Returns:
+    - `Option<&Attribute>` - The attribute, or `None` if the mesh has no attribute called `name`.
+     */
+    pub fn get_vertex_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.vertex_attributes.iter().find(|a| a.name == name)
+    }
+
+    /**
+    Gets the per-face attribute called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the attribute.
+
+    Returns:
+    - `Option<&Attribute>` - The attribute, or `None` if the mesh has no attribute called `name`.
+     */
+    pub fn get_face_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.face_attributes.iter().find(|a| a.name == name)
+    }
+
+    /**
+    Attaches a per-vertex attribute to the mesh, replacing any attribute with the same name.
+    Vertices added afterwards take the value zero.
+
+    - Attribute has the wrong length: `MeshError::IndexingError("Attribute length does not match
+      vertex count.")`
+
+    Parameters:
+    - `attribute: Attribute` - The attribute, holding one value per vertex.
+
+    Returns:
+    - `Result<(), MeshError>` - Returns `Ok(())` if the attribute was attached, else
+      `MeshError::IndexingError`.
+     */
+    pub fn set_vertex_attribute(&mut self, attribute: Attribute) -> Result<(), MeshError> {
+        if attribute.values.len() != self.vertices.len() {
+            return Err(MeshError::IndexingError(
+                "Attribute length does not match vertex count.",
+            ));
+        }
+        set_attribute(&mut self.vertex_attributes, attribute);
+        Ok(())
+    }
+
+    /**
+    Attaches a per-face attribute to the mesh, replacing any attribute with the same name. Faces
+    added afterwards take the value zero.
+
+    - Attribute has the wrong length: `MeshError::IndexingError("Attribute length does not match
+      face count.")`
+
+    Parameters:
+    - `attribute: Attribute` - The attribute, holding one value per face.
+
+    Returns:
+    - `Result<(), MeshError>` - Returns `Ok(())` if the attribute was attached, else
+      `MeshError::IndexingError`.
+     */
+    pub fn set_face_attribute(&mut self, attribute: Attribute) -> Result<(), MeshError> {
+        if attribute.values.len() != self.faces.len() {
+            return Err(MeshError::IndexingError(
+                "Attribute length does not match face count.",
+            ));
+        }
+        set_attribute(&mut self.face_attributes, attribute);
+        Ok(())
+    }
+}
+
+/// Replaces the attribute in `attributes` named like `attribute`, or appends it if there is none.
+fn set_attribute(attributes: &mut Vec<Attribute>, attribute: Attribute) {
+    match attributes.iter_mut().find(|a| a.name == attribute.name) {
+        Some(existing) => *existing = attribute,
+        None => attributes.push(attribute),
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Converts the mesh to another scalar type, for example to hand a mesh loaded in double
+    precision to code working in single precision. Normals are renormalized after conversion.
+
+    Returns:
+    - `TriangleMesh<U>` - A copy of the mesh with every coordinate converted to `U`.
+     */
+    pub fn cast<U: RealField + Copy>(&self) -> TriangleMesh<U>
+    where
+        T: SubsetOf<U>,
+    {
+        TriangleMesh {
+            vertices: self.vertices.iter().map(|v| v.cast()).collect(),
+            faces: self.faces.clone(),
+            face_normals: self.face_normals.iter().map(cast_normal).collect(),
+            patches: self.patches.clone(),
+        }
+    }
+
+    /// Retrieves the boundary patches of the mesh. A face belongs to at most one patch.
+    pub fn get_patches(&self) -> &Vec<FaceGroup> {
+        &self.patches
+    }
+
+    /**
+    Gets the boundary patch called `name`.
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+
+    Returns:
+    - `Option<&FaceGroup>` - The patch, or `None` if the mesh has no patch called `name`.
+     */
+    pub fn get_patch(&self, name: &str) -> Option<&FaceGroup> {
+        self.patches.iter().find(|p| p.name == name)
+    }
+
+    /**
+    Assigns faces to the boundary patch called `name`, creating the patch if it does not exist.
+    Faces are taken out of any other patch they belonged to.
+
+    - Face out of range: `MeshError::IndexingError("Face not contained in mesh.")`
+
+    Parameters:
+    - `name: &str` - The name of the patch.
+    - `faces: &[usize]` - The faces to assign to the patch.
+
+    Returns:
+    - `Result<(), MeshError>` - Returns `Ok(())` if the faces were assigned, else
+      `MeshError::IndexingError`.
+     */
+    pub fn set_patch(&mut self, name: &str, faces: &[usize]) -> Result<(), MeshError> {
+        check_patch_faces(faces, self.faces.len())?;
+        assign_patch_faces(&mut self.patches, name, faces, self.faces.len());
+        Ok(())
+    }
+}
+
+/// Checks that every face assigned to a patch is one of the `face_count` faces of the mesh.
+fn check_patch_faces(faces: &[usize], face_count: usize) -> Result<(), MeshError> {
+    if faces.iter().any(|&f| f >= face_count) {
+        return Err(MeshError::IndexingError("Face not contained in mesh."));
+    }
+    Ok(())
+}
+
+/**
+Assigns `faces` to the patch called `name` in `patches`, taking them out of every other patch. The
+faces of each patch are kept sorted and free of duplicates.
+
+Parameters:
+- `patches: &mut Vec<FaceGroup>` - The patches of a mesh.
+- `name: &str` - The name of the patch.
+- `faces: &[usize]` - The faces to assign to the patch, all less than `face_count`.
+- `face_count: usize` - The number of faces in the mesh.
+ */
+fn assign_patch_faces(
+    patches: &mut Vec<FaceGroup>,
+    name: &str,
+    faces: &[usize],
+    face_count: usize,
+) {
+    let mut assigned = vec![false; face_count];
+    for &f in faces {
+        assigned[f] = true;
+    }
+    for patch in patches.iter_mut() {
+        patch.faces.retain(|&f| !assigned[f]);
+    }
+
+    let index = find_or_add_group(patches, name);
+    let patch = &mut patches[index].faces;
+    patch.extend(faces);
+    patch.sort_unstable();
+    patch.dedup();
+}
+
+/// Converts a normal to another scalar type, renormalizing it to undo any rounding.
+fn cast_normal<T, U>(normal: &UnitVec3<T>) -> UnitVec3<U>
+where
+    T: RealField + Copy + SubsetOf<U>,
+    U: RealField + Copy,
+{
+    Unit::new_normalize(normal.into_inner().cast())
+}
+
+impl<T: RealField + Copy> MutateMesh for PolygonMesh<T> {
+    fn add_vertex(&mut self, vertex: Point3<T>) -> usize {
         self.vertices.push(vertex);
+        for attribute in &mut self.vertex_attributes {
+            attribute.values.push(0.);
+        }
         self.vertices.len() - 1
     }
 
     fn add_face(
         &mut self,
         face: &[usize],
-        face_normal: Option<UnitVec3>,
+        face_normal: Option<UnitVec3<T>>,
     ) -> Result<usize, MeshError> {
-        if let Some(e) = self.add_normals(face, face_normal) {
+        self.push_face(face.to_vec(), face_normal)
+    }
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /// Adds a face as `add_face` does, taking the vertex list rather than copying it.
+    pub(crate) fn push_face(
+        &mut self,
+        face: Vec<usize>,
+        face_normal: Option<UnitVec3<T>>,
+    ) -> Result<usize, MeshError> {
+        if let Some(e) = self.add_normals(&face, face_normal) {
             return Err(e);
         } // If there was an error, the normal was not added to the mesh, so don't attempt to add the face.
-        self.faces.push(face.to_vec());
+        self.faces.push(face);
+        self.face_texture_coords.push(None);
+        self.face_vertex_normals.push(None);
+        self.smoothing_groups.push(None);
+        self.face_materials.push(None);
+        for attribute in &mut self.face_attributes {
+            attribute.values.push(0.);
+        }
         Ok(self.faces.len() - 1)
     }
 }
 
-impl MutateMesh for TriangleMesh {
-    fn add_vertex(&mut self, vertex: Point3) -> usize {
+impl<T: RealField + Copy> MutateMesh for TriangleMesh<T> {
+    fn add_vertex(&mut self, vertex: Point3<T>) -> usize {
         self.vertices.push(vertex);
         self.vertices.len() - 1
     }
@@ -469,7 +2015,7 @@ impl MutateMesh for TriangleMesh {
     fn add_face(
         &mut self,
         face: &[usize],
-        face_normal: Option<UnitVec3>,
+        face_normal: Option<UnitVec3<T>>,
     ) -> Result<usize, MeshError> {
         if let Some(e) = self.add_normals(face, face_normal) {
             return Err(e);
@@ -479,30 +2025,31 @@ impl MutateMesh for TriangleMesh {
     }
 }
 
-impl PolyMesh for PolygonMesh {
+impl<T: RealField + Copy> PolyMesh for PolygonMesh<T> {
     type FaceType = Vec<usize>;
+    type Scalar = T;
 
-    fn get_vertices(&self) -> &Vec<Point3> {
+    fn get_vertices(&self) -> &Vec<Point3<T>> {
         self.vertices.as_ref()
     }
     fn get_faces(&self) -> &Vec<Self::FaceType> {
         self.faces.as_ref()
     }
-    fn get_normals(&self) -> &Vec<UnitVec3> {
+    fn get_normals(&self) -> &Vec<UnitVec3<T>> {
         self.face_normals.as_ref()
     }
 
-    fn take_mut_vertices(&mut self) -> &mut Vec<Point3> {
+    fn take_mut_vertices(&mut self) -> &mut Vec<Point3<T>> {
         self.vertices.as_mut()
     }
     fn take_mut_faces(&mut self) -> &mut Vec<Self::FaceType> {
         self.faces.as_mut()
     }
-    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3> {
+    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3<T>> {
         self.face_normals.as_mut()
     }
 
-    fn get_vertex(&self, idx: usize) -> Result<&Point3, MeshError> {
+    fn get_vertex(&self, idx: usize) -> Result<&Point3<T>, MeshError> {
         self.vertices
             .get(idx)
             .ok_or(MeshError::IndexingError("Indexing failed."))
@@ -515,33 +2062,34 @@ impl PolyMesh for PolygonMesh {
             .ok_or(MeshError::IndexingError("Indexing failed."))
     }
 
-    fn get_normal(&self, idx: usize) -> Result<&UnitVec3, MeshError> {
+    fn get_normal(&self, idx: usize) -> Result<&UnitVec3<T>, MeshError> {
         self.face_normals
             .get(idx)
             .ok_or(MeshError::IndexingError("Indexing failed."))
     }
 }
 
-impl PolyMesh for TriangleMesh {
+impl<T: RealField + Copy> PolyMesh for TriangleMesh<T> {
     type FaceType = [usize; 3];
+    type Scalar = T;
 
-    fn get_vertices(&self) -> &Vec<Point3> {
+    fn get_vertices(&self) -> &Vec<Point3<T>> {
         self.vertices.as_ref()
     }
     fn get_faces(&self) -> &Vec<Self::FaceType> {
         self.faces.as_ref()
     }
-    fn get_normals(&self) -> &Vec<UnitVec3> {
+    fn get_normals(&self) -> &Vec<UnitVec3<T>> {
         self.face_normals.as_ref()
     }
 
-    fn take_mut_vertices(&mut self) -> &mut Vec<Point3> {
+    fn take_mut_vertices(&mut self) -> &mut Vec<Point3<T>> {
         self.vertices.as_mut()
     }
     fn take_mut_faces(&mut self) -> &mut Vec<Self::FaceType> {
         &mut self.faces
     }
-    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3> {
+    fn take_mut_normals(&mut self) -> &mut Vec<UnitVec3<T>> {
         self.face_normals.as_mut()
     }
 }
@@ -0,0 +1,332 @@
+/*! Cleanup of meshes assembled from separate facets, as those read from STL files and exported by
+CAD tools are. Vertices closer together than a tolerance are found through a spatial hash and
+welded into one, then faces left with no area, faces repeating another and vertices that no face
+or polyline uses are removed, with everything stored per vertex or per face kept in step. */
+
+use std::collections::{HashMap, HashSet};
+
+use nalgebra::{RealField, Vector3};
+
+use super::validate::{has_no_area, to_f64};
+use super::{FaceGroup, MeshError, PolygonMesh, TriangleMesh};
+use crate::Point3;
+
+/// Statistics on the changes made by `cleanup`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// The number of vertices welded into another vertex and removed.
+    pub welded_vertices: usize,
+    /// The number of vertices removed because no face or polyline used them once faces were
+    /// removed.
+    pub unreferenced_vertices: usize,
+    /// The number of faces removed for having no area, or for using a vertex more than once, once
+    /// vertices were welded.
+    pub degenerate_faces: usize,
+    /// The number of faces removed for using the same vertices as an earlier face, in any order.
+    pub duplicate_faces: usize,
+    /// The number of corners removed from polygons whose neighbouring corners were welded into
+    /// one, leaving a smaller polygon.
+    pub collapsed_corners: usize,
+    /// The number of polylines removed for being left with fewer than two vertices once vertices
+    /// were welded.
+    pub collapsed_polylines: usize,
+}
+
+/// The changes a cleanup makes, found before any are applied so that every array of a mesh can be
+/// changed in step.
+struct Cleanup {
+    // The index each vertex is given, that of the vertex it was welded into if it was welded.
+    vertex_indices: Vec<usize>,
+    kept_vertices: Vec<bool>,
+    // The positions within each face of the corners it keeps, or `None` if it is removed.
+    corners: Vec<Option<Vec<usize>>>,
+    report: CleanupReport,
+}
+
+/**
+Welds each vertex into the nearest earlier vertex that was not itself welded and lies within
+`tolerance` of it. The space is hashed into cubes as wide as the tolerance, so only the cube of a
+vertex and those around it are searched. A tolerance of zero welds only vertices at the same
+position.
+
+Parameters:
+- `vertices: &[Point3<T>]` - The vertices to weld.
+- `tolerance: f64` - The greatest distance between welded vertices.
+
+Returns:
+- `Vec<usize>` - The vertex each vertex is welded into, or the vertex itself if it is kept.
+ */
+fn weld<T: RealField + Copy>(vertices: &[Point3<T>], tolerance: f64) -> Vec<usize> {
+    let positions: Vec<Vector3<f64>> = vertices.iter().map(to_f64).collect();
+    // Adding zero turns -0 into 0, so that the two are hashed alike.
+    let cell = |p: &Vector3<f64>| {
+        p.map(|x| {
+            if tolerance > 0. {
+                (x / tolerance).floor() as i64
+            } else {
+                (x + 0.).to_bits() as i64
+            }
+        })
+    };
+    let reach = if tolerance > 0. { 1 } else { 0 };
+
+    let mut cells: HashMap<Vector3<i64>, Vec<usize>> = HashMap::new();
+    let mut welded = Vec::with_capacity(vertices.len());
+    for (v, position) in positions.iter().enumerate() {
+        let centre = cell(position);
+        let mut nearest: Option<(f64, usize)> = None;
+        for x in -reach..=reach {
+            for y in -reach..=reach {
+                for z in -reach..=reach {
+                    let kept = cells.get(&(centre + Vector3::new(x, y, z)));
+                    for &k in kept.into_iter().flatten() {
+                        let distance = (positions[k] - position).norm();
+                        if distance <= tolerance
+                            && nearest.is_none_or(|(d, n)| (distance, k) < (d, n))
+                        {
+                            nearest = Some((distance, k));
+                        }
+                    }
+                }
+            }
+        }
+        match nearest {
+            Some((_, k)) => welded.push(k),
+            None => {
+                cells.entry(centre).or_default().push(v);
+                welded.push(v);
+            }
+        }
+    }
+    welded
+}
+
+impl Cleanup {
+    /**
+    Finds the changes a cleanup makes to a mesh.
+    This method may return `MeshError` if:
+    - Vertex referenced in face or polyline out of range: `MeshError::IndexingError("Vertex not
+      contained in mesh.")`
+
+    Parameters:
+    - `vertices: &[Point3<T>]` - The vertices of the mesh.
+    - `faces: &[&[usize]]` - The faces of the mesh.
+    - `polylines: &[Vec<usize>]` - The polylines of the mesh, whose vertices are kept.
+    - `tolerance: T` - The greatest distance between welded vertices.
+
+    Returns:
+    - `Result<Cleanup, MeshError>` - The changes, else a `MeshError`.
+     */
+    fn new<T: RealField + Copy>(
+        vertices: &[Point3<T>],
+        faces: &[&[usize]],
+        polylines: &[Vec<usize>],
+        tolerance: T,
+    ) -> Result<Cleanup, MeshError> {
+        if faces
+            .iter()
+            .flat_map(|f| f.iter())
+            .chain(polylines.iter().flatten())
+            .any(|&v| v >= vertices.len())
+        {
+            return Err(MeshError::IndexingError("Vertex not contained in mesh."));
+        }
+        let mut report = CleanupReport::default();
+        let welded = weld(vertices, tolerance.to_subset_unchecked().max(0.));
+
+        // Corners welded into the corner after them are dropped. Faces left with fewer than three
+        // corners, a corner used twice or no area are degenerate.
+        let mut referenced = vec![false; vertices.len()];
+        for &v in polylines.iter().flatten() {
+            referenced[welded[v]] = true;
+        }
+        let mut keys: HashSet<Vec<usize>> = HashSet::new();
+        let mut corners = Vec::with_capacity(faces.len());
+        for (f, face) in faces.iter().enumerate() {
+            let kept: Vec<usize> = (0..face.len())
+                .filter(|&i| welded[face[i]] != welded[face[(i + 1) % face.len()]])
+                .collect();
+            let face: Vec<usize> = kept.iter().map(|&i| welded[face[i]]).collect();
+            let mut key = face.clone();
+            key.sort_unstable();
+            if face.len() < 3
+                || key.windows(2).any(|w| w[0] == w[1])
+                || has_no_area(vertices, &face)
+            {
+                report.degenerate_faces += 1;
+                corners.push(None);
+            } else if !keys.insert(key) {
+                report.duplicate_faces += 1;
+                corners.push(None);
+            } else {
+                report.collapsed_corners += faces[f].len() - kept.len();
+                for &v in &face {
+                    referenced[v] = true;
+                }
+                corners.push(Some(kept));
+            }
+        }
+
+        // Kept vertices are numbered in order, and welded vertices take the number of the vertex
+        // they were welded into, which always comes before them.
+        let mut kept_vertices = Vec::with_capacity(vertices.len());
+        let mut vertex_indices = Vec::with_capacity(vertices.len());
+        let mut count = 0;
+        for (v, &w) in welded.iter().enumerate() {
+            if w != v {
+                report.welded_vertices += 1;
+                kept_vertices.push(false);
+                vertex_indices.push(vertex_indices[w]);
+            } else if !referenced[v] {
+                report.unreferenced_vertices += 1;
+                kept_vertices.push(false);
+                vertex_indices.push(usize::MAX);
+            } else {
+                kept_vertices.push(true);
+                vertex_indices.push(count);
+                count += 1;
+            }
+        }
+
+        Ok(Cleanup {
+            vertex_indices,
+            kept_vertices,
+            corners,
+            report,
+        })
+    }
+
+    /// Whether each face is kept.
+    fn kept_faces(&self) -> Vec<bool> {
+        self.corners.iter().map(Option::is_some).collect()
+    }
+
+    /// Renumbers the faces of each group, leaving out those removed.
+    fn remap_groups(&self, groups: &mut [FaceGroup]) {
+        let mut face_indices = Vec::with_capacity(self.corners.len());
+        let mut count = 0;
+        for corners in &self.corners {
+            face_indices.push(corners.as_ref().map(|_| count));
+            count += usize::from(corners.is_some());
+        }
+        for group in groups {
+            group.faces = group
+                .faces
+                .iter()
+                .filter_map(|&f| face_indices[f])
+                .collect();
+        }
+    }
+}
+
+/// Keeps the values whose entry in `keep` is true.
+fn retain<V>(values: &mut Vec<V>, keep: &[bool]) {
+    let mut keep = keep.iter();
+    values.retain(|_| keep.next() == Some(&true));
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Welds vertices at most `tolerance` apart into one, then removes faces with no area, faces using
+    the same vertices as an earlier face and vertices that no face or polyline uses. Polygons whose
+    neighbouring corners are welded together lose the repeated corners, along with their texture
+    coordinate and vertex normal indices, rather than being removed. Polylines lose repeated
+    vertices in the same way, and are removed if fewer than two are left. Welded vertices move to
+    the vertex they are welded into, and the normals of kept faces are left as they were.
+    This method may return `MeshError` if:
+    - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+
+    Parameters:
+    - `tolerance: T` - The greatest distance between welded vertices. Zero welds only vertices at
+      the same position.
+
+    Returns:
+    - `Result<CleanupReport, MeshError>` - What was changed, else a `MeshError`.
+     */
+    pub fn cleanup(&mut self, tolerance: T) -> Result<CleanupReport, MeshError> {
+        let faces: Vec<&[usize]> = self.faces.iter().map(|f| &f[..]).collect();
+        let mut cleanup = Cleanup::new(&self.vertices, &faces, &self.polylines, tolerance)?;
+
+        for polyline in &mut self.polylines {
+            *polyline = polyline
+                .iter()
+                .map(|&v| cleanup.vertex_indices[v])
+                .collect();
+            polyline.dedup();
+        }
+        let count = self.polylines.len();
+        self.polylines.retain(|polyline| polyline.len() >= 2);
+        cleanup.report.collapsed_polylines = count - self.polylines.len();
+
+        for (f, corners) in cleanup.corners.iter().enumerate() {
+            let Some(corners) = corners else { continue };
+            let face = &mut self.faces[f];
+            *face = corners
+                .iter()
+                .map(|&i| cleanup.vertex_indices[face[i]])
+                .collect();
+            for indices in [
+                &mut self.face_texture_coords[f],
+                &mut self.face_vertex_normals[f],
+            ]
+            .into_iter()
+            .flatten()
+            {
+                *indices = corners.iter().map(|&i| indices[i]).collect();
+            }
+        }
+
+        let kept_faces = cleanup.kept_faces();
+        retain(&mut self.faces, &kept_faces);
+        retain(&mut self.face_normals, &kept_faces);
+        retain(&mut self.face_texture_coords, &kept_faces);
+        retain(&mut self.face_vertex_normals, &kept_faces);
+        retain(&mut self.smoothing_groups, &kept_faces);
+        retain(&mut self.face_materials, &kept_faces);
+        for attribute in &mut self.face_attributes {
+            retain(&mut attribute.values, &kept_faces);
+        }
+        cleanup.remap_groups(&mut self.groups);
+        cleanup.remap_groups(&mut self.objects);
+        cleanup.remap_groups(&mut self.patches);
+
+        retain(&mut self.vertices, &cleanup.kept_vertices);
+        for attribute in &mut self.vertex_attributes {
+            retain(&mut attribute.values, &cleanup.kept_vertices);
+        }
+        Ok(cleanup.report)
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Welds vertices at most `tolerance` apart into one, then removes faces with no area, faces using
+    the same vertices as an earlier face and vertices that no face uses. Welded vertices move to
+    the vertex they are welded into, and the normals of kept faces are left as they were.
+    This method may return `MeshError` if:
+    - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+
+    Parameters:
+    - `tolerance: T` - The greatest distance between welded vertices. Zero welds only vertices at
+      the same position.
+
+    Returns:
+    - `Result<CleanupReport, MeshError>` - What was changed, else a `MeshError`.
+     */
+    pub fn cleanup(&mut self, tolerance: T) -> Result<CleanupReport, MeshError> {
+        let faces: Vec<&[usize]> = self.faces.iter().map(|f| &f[..]).collect();
+        let cleanup = Cleanup::new(&self.vertices, &faces, &[], tolerance)?;
+
+        for face in &mut self.faces {
+            *face = face.map(|v| cleanup.vertex_indices[v]);
+        }
+        let kept_faces = cleanup.kept_faces();
+        retain(&mut self.faces, &kept_faces);
+        retain(&mut self.face_normals, &kept_faces);
+        cleanup.remap_groups(&mut self.patches);
+        retain(&mut self.vertices, &cleanup.kept_vertices);
+        Ok(cleanup.report)
+    }
+}
@@ -0,0 +1,217 @@
+/*! Reading and writing of Wavefront MTL material libraries, which OBJ files reference through
+`mtllib` and assign to faces through `usemtl`. */
+
+use std::io::{BufRead, BufReader, Error, Write};
+
+use super::{open_file, write_file, MeshError};
+use crate::Float;
+
+/// A material from an MTL library. Properties the library does not set are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// The ambient colour, `Ka`.
+    pub ambient: Option<[Float; 3]>,
+    /// The diffuse colour, `Kd`.
+    pub diffuse: Option<[Float; 3]>,
+    /// The specular colour, `Ks`.
+    pub specular: Option<[Float; 3]>,
+    /// The emissive colour, `Ke`.
+    pub emissive: Option<[Float; 3]>,
+    /// The specular exponent, `Ns`.
+    pub shininess: Option<Float>,
+    /// The opacity, `d`, where 1 is fully opaque. Libraries giving the transparency `Tr` instead
+    /// are read as `d = 1 - Tr`.
+    pub dissolve: Option<Float>,
+    /// The index of refraction, `Ni`.
+    pub optical_density: Option<Float>,
+    /// The illumination model, `illum`.
+    pub illumination: Option<u32>,
+    /// The ambient texture, `map_Ka`, including any options given before the file name.
+    pub ambient_map: Option<String>,
+    /// The diffuse texture, `map_Kd`.
+    pub diffuse_map: Option<String>,
+    /// The specular texture, `map_Ks`.
+    pub specular_map: Option<String>,
+    /// The opacity texture, `map_d`.
+    pub alpha_map: Option<String>,
+    /// The bump map, `map_Bump` or `bump`.
+    pub bump_map: Option<String>,
+}
+
+/**
+Parses the values of an MTL statement. A colour given as a single value is grey, with that value
+in every channel.
+
+- Float cannot be processed: `MeshError::FormatError("Failed to parse float.")`
+- Wrong number of values: `MeshError::FormatError("Unable to process string.")`
+
+Parameters:
+- `value_string: &str` - String slice to process.
+
+Returns:
+- `Result<Vec<Float>, MeshError>` - The values, else a `MeshError::FormatError`.
+ */
+fn parse_mtl_floats(value_string: &str) -> Result<Vec<Float>, MeshError> {
+    value_string
+        .split_ascii_whitespace()
+        .map(|v| {
+            v.parse()
+                .map_err(|_| MeshError::FormatError("Failed to parse float."))
+        })
+        .collect()
+}
+
+/// Parses a colour, which is given as either one or three values.
+fn parse_mtl_colour(value_string: &str) -> Result<[Float; 3], MeshError> {
+    match parse_mtl_floats(value_string)?[..] {
+        [grey] => Ok([grey; 3]),
+        [r, g, b] => Ok([r, g, b]),
+        _ => Err(MeshError::FormatError("Unable to process string.")),
+    }
+}
+
+/// Parses a statement holding a single value.
+fn parse_mtl_float(value_string: &str) -> Result<Float, MeshError> {
+    match parse_mtl_floats(value_string)?[..] {
+        [value] => Ok(value),
+        _ => Err(MeshError::FormatError("Unable to process string.")),
+    }
+}
+
+/**
+Loads the materials of the MTL library `filename`, in the order they are defined. Statements this
+module does not know, such as reflection maps, are skipped.
+
+- Statement before the first `newmtl`: `MeshError::FormatError("Material statement before
+  newmtl.")`
+- Values cannot be processed: `MeshError::FormatError("Failed to parse float.")`,
+  `MeshError::FormatError("Failed to parse integer.")` or
+  `MeshError::FormatError("Unable to process string.")`
+
+Parameters:
+- `filename: &str` - A string containing the file path to load.
+
+Returns:
+- `Result<Vec<Material>, MeshError>` - The materials of the library, otherwise a `MeshError` of
+  some form, depending on the error.
+ */
+pub fn load_mtl(filename: &str) -> Result<Vec<Material>, MeshError> {
+    let mut bufread = BufReader::new(open_file(filename)?);
+    let mut materials: Vec<Material> = Vec::new();
+    let mut buffer_string = String::new();
+
+    while match bufread.read_line(&mut buffer_string) {
+        Ok(t) => t != 0,
+        Err(e) => {
+            return Err(MeshError::file("Could not read next line.", filename, e));
+        }
+    } {
+        let line = buffer_string.trim();
+        let (keyword, rest) = line
+            .split_once(|c: char| c.is_ascii_whitespace())
+            .unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        if keyword == "newmtl" {
+            materials.push(Material {
+                name: rest.to_string(),
+                ..Default::default()
+            });
+        } else if !(keyword.is_empty() || keyword.starts_with('#')) {
+            let material = materials
+                .last_mut()
+                .ok_or(MeshError::FormatError("Material statement before newmtl."))?;
+            match keyword {
+                "Ka" => material.ambient = Some(parse_mtl_colour(rest)?),
+                "Kd" => material.diffuse = Some(parse_mtl_colour(rest)?),
+                "Ks" => material.specular = Some(parse_mtl_colour(rest)?),
+                "Ke" => material.emissive = Some(parse_mtl_colour(rest)?),
+                "Ns" => material.shininess = Some(parse_mtl_float(rest)?),
+                "d" => material.dissolve = Some(parse_mtl_float(rest)?),
+                "Tr" => material.dissolve = Some(1. - parse_mtl_float(rest)?),
+                "Ni" => material.optical_density = Some(parse_mtl_float(rest)?),
+                "illum" => {
+                    material.illumination = Some(
+                        rest.parse()
+                            .map_err(|_| MeshError::FormatError("Failed to parse integer."))?,
+                    )
+                }
+                "map_Ka" => material.ambient_map = Some(rest.to_string()),
+                "map_Kd" => material.diffuse_map = Some(rest.to_string()),
+                "map_Ks" => material.specular_map = Some(rest.to_string()),
+                "map_d" => material.alpha_map = Some(rest.to_string()),
+                "map_Bump" | "map_bump" | "bump" => material.bump_map = Some(rest.to_string()),
+                _ => {}
+            }
+        }
+        buffer_string.clear();
+    }
+
+    Ok(materials)
+}
+
+/**
+Writes materials to the MTL library `filename`.
+
+Parameters:
+- `filename: &str` - A string containing the filename to save the materials to.
+- `materials: &[Material]` - The materials to write.
+
+Returns:
+- `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is successful,
+  otherwise `MeshError::FileError`.
+ */
+pub(crate) fn write_mtl(filename: &str, materials: &[Material]) -> Result<usize, MeshError> {
+    write_file(filename, |file| write_materials(file, materials))
+}
+
+/// Writes materials as MTL data to `file`, as `write_mtl` does.
+fn write_materials<W: Write>(file: &mut W, materials: &[Material]) -> Result<usize, Error> {
+    let mut bytes: usize = 0;
+    let mut line = |string: String| -> Result<(), Error> {
+        writeln!(file, "{}", string)?;
+        bytes += string.len() + 1;
+        Ok(())
+    };
+
+    for material in materials {
+        line(format!("newmtl {}", material.name))?;
+        for (keyword, colour) in [
+            ("Ka", material.ambient),
+            ("Kd", material.diffuse),
+            ("Ks", material.specular),
+            ("Ke", material.emissive),
+        ] {
+            if let Some([r, g, b]) = colour {
+                line(format!("{} {} {} {}", keyword, r, g, b))?;
+            }
+        }
+        for (keyword, value) in [
+            ("Ns", material.shininess),
+            ("d", material.dissolve),
+            ("Ni", material.optical_density),
+        ] {
+            if let Some(value) = value {
+                line(format!("{} {}", keyword, value))?;
+            }
+        }
+        if let Some(illumination) = material.illumination {
+            line(format!("illum {}", illumination))?;
+        }
+        for (keyword, map) in [
+            ("map_Ka", &material.ambient_map),
+            ("map_Kd", &material.diffuse_map),
+            ("map_Ks", &material.specular_map),
+            ("map_d", &material.alpha_map),
+            ("map_Bump", &material.bump_map),
+        ] {
+            if let Some(map) = map {
+                line(format!("{} {}", keyword, map))?;
+            }
+        }
+        line(String::new())?;
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,203 @@
+/*! Parallel loading of OBJ files, enabled by the `rayon` feature. The file is memory-mapped and
+split into chunks of whole lines, whose statements are parsed on every core a batch of chunks at a
+time. The statements of each batch are then applied to the mesh in file order by the same code as
+`load_obj`, so the mesh, and the error reported for the first bad line, are the same as when
+loading serially. */
+
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
+
+use memmap2::Mmap;
+use nalgebra::RealField;
+use rayon::prelude::*;
+
+use super::{
+    apply_obj_statement, locate_obj_error, obj_read_error, open_file, parse_obj_statement,
+    MeshError, ObjError, ObjState, ObjStatement, PolygonMesh,
+};
+
+/// The number of chunks each thread is given in a batch, so that threads finishing early can
+/// take more.
+const CHUNKS_PER_THREAD: usize = 8;
+
+/// The largest chunk, in bytes, which bounds the statements held in memory at once to those of a
+/// batch of chunks, however large the file.
+const MAX_CHUNK_LENGTH: usize = 1 << 20;
+
+/**
+Splits `bytes` into about `count` chunks, each ending just after a line break or at the end of
+`bytes`.
+
+Parameters:
+- `bytes: &[u8]` - The bytes to split.
+- `count: usize` - The number of chunks to aim for.
+
+Returns:
+- `Vec<&[u8]>` - The chunks, in order.
+ */
+fn line_chunks(bytes: &[u8], count: usize) -> Vec<&[u8]> {
+    let size = bytes.len().div_ceil(count.max(1)).max(1);
+    let mut chunks = Vec::with_capacity(count);
+    let mut start = 0;
+    while start < bytes.len() {
+        let end = (start + size).min(bytes.len());
+        let end = match bytes[end..].iter().position(|&b| b == b'\n') {
+            Some(offset) => end + offset + 1,
+            None => bytes.len(),
+        };
+        chunks.push(&bytes[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+/// A line of a chunk: the line itself and its parsed statement, or `None` if the line is not
+/// valid UTF-8.
+type ChunkLine<'a, T> = Option<(&'a str, Result<ObjStatement<'a, T>, ObjError<'a>>)>;
+
+/**
+Parses the lines of a chunk into statements, stopping at the first line that cannot be read or
+parsed, as no line after it is applied. Each line of the chunk gives one entry, so that lines can
+be numbered by counting entries.
+
+Parameters:
+- `chunk: &[u8]` - The chunk to parse.
+
+Returns:
+- `Vec<ChunkLine<T>>` - The lines of the chunk, ending with the first bad line if there is one.
+ */
+fn parse_chunk<T>(chunk: &[u8]) -> Vec<ChunkLine<'_, T>>
+where
+    T: RealField + Copy + FromStr,
+{
+    let mut lines = Vec::new();
+    for line in chunk.split_inclusive(|&b| b == b'\n') {
+        let line = std::str::from_utf8(line)
+            .ok()
+            .map(|line| (line, parse_obj_statement(line.trim())));
+        let failed = !matches!(line, Some((_, Ok(_))));
+        lines.push(line);
+        if failed {
+            break;
+        }
+    }
+    lines
+}
+
+/**
+Maps `file` into memory, so that it can be parsed without copying it.
+
+Parameters:
+- `file: &File` - The open file to map.
+
+Returns:
+- `Option<Mmap>` - The mapped file, or `None` for files that cannot be mapped, such as directories
+  and pipes.
+ */
+pub(crate) fn map_file(file: &File) -> Option<Mmap> {
+    // SAFETY: The map is only read while the file is open, and, as with any loader, the file must
+    // not be changed while it is being loaded.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+impl PolygonMesh {
+    /**
+    Loads a `PolygonMesh` from the filename passed in, parsing it on every core, with coordinates
+    stored as `Float`. Use `load_obj_parallel_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_obj_parallel(filename: &str) -> Result<Box<PolygonMesh>, MeshError> {
+        PolygonMesh::load_obj_parallel_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy + FromStr + Send> PolygonMesh<T> {
+    /**
+    Loads a `PolygonMesh` from the filename passed in, parsing it on every core, with coordinates
+    parsed directly as `T`. The mesh and any error are the same as those of
+    `load_obj_with_precision`, which is faster for small files.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_obj_parallel_with_precision(
+        filename: &str,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let file = open_file(filename)?;
+        let path = Some(Path::new(filename));
+        // Files that cannot be mapped are read a line at a time, so they load, or fail, as they do
+        // serially.
+        match map_file(&file) {
+            Some(bytes) => PolygonMesh::parse_obj_parallel(&bytes, path),
+            None => PolygonMesh::parse_obj(BufReader::new(file), path),
+        }
+    }
+
+    /**
+    Parses a `PolygonMesh` from OBJ data in memory on every core, as
+    `load_obj_parallel_with_precision` does.
+
+    Parameters:
+    - `bytes: &[u8]` - The OBJ data.
+    - `path: Option<&Path>` - The path of the OBJ file, used to find its material libraries and
+      to report errors, or `None` if the data is not read from a file.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the parsing
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub(crate) fn parse_obj_parallel(
+        bytes: &[u8],
+        path: Option<&Path>,
+    ) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let batch = rayon::current_num_threads() * CHUNKS_PER_THREAD;
+        let chunks = line_chunks(bytes, batch.max(bytes.len().div_ceil(MAX_CHUNK_LENGTH)));
+
+        let mut polymesh = PolygonMesh {
+            vertices: Vec::with_capacity(4),
+            faces: Vec::with_capacity(4),
+            face_normals: Vec::with_capacity(4),
+            ..Default::default()
+        };
+        let mut state = ObjState::default();
+        let directory = path.map(|p| p.parent().unwrap_or(Path::new("")));
+        let mut number = 0;
+        // Each batch is parsed in parallel and applied before the next is parsed, so only the
+        // statements of one batch are held at a time.
+        for batch in chunks.chunks(batch) {
+            let parsed: Vec<Vec<ChunkLine<T>>> =
+                batch.par_iter().map(|chunk| parse_chunk(chunk)).collect();
+            for line in parsed.into_iter().flatten() {
+                number += 1;
+                // Invalid UTF-8 fails with the error `read_line` gives when loading serially.
+                let Some((line, statement)) = line else {
+                    let error =
+                        Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8");
+                    return Err(obj_read_error(error, number, path));
+                };
+                if let Some(error) = match statement {
+                    Ok(statement) => {
+                        apply_obj_statement(&mut polymesh, &mut state, statement, directory)
+                    }
+                    Err(e) => Some(e),
+                } {
+                    return Err(locate_obj_error(error, line, number, path));
+                }
+            }
+        }
+
+        Ok(Box::new(polymesh))
+    }
+}
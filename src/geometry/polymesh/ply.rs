@@ -0,0 +1,701 @@
+/*! Reading and writing of Stanford PLY files in all three encodings. Vertex coordinates and face
+vertex lists become the geometry of a `PolygonMesh`, while every other scalar property of the
+vertices and faces is kept as a named `Attribute`. */
+
+use std::io::{Read, Write};
+use std::mem::size_of;
+use std::path::Path;
+
+use nalgebra::RealField;
+
+use super::{
+    locate_text_error, open_file, write_error, write_file, Attribute, AttributeType, MeshError,
+    MutateMesh, PolygonMesh,
+};
+use crate::Point3;
+
+/// The encoding of a PLY file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// The names a face's vertex list may be given.
+const FACE_LISTS: [&str; 2] = ["vertex_indices", "vertex_index"];
+
+/// The type of a property declared in a PLY header.
+enum PropertyKind {
+    Scalar(AttributeType),
+    /// A list with its length stored as the first type and its items as the second.
+    List(AttributeType, AttributeType),
+}
+
+/// A property declared in a PLY header.
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+/// An element declared in a PLY header, along with its properties in file order.
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl AttributeType {
+    /// Parses the name of a PLY type, accepting both the original and the sized names.
+    fn from_ply(name: &str) -> Result<AttributeType, MeshError> {
+        Ok(match name {
+            "char" | "int8" => AttributeType::Int8,
+            "uchar" | "uint8" => AttributeType::Uint8,
+            "short" | "int16" => AttributeType::Int16,
+            "ushort" | "uint16" => AttributeType::Uint16,
+            "int" | "int32" => AttributeType::Int32,
+            "uint" | "uint32" => AttributeType::Uint32,
+            "float" | "float32" => AttributeType::Float32,
+            "double" | "float64" => AttributeType::Float64,
+            _ => return Err(MeshError::FormatError("Invalid PLY property type.")),
+        })
+    }
+
+    /// The original PLY name of the type.
+    fn ply_name(self) -> &'static str {
+        match self {
+            AttributeType::Int8 => "char",
+            AttributeType::Uint8 => "uchar",
+            AttributeType::Int16 => "short",
+            AttributeType::Uint16 => "ushort",
+            AttributeType::Int32 => "int",
+            AttributeType::Uint32 => "uint",
+            AttributeType::Float32 => "float",
+            AttributeType::Float64 => "double",
+        }
+    }
+
+    /// The number of bytes a value of the type takes in a binary file.
+    pub(crate) fn size(self) -> usize {
+        match self {
+            AttributeType::Int8 | AttributeType::Uint8 => 1,
+            AttributeType::Int16 | AttributeType::Uint16 => 2,
+            AttributeType::Int32 | AttributeType::Uint32 | AttributeType::Float32 => 4,
+            AttributeType::Float64 => 8,
+        }
+    }
+
+    /// Converts `value` to the type and writes it as text.
+    pub(crate) fn to_text(self, value: f64) -> String {
+        match self {
+            AttributeType::Float32 => (value as f32).to_string(),
+            AttributeType::Float64 => value.to_string(),
+            _ => (value as i64).to_string(),
+        }
+    }
+
+    /// Converts `value` to the type and returns its bytes in the requested byte order.
+    pub(crate) fn to_bytes(self, value: f64, big_endian: bool) -> Vec<u8> {
+        let mut raw = match self {
+            AttributeType::Int8 => (value as i8).to_le_bytes().to_vec(),
+            AttributeType::Uint8 => (value as u8).to_le_bytes().to_vec(),
+            AttributeType::Int16 => (value as i16).to_le_bytes().to_vec(),
+            AttributeType::Uint16 => (value as u16).to_le_bytes().to_vec(),
+            AttributeType::Int32 => (value as i32).to_le_bytes().to_vec(),
+            AttributeType::Uint32 => (value as u32).to_le_bytes().to_vec(),
+            AttributeType::Float32 => (value as f32).to_le_bytes().to_vec(),
+            AttributeType::Float64 => value.to_le_bytes().to_vec(),
+        };
+        if big_endian {
+            raw.reverse();
+        }
+        raw
+    }
+}
+
+/// The body of a PLY file, from which values are read in order. An ASCII body keeps the whole
+/// text of the file and the last token read, so that errors can be given a line and column.
+enum Body<'a> {
+    Ascii {
+        text: &'a str,
+        tokens: std::str::SplitAsciiWhitespace<'a>,
+        last: &'a str,
+    },
+    Binary {
+        bytes: &'a [u8],
+        big_endian: bool,
+    },
+}
+
+impl Body<'_> {
+    /**
+    Reads the next value from the body.
+
+    - Body ends early: `MeshError::FormatError("Unexpected end of file.")`
+    - ASCII value cannot be parsed: `MeshError::FormatError("Failed to parse float.")` or
+      `MeshError::FormatError("Failed to parse integer.")`
+
+    Parameters:
+    - `value_type: AttributeType` - The type of the value.
+
+    Returns:
+    - `Result<f64, MeshError>` - The value, else a `MeshError::FormatError`.
+     */
+    fn read(&mut self, value_type: AttributeType) -> Result<f64, MeshError> {
+        let end = MeshError::FormatError("Unexpected end of file.");
+        match self {
+            Body::Ascii { text, tokens, last } => {
+                // A missing token is reported at the end of the text.
+                *last = tokens.next().unwrap_or(&text[text.len()..]);
+                let token = *last;
+                if token.is_empty() {
+                    return Err(end);
+                }
+                match value_type {
+                    AttributeType::Float32 | AttributeType::Float64 => token
+                        .parse()
+                        .map_err(|_| MeshError::FormatError("Failed to parse float.")),
+                    _ => token
+                        .parse::<i64>()
+                        .map(|v| v as f64)
+                        .map_err(|_| MeshError::FormatError("Failed to parse integer.")),
+                }
+            }
+            Body::Binary { bytes, big_endian } => {
+                let size = value_type.size();
+                if bytes.len() < size {
+                    return Err(end);
+                }
+                let mut raw = [0; 8];
+                raw[..size].copy_from_slice(&bytes[..size]);
+                *bytes = &bytes[size..];
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+
+                let [a, b, c, d, ..] = raw;
+                Ok(match value_type {
+                    AttributeType::Int8 => f64::from(i8::from_le_bytes([a])),
+                    AttributeType::Uint8 => f64::from(a),
+                    AttributeType::Int16 => f64::from(i16::from_le_bytes([a, b])),
+                    AttributeType::Uint16 => f64::from(u16::from_le_bytes([a, b])),
+                    AttributeType::Int32 => f64::from(i32::from_le_bytes([a, b, c, d])),
+                    AttributeType::Uint32 => f64::from(u32::from_le_bytes([a, b, c, d])),
+                    AttributeType::Float32 => f64::from(f32::from_le_bytes([a, b, c, d])),
+                    AttributeType::Float64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+
+    /**
+    Gives an error found while reading a record of the body its position: the line, column and
+    last token read for an ASCII body, otherwise the element and number of the record.
+
+    Parameters:
+    - `error: MeshError` - The error found in the record.
+    - `element: &str` - The name of the element the record belongs to.
+    - `record: usize` - The index of the record among those of its element, from 0.
+
+    Returns:
+    - `MeshError` - A `MeshError::LineError` or `MeshError::RecordError`, without a path.
+     */
+    fn locate(&self, error: MeshError, element: &str, record: usize) -> MeshError {
+        match self {
+            Body::Ascii { text, last, .. } => locate_text_error(error, text, last),
+            Body::Binary { .. } => MeshError::RecordError {
+                path: None,
+                element: element.to_string(),
+                record: record + 1,
+                source: Box::new(error),
+            },
+        }
+    }
+}
+
+/**
+Parses the header of a PLY file.
+
+- File does not start with a valid header: `MeshError::FormatError("Invalid PLY header.")`
+- Property of an unknown type: `MeshError::FormatError("Invalid PLY property type.")`
+
+Parameters:
+- `bytes: &[u8]` - The contents of the file.
+
+Returns:
+- `Result<(PlyFormat, Vec<Element>, usize), MeshError>` - The encoding of the body, the declared
+  elements, and the offset at which the body starts, else a `MeshError::FormatError`.
+ */
+fn parse_header(bytes: &[u8]) -> Result<(PlyFormat, Vec<Element>, usize), MeshError> {
+    let invalid = || MeshError::FormatError("Invalid PLY header.");
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+    let mut number = 0;
+
+    loop {
+        number += 1;
+        let length = bytes[offset..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(invalid)?;
+        let line = std::str::from_utf8(&bytes[offset..offset + length]).map_err(|_| invalid())?;
+        let first = offset == 0;
+        offset += length + 1;
+
+        let words: Vec<&str> = line.split_ascii_whitespace().collect();
+        match words.as_slice() {
+            ["ply"] if first => {}
+            _ if first => return Err(invalid()),
+            ["format", encoding, "1.0"] => {
+                format = Some(match *encoding {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(invalid()),
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid())?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", length, item, name] => {
+                let kind = PropertyKind::List(
+                    AttributeType::from_ply(length)?,
+                    AttributeType::from_ply(item)?,
+                );
+                let element = elements.last_mut().ok_or_else(invalid)?;
+                // Only the face vertex list has a place in a `PolygonMesh`, so any other list on
+                // the vertices or faces would be lost.
+                let kept = element.name == "face" && FACE_LISTS.contains(name);
+                if !kept && (element.name == "vertex" || element.name == "face") {
+                    // The name is a slice of the line, so its offset in the line gives its column.
+                    let column = name.as_ptr() as usize - line.as_ptr() as usize + 1;
+                    return Err(MeshError::LineError {
+                        path: None,
+                        line: number,
+                        column,
+                        token: name.to_string(),
+                        source: Box::new(MeshError::FormatError("Unsupported PLY list property.")),
+                    });
+                }
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind,
+                });
+            }
+            ["property", value_type, name] => {
+                let kind = PropertyKind::Scalar(AttributeType::from_ply(value_type)?);
+                elements
+                    .last_mut()
+                    .ok_or_else(invalid)?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        kind,
+                    });
+            }
+            ["end_header"] => return Ok((format.ok_or_else(invalid)?, elements, offset)),
+            _ => return Err(invalid()),
+        }
+    }
+}
+
+/**
+Reads a `PolygonMesh` from the contents of a PLY file. The `x`, `y` and `z` properties of the
+`vertex` element and the `vertex_indices` list of the `face` element form the geometry, and every
+other scalar property of those two elements becomes an attribute. Elements other than vertices
+and faces are skipped.
+
+- Vertex or face list property other than the face vertex list, including list coordinates:
+  `MeshError::LineError` on the header line of the property, wrapping
+  `MeshError::FormatError("Unsupported PLY list property.")`
+- Element with a nonzero count and no properties: `MeshError::FormatError("PLY element has no
+  properties.")`
+- Vertices without coordinates: `MeshError::FormatError("PLY vertices lack coordinates.")`
+- Faces without a vertex list: `MeshError::FormatError("PLY faces lack vertex lists.")`
+
+Errors in the body are returned as a `MeshError::LineError` at the offending token of an ASCII
+file, or a `MeshError::RecordError` naming the element and record of a binary file, around:
+- Body ends early: `MeshError::FormatError("Unexpected end of file.")`
+- ASCII value cannot be parsed: `MeshError::FormatError("Failed to parse float.")` or
+  `MeshError::FormatError("Failed to parse integer.")`
+- Face has less than 3 vertices: `MeshError::FormatError("Face does not have enough vertices.")`
+- Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+  mesh.")`
+
+Parameters:
+- `bytes: &[u8]` - The contents of the file.
+
+Returns:
+- `Result<PolygonMesh<T>, MeshError>` - The mesh, else a `MeshError`.
+ */
+fn parse_ply<T: RealField + Copy>(bytes: &[u8]) -> Result<PolygonMesh<T>, MeshError> {
+    let (format, elements, offset) = parse_header(bytes)?;
+    let mut body = match format {
+        PlyFormat::Ascii => {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|_| MeshError::FormatError("Unable to process string."))?;
+            Body::Ascii {
+                text,
+                tokens: text[offset..].split_ascii_whitespace(),
+                last: &text[offset..offset],
+            }
+        }
+        _ => Body::Binary {
+            bytes: &bytes[offset..],
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut vertices: Vec<[f64; 3]> = Vec::new();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    let mut vertex_attributes: Vec<Attribute> = Vec::new();
+    let mut face_attributes: Vec<Attribute> = Vec::new();
+    // Faces are checked against the vertex count of the header as they are read, so that their
+    // errors point into the body.
+    let vertex_count = elements
+        .iter()
+        .find(|element| element.name == "vertex")
+        .map_or(0, |element| element.count);
+
+    for element in &elements {
+        // Such an element takes up no bytes, so its count would be looped over without bound.
+        if element.count > 0 && element.properties.is_empty() {
+            return Err(MeshError::FormatError("PLY element has no properties."));
+        }
+        let is_vertex = element.name == "vertex";
+        let is_face = element.name == "face";
+        let coordinates =
+            ["x", "y", "z"].map(|axis| element.properties.iter().position(|p| p.name == axis));
+        if is_vertex && coordinates.contains(&None) {
+            return Err(MeshError::FormatError("PLY vertices lack coordinates."));
+        }
+        if is_face
+            && !element.properties.iter().any(|p| {
+                FACE_LISTS.contains(&p.name.as_str()) && matches!(p.kind, PropertyKind::List(..))
+            })
+        {
+            return Err(MeshError::FormatError("PLY faces lack vertex lists."));
+        }
+
+        // The attributes this element fills, indexed like its properties.
+        let attributes = if is_vertex {
+            &mut vertex_attributes
+        } else {
+            &mut face_attributes
+        };
+        let mut slots: Vec<Option<usize>> = Vec::with_capacity(element.properties.len());
+        for (p, property) in element.properties.iter().enumerate() {
+            let kept = match property.kind {
+                PropertyKind::Scalar(value_type) if is_vertex || is_face => {
+                    (is_face || !coordinates.contains(&Some(p))).then_some(value_type)
+                }
+                _ => None,
+            };
+            slots.push(kept.map(|value_type| {
+                attributes.push(Attribute {
+                    name: property.name.clone(),
+                    value_type,
+                    // The count in the header is not trusted to size the values, as the body may
+                    // hold far fewer.
+                    values: Vec::new(),
+                });
+                attributes.len() - 1
+            }));
+        }
+
+        for record in 0..element.count {
+            let mut read_record = || -> Result<(), MeshError> {
+                let mut vertex = [0.; 3];
+                for (p, property) in element.properties.iter().enumerate() {
+                    match property.kind {
+                        PropertyKind::Scalar(value_type) => {
+                            let value = body.read(value_type)?;
+                            if let Some(slot) = slots[p] {
+                                attributes[slot].values.push(value);
+                            } else if let Some(axis) =
+                                coordinates.iter().position(|&c| c == Some(p))
+                            {
+                                vertex[axis] = value;
+                            }
+                        }
+                        PropertyKind::List(length_type, item_type) => {
+                            let is_face_list =
+                                is_face && FACE_LISTS.contains(&property.name.as_str());
+                            let length = body.read(length_type)?.max(0.) as usize;
+                            if is_face_list && length < 3 {
+                                return Err(MeshError::FormatError(
+                                    "Face does not have enough vertices.",
+                                ));
+                            }
+                            let mut face =
+                                Vec::with_capacity(if is_face_list { length } else { 0 });
+                            for _ in 0..length {
+                                let item = body.read(item_type)?;
+                                if !is_face_list {
+                                    continue;
+                                }
+                                if item < 0. || item >= vertex_count as f64 {
+                                    return Err(MeshError::IndexingError(
+                                        "Vertex not contained in mesh.",
+                                    ));
+                                }
+                                face.push(item as usize);
+                            }
+                            if is_face_list {
+                                faces.push(face);
+                            }
+                        }
+                    }
+                }
+                if is_vertex {
+                    vertices.push(vertex);
+                }
+                Ok(())
+            };
+            read_record().map_err(|e| body.locate(e, &element.name, record))?;
+        }
+    }
+
+    let mut polymesh = PolygonMesh {
+        vertices: Vec::with_capacity(vertices.len()),
+        faces: Vec::with_capacity(faces.len()),
+        face_normals: Vec::with_capacity(faces.len()),
+        ..Default::default()
+    };
+    for vertex in vertices {
+        polymesh.add_vertex(Point3::from(vertex.map(nalgebra::convert)));
+    }
+    for face in faces {
+        polymesh.push_face(face, None)?;
+    }
+    polymesh.vertex_attributes = vertex_attributes;
+    polymesh.face_attributes = face_attributes;
+
+    Ok(polymesh)
+}
+
+/// The body of a PLY file being written, which lays values out in the requested encoding.
+struct BodyWriter {
+    format: PlyFormat,
+    bytes: Vec<u8>,
+    line: Vec<String>,
+}
+
+impl BodyWriter {
+    /// Appends `value` to the current element, stored as `value_type`.
+    fn push(&mut self, value_type: AttributeType, value: f64) {
+        if self.format == PlyFormat::Ascii {
+            self.line.push(value_type.to_text(value));
+            return;
+        }
+
+        let big_endian = self.format == PlyFormat::BinaryBigEndian;
+        self.bytes.extend(value_type.to_bytes(value, big_endian));
+    }
+
+    /// Finishes the current element, which ends its line in ASCII files.
+    fn end_element(&mut self) {
+        if self.format == PlyFormat::Ascii {
+            self.bytes.extend(self.line.join(" ").bytes());
+            self.bytes.push(b'\n');
+            self.line.clear();
+        }
+    }
+}
+
+impl PolygonMesh {
+    /**
+    Loads a `PolygonMesh` from the ASCII or binary PLY file passed in, with coordinates stored as
+    `Float`. Use `load_ply_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_ply(filename: &str) -> Result<Box<PolygonMesh>, MeshError> {
+        PolygonMesh::load_ply_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Loads a `PolygonMesh` from the ASCII or binary PLY file passed in, with coordinates stored as
+    `T`. Scalar vertex and face properties other than the coordinates, such as colours, normals or
+    scalar fields, are kept as attributes of the same name.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_ply_with_precision(filename: &str) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        let mut bytes = Vec::new();
+        open_file(filename)?
+            .read_to_end(&mut bytes)
+            .map_err(|e| MeshError::file("Could not read file.", filename, e))?;
+        PolygonMesh::parse_ply(&bytes).map_err(|error| error.in_file(Path::new(filename)))
+    }
+
+    /**
+    Parses a `PolygonMesh` from ASCII or binary PLY data, as `load_ply_with_precision` does.
+
+    Parameters:
+    - `bytes: &[u8]` - The PLY data.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the parsing
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub(crate) fn parse_ply(bytes: &[u8]) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        Ok(Box::new(parse_ply(bytes)?))
+    }
+
+    /**
+    Writes a `PolygonMesh` and its attributes to the PLY file passed in. Coordinates are written
+    as `float` when `T` is no wider than 32 bits and as `double` otherwise. The attributes are
+    checked before the file is created.
+    This method may return `MeshError` if:
+    - Vertex attribute length differs from the vertex count: `MeshError::FormatError("Attribute
+      length does not match vertex count.")`
+    - Face attribute length differs from the face count: `MeshError::FormatError("Attribute
+      length does not match face count.")`
+    - File cannot be created or written: `MeshError::FileError`, holding the `std::io::Error`
+      that stopped it
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: PlyFormat` - The encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError`.
+     */
+    pub fn write_ply(&self, filename: &str, format: PlyFormat) -> Result<usize, MeshError> {
+        let bytes = self.encode_ply(format)?;
+        write_file(filename, |file| file.write_all(&bytes).map(|_| bytes.len()))
+    }
+
+    /**
+    Writes a `PolygonMesh` and its attributes as PLY data to the writer passed in, as `write_ply`
+    does.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the PLY data to.
+    - `format: PlyFormat` - The encoding of the data.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, with failures to write given as a
+      `MeshError::FileError` without a path.
+     */
+    pub(crate) fn write_ply_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        format: PlyFormat,
+    ) -> Result<usize, MeshError> {
+        let bytes = self.encode_ply(format)?;
+        writer.write_all(&bytes).map_err(write_error)?;
+        Ok(bytes.len())
+    }
+
+    /// Encodes the mesh and its attributes as the contents of a PLY file, as `write_ply` writes
+    /// them, after checking that every attribute has a value for each vertex or face.
+    fn encode_ply(&self, format: PlyFormat) -> Result<Vec<u8>, MeshError> {
+        if self
+            .vertex_attributes
+            .iter()
+            .any(|a| a.values.len() != self.vertices.len())
+        {
+            return Err(MeshError::FormatError(
+                "Attribute length does not match vertex count.",
+            ));
+        }
+        if self
+            .face_attributes
+            .iter()
+            .any(|a| a.values.len() != self.faces.len())
+        {
+            return Err(MeshError::FormatError(
+                "Attribute length does not match face count.",
+            ));
+        }
+
+        let coordinate_type = if size_of::<T>() <= 4 {
+            AttributeType::Float32
+        } else {
+            AttributeType::Float64
+        };
+        let length_type = if self.faces.iter().all(|f| f.len() <= u8::MAX as usize) {
+            AttributeType::Uint8
+        } else {
+            AttributeType::Int32
+        };
+
+        let mut header = vec![
+            "ply".to_string(),
+            format!(
+                "format {} 1.0",
+                match format {
+                    PlyFormat::Ascii => "ascii",
+                    PlyFormat::BinaryLittleEndian => "binary_little_endian",
+                    PlyFormat::BinaryBigEndian => "binary_big_endian",
+                }
+            ),
+            "comment rasterflow".to_string(),
+            format!("element vertex {}", self.vertices.len()),
+        ];
+        for axis in ["x", "y", "z"] {
+            header.push(format!("property {} {}", coordinate_type.ply_name(), axis));
+        }
+        for attribute in &self.vertex_attributes {
+            let value_type = attribute.value_type.ply_name();
+            header.push(format!("property {} {}", value_type, attribute.name));
+        }
+        header.push(format!("element face {}", self.faces.len()));
+        header.push(format!(
+            "property list {} int vertex_indices",
+            length_type.ply_name()
+        ));
+        for attribute in &self.face_attributes {
+            let value_type = attribute.value_type.ply_name();
+            header.push(format!("property {} {}", value_type, attribute.name));
+        }
+        header.push("end_header\n".to_string());
+
+        let mut body = BodyWriter {
+            format,
+            bytes: header.join("\n").into_bytes(),
+            line: Vec::new(),
+        };
+        for (v, vertex) in self.vertices.iter().enumerate() {
+            for x in vertex.iter() {
+                body.push(coordinate_type, x.to_subset_unchecked());
+            }
+            for attribute in &self.vertex_attributes {
+                body.push(attribute.value_type, attribute.values[v]);
+            }
+            body.end_element();
+        }
+        for (f, face) in self.faces.iter().enumerate() {
+            body.push(length_type, face.len() as f64);
+            for &v in face {
+                body.push(AttributeType::Int32, v as f64);
+            }
+            for attribute in &self.face_attributes {
+                body.push(attribute.value_type, attribute.values[f]);
+            }
+            body.end_element();
+        }
+
+        Ok(body.bytes)
+    }
+}
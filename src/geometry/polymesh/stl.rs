@@ -0,0 +1,474 @@
+/*! Reading and writing of STL files. Binary and ASCII files are told apart by their size and first
+bytes, and the three corners stored separately for every facet are welded into shared vertices on
+load. */
+
+use std::collections::HashMap;
+use std::io::{Error, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use nalgebra::{RealField, Unit};
+
+use super::{
+    locate_text_error, open_file, write_error, write_file, MeshError, MutateMesh, PolygonMesh,
+    TriangleMesh, Triangulation,
+};
+use crate::{Point3, Vec3};
+
+/// The length of the header of a binary STL file.
+const HEADER_LENGTH: usize = 80;
+
+/// The length of a facet in a binary STL file: twelve `f32`s and a two byte attribute count.
+const FACET_LENGTH: usize = 50;
+
+/// The encoding of an STL file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+/// A facet as stored in an STL file: its normal followed by its three corners.
+type Facet<T> = [[T; 3]; 4];
+
+/// Converts a coordinate to `f64`, which represents every coordinate STL files can hold.
+fn to_f64<T: RealField + Copy>(x: T) -> f64 {
+    x.to_subset_unchecked()
+}
+
+/**
+Parses the facets of a binary STL file, whose size has already been checked against the facet
+count in its header.
+
+Parameters:
+- `bytes: &[u8]` - The contents of the file.
+
+Returns:
+- `Vec<Facet<T>>` - The facets of the file.
+ */
+fn parse_binary<T: RealField + Copy>(bytes: &[u8]) -> Vec<Facet<T>> {
+    bytes[HEADER_LENGTH + 4..]
+        .chunks_exact(FACET_LENGTH)
+        .map(|facet| {
+            let value = |k: usize| {
+                let start = 4 * k;
+                let raw = [0, 1, 2, 3].map(|i| facet[start + i]);
+                nalgebra::convert(f64::from(f32::from_le_bytes(raw)))
+            };
+            [0, 1, 2, 3].map(|row| [0, 1, 2].map(|column| value(3 * row + column)))
+        })
+        .collect()
+}
+
+/**
+Parses the facets of an ASCII STL file. Files holding several solids one after another are read as
+a single solid.
+
+Errors are returned as a `MeshError::LineError` giving the line, column and token they were found
+at, or the end of the file if it ends in the middle of a facet, around:
+- Keywords out of place: `MeshError::FormatError("Invalid STL facet.")`
+- Coordinates that cannot be parsed: `MeshError::FormatError("Failed to parse float.")`
+
+Parameters:
+- `text: &str` - The contents of the file.
+
+Returns:
+- `Result<Vec<Facet<T>>, MeshError>` - The facets of the file, else a `MeshError::LineError`.
+ */
+fn parse_ascii<T: RealField + Copy + FromStr>(text: &str) -> Result<Vec<Facet<T>>, MeshError> {
+    // The names following `solid` and `endsolid` may contain anything, so their lines are reduced
+    // to the keyword alone.
+    let mut tokens = text.lines().flat_map(|line| {
+        let mut words = line.split_ascii_whitespace().peekable();
+        let named = matches!(words.peek(), Some(&"solid") | Some(&"endsolid"));
+        words.take(if named { 1 } else { usize::MAX })
+    });
+
+    // A missing token is reported at the end of the text.
+    let end = &text[text.len()..];
+    let fail =
+        |message, token: &str| locate_text_error(MeshError::FormatError(message), text, token);
+    let expect = |keyword: &str, tokens: &mut dyn Iterator<Item = &str>| match tokens.next() {
+        Some(token) if token == keyword => Ok(()),
+        token => Err(fail("Invalid STL facet.", token.unwrap_or(end))),
+    };
+    let triple = |tokens: &mut dyn Iterator<Item = &str>| -> Result<[T; 3], MeshError> {
+        let mut values = [T::zero(); 3];
+        for value in &mut values {
+            let token = tokens.next().unwrap_or(end);
+            *value = token
+                .parse()
+                .map_err(|_| fail("Failed to parse float.", token))?;
+        }
+        Ok(values)
+    };
+
+    let mut facets = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            "solid" | "endsolid" => continue,
+            "facet" => {}
+            _ => return Err(fail("Invalid STL facet.", token)),
+        }
+        expect("normal", &mut tokens)?;
+        let normal = triple(&mut tokens)?;
+        expect("outer", &mut tokens)?;
+        expect("loop", &mut tokens)?;
+        let mut corners = [[T::zero(); 3]; 3];
+        for corner in &mut corners {
+            expect("vertex", &mut tokens)?;
+            *corner = triple(&mut tokens)?;
+        }
+        expect("endloop", &mut tokens)?;
+        expect("endfacet", &mut tokens)?;
+        facets.push([normal, corners[0], corners[1], corners[2]]);
+    }
+    Ok(facets)
+}
+
+/// Checks whether the size of STL data matches the facet count in its header, which makes it
+/// binary.
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < HEADER_LENGTH + 4 {
+        return false;
+    }
+    let count = [0, 1, 2, 3].map(|i| bytes[HEADER_LENGTH + i]);
+    let count = u32::from_le_bytes(count) as usize;
+    count.checked_mul(FACET_LENGTH) == Some(bytes.len() - HEADER_LENGTH - 4)
+}
+
+/// Checks whether data is a binary STL file or starts like an ASCII one.
+pub(crate) fn detect_stl(bytes: &[u8]) -> bool {
+    is_binary(bytes) || bytes.starts_with(b"solid")
+}
+
+/**
+Parses the facets of STL data. Data whose size matches the facet count in its header is read as
+binary (even if it starts with `solid`, as some exporters write), and any other data must be
+ASCII.
+
+- Binary files of the wrong size: `MeshError::FormatError("STL facet count does not match file size.")`
+- Files too short for a binary header and not starting with `solid`: `MeshError::FormatError("Invalid STL header.")`
+
+Parameters:
+- `bytes: &[u8]` - The contents of the file.
+
+Returns:
+- `Result<Vec<Facet<T>>, MeshError>` - The facets of the file, else a `MeshError`.
+ */
+fn parse_facets<T: RealField + Copy + FromStr>(bytes: &[u8]) -> Result<Vec<Facet<T>>, MeshError> {
+    if is_binary(bytes) {
+        return Ok(parse_binary(bytes));
+    }
+
+    if !bytes.starts_with(b"solid") {
+        return Err(MeshError::FormatError(if bytes.len() < HEADER_LENGTH + 4 {
+            "Invalid STL header."
+        } else {
+            "STL facet count does not match file size."
+        }));
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => parse_ascii(text),
+        Err(_) => Err(MeshError::FormatError("Unable to process string.")),
+    }
+}
+
+/**
+Reads the facets of the STL file `filename`, as `parse_facets` does.
+
+Parameters:
+- `filename: &str` - A string containing the file path to load.
+
+Returns:
+- `Result<Vec<Facet<T>>, MeshError>` - The facets of the file, else a `MeshError`.
+ */
+fn read_facets<T: RealField + Copy + FromStr>(filename: &str) -> Result<Vec<Facet<T>>, MeshError> {
+    let mut bytes = Vec::new();
+    open_file(filename)?
+        .read_to_end(&mut bytes)
+        .map_err(|e| MeshError::file("Could not read file.", filename, e))?;
+    parse_facets(&bytes).map_err(|e| e.in_file(Path::new(filename)))
+}
+
+/**
+Builds a `TriangleMesh` from STL facets, welding corners with identical coordinates into a single
+vertex. Facets whose corners weld together are kept, so that `cleanup` can remove them and count
+them among its degenerate faces. The stored normal of each facet is kept unless it is zero, in
+which case it is computed from the corners.
+
+Parameters:
+- `facets: Vec<Facet<T>>` - The facets to assemble.
+
+Returns:
+- `Result<TriangleMesh<T>, MeshError>` - The assembled mesh.
+ */
+fn weld<T: RealField + Copy>(facets: Vec<Facet<T>>) -> Result<TriangleMesh<T>, MeshError> {
+    let mut mesh = TriangleMesh {
+        vertices: Vec::new(),
+        faces: Vec::with_capacity(facets.len()),
+        face_normals: Vec::with_capacity(facets.len()),
+        patches: Vec::new(),
+    };
+
+    // Adding zero turns negative zero into positive zero, so the two weld together.
+    let mut index: HashMap<[u64; 3], usize> = HashMap::new();
+    for [normal, corners @ ..] in facets {
+        let face = corners.map(|corner| {
+            let key = corner.map(|x| (to_f64(x) + 0.).to_bits());
+            *index
+                .entry(key)
+                .or_insert_with(|| mesh.add_vertex(Point3::from(corner)))
+        });
+
+        let normal = Vec3::from(normal);
+        let normal = (normal.norm_squared() > T::zero()).then(|| Unit::new_normalize(normal));
+        mesh.add_face(&face, normal)?;
+    }
+    Ok(mesh)
+}
+
+/**
+Writes facets as STL data to `file`.
+
+Parameters:
+- `file: &mut W where W: Write` - The writer to write the facets to.
+- `facets: &[Facet<T>]` - The facets to write.
+- `format: StlFormat` - Whether to write an ASCII or a binary file.
+
+Returns:
+- `Result<usize, Error>` - Returns the number of bytes written if writing is successful otherwise
+  returns an `std::io::Error`.
+ */
+fn write_facets<T: RealField + Copy, W: Write + ?Sized>(
+    file: &mut W,
+    facets: &[Facet<T>],
+    format: StlFormat,
+) -> Result<usize, Error> {
+    let mut bytes: usize = 0;
+
+    match format {
+        StlFormat::Ascii => {
+            let mut line = |file: &mut W, string: String| {
+                bytes += string.len() + 1;
+                writeln!(file, "{}", string)
+            };
+            line(file, "solid rasterflow".to_string())?;
+            for [n, corners @ ..] in facets {
+                line(file, format!("facet normal {} {} {}", n[0], n[1], n[2]))?;
+                line(file, "outer loop".to_string())?;
+                for v in corners {
+                    line(file, format!("vertex {} {} {}", v[0], v[1], v[2]))?;
+                }
+                line(file, "endloop".to_string())?;
+                line(file, "endfacet".to_string())?;
+            }
+            line(file, "endsolid rasterflow".to_string())?;
+        }
+        StlFormat::Binary => {
+            let mut header = [b' '; HEADER_LENGTH];
+            header[..17].copy_from_slice(b"rasterflow binary");
+            file.write_all(&header)?;
+            file.write_all(&(facets.len() as u32).to_le_bytes())?;
+            for facet in facets {
+                for x in facet.iter().flatten() {
+                    file.write_all(&(to_f64(*x) as f32).to_le_bytes())?;
+                }
+                file.write_all(&[0, 0])?;
+            }
+            bytes = HEADER_LENGTH + 4 + FACET_LENGTH * facets.len();
+        }
+    }
+
+    Ok(bytes)
+}
+
+/**
+Writes facets to the STL file `filename`.
+
+Parameters:
+- `filename: &str` - A string containing the filename to save the mesh to.
+- `facets: &[Facet<T>]` - The facets to write.
+- `format: StlFormat` - Whether to write an ASCII or a binary file.
+
+Returns:
+- `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is successful,
+  otherwise `MeshError::FileError`.
+ */
+fn write_facets_to_file<T: RealField + Copy>(
+    filename: &str,
+    facets: &[Facet<T>],
+    format: StlFormat,
+) -> Result<usize, MeshError> {
+    write_file(filename, |file| write_facets(file, facets, format))
+}
+
+/// Collects the triangles of `mesh` as STL facets.
+fn facets_of<T: RealField + Copy>(mesh: &TriangleMesh<T>) -> Vec<Facet<T>> {
+    mesh.faces
+        .iter()
+        .zip(&mesh.face_normals)
+        .map(|(face, normal)| {
+            let [a, b, c] = face.map(|v| {
+                let p = mesh.vertices[v];
+                [p.x, p.y, p.z]
+            });
+            [[normal.x, normal.y, normal.z], a, b, c]
+        })
+        .collect()
+}
+
+impl TriangleMesh {
+    /**
+    Loads a `TriangleMesh` from the ASCII or binary STL file passed in, with coordinates stored as
+    `Float`. Use `load_stl_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh>, MeshError>` - Returns the `Box<TriangleMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_stl(filename: &str) -> Result<Box<TriangleMesh>, MeshError> {
+        TriangleMesh::load_stl_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy + FromStr> TriangleMesh<T> {
+    /**
+    Loads a `TriangleMesh` from the ASCII or binary STL file passed in, with coordinates stored as
+    `T`. The corners of neighbouring facets are welded into shared vertices, and the facet normals
+    stored in the file are kept.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<TriangleMesh<T>>, MeshError>` - Returns the `Box<TriangleMesh<T>>` if the
+      loading succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_stl_with_precision(filename: &str) -> Result<Box<TriangleMesh<T>>, MeshError> {
+        Ok(Box::new(weld(read_facets(filename)?)?))
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Writes a `TriangleMesh` to the STL file passed in. Binary files store coordinates as `f32`.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: StlFormat` - Whether to write an ASCII or a binary file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_stl(&self, filename: &str, format: StlFormat) -> Result<usize, MeshError> {
+        write_facets_to_file(filename, &facets_of(self), format)
+    }
+}
+
+impl PolygonMesh {
+    /**
+    Loads a `PolygonMesh` from the ASCII or binary STL file passed in, with coordinates stored as
+    `Float`. Use `load_stl_with_precision` to load them as another scalar type.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh>, MeshError>` - Returns the `Box<PolygonMesh>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_stl(filename: &str) -> Result<Box<PolygonMesh>, MeshError> {
+        PolygonMesh::load_stl_with_precision(filename)
+    }
+}
+
+impl<T: RealField + Copy + FromStr> PolygonMesh<T> {
+    /**
+    Loads a `PolygonMesh` of triangles from the ASCII or binary STL file passed in, with
+    coordinates stored as `T`. The corners of neighbouring facets are welded into shared vertices,
+    and the facet normals stored in the file are kept.
+
+    Parameters:
+    - `filename: &str` - A string containing the file path to load.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the loading
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub fn load_stl_with_precision(filename: &str) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        Ok(Box::new(PolygonMesh::from_facets(read_facets(filename)?)?))
+    }
+
+    /**
+    Parses a `PolygonMesh` of triangles from ASCII or binary STL data, as `load_stl_with_precision`
+    does.
+
+    Parameters:
+    - `bytes: &[u8]` - The STL data.
+
+    Returns:
+    - `Result<Box<PolygonMesh<T>>, MeshError>` - Returns the `Box<PolygonMesh<T>>` if the parsing
+      succeeded, otherwise a `MeshError` of some form, depending on the error.
+     */
+    pub(crate) fn parse_stl(bytes: &[u8]) -> Result<Box<PolygonMesh<T>>, MeshError> {
+        Ok(Box::new(PolygonMesh::from_facets(parse_facets(bytes)?)?))
+    }
+
+    /// Builds a `PolygonMesh` of triangles from STL facets, welding them as `weld` does.
+    fn from_facets(facets: Vec<Facet<T>>) -> Result<PolygonMesh<T>, MeshError> {
+        let mesh = weld(facets)?;
+        Ok(PolygonMesh {
+            vertices: mesh.vertices,
+            faces: mesh.faces.iter().map(|f| f.to_vec()).collect(),
+            face_normals: mesh.face_normals,
+            ..Default::default()
+        })
+    }
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Writes a `PolygonMesh` to the STL file passed in. Faces with more than three vertices are split
+    into triangles by ear clipping, so concave faces are written as they are. Binary files store
+    coordinates as `f32`.
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `format: StlFormat` - Whether to write an ASCII or a binary file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` if a face references a vertex that does not exist, or a
+      `MeshError::FileError` holding the `std::io::Error` that stopped the writing.
+     */
+    pub fn write_stl(&self, filename: &str, format: StlFormat) -> Result<usize, MeshError> {
+        let (triangles, _) = self.triangulate(Triangulation::EarClipping)?;
+        write_facets_to_file(filename, &facets_of(&triangles), format)
+    }
+
+    /**
+    Writes a `PolygonMesh` as STL data to the writer passed in, as `write_stl` does.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the STL data to.
+    - `format: StlFormat` - Whether to write ASCII or binary data.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, with failures to write given as a
+      `MeshError::FileError` without a path.
+     */
+    pub(crate) fn write_stl_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        format: StlFormat,
+    ) -> Result<usize, MeshError> {
+        let (triangles, _) = self.triangulate(Triangulation::EarClipping)?;
+        write_facets(writer, &facets_of(&triangles), format).map_err(write_error)
+    }
+}
@@ -0,0 +1,426 @@
+/*! Triangulation of the polygons of a `PolygonMesh`. Each polygon is projected onto the plane that
+best fits it, so that concave and slightly non-planar polygons are split into triangles that cover
+them without overlapping, using only the polygon's own vertices. */
+
+use nalgebra::{Point2, RealField, Vector3};
+
+use super::{FaceGroup, MeshError, MutateMesh, PolyMesh, PolygonMesh, TriangleMesh};
+use crate::geometry::predicates::{incircle, orient2d};
+use crate::{Point3, Vec3};
+
+/// How the polygons of a `PolygonMesh` are split into triangles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Triangulation {
+    /// Repeatedly cuts off a corner of the polygon that holds no other vertex. This is the fastest
+    /// method and handles any simple polygon, but may leave long, thin triangles.
+    #[default]
+    EarClipping,
+    /// Chooses the triangles whose edges inside the polygon have the least total length, which
+    /// avoids long, thin triangles. It takes time cubic in the number of vertices of a polygon.
+    MinimumWeight,
+    /// Clips ears, then flips the edges inside the polygon until no triangle's circumcircle holds
+    /// a vertex of its neighbour, maximising the smallest angle.
+    Delaunay,
+    /// Splits the polygon into a fan of triangles around a vertex added at its centroid. This
+    /// keeps triangles from touching across the polygon, but is only correct for polygons that
+    /// are star-shaped about their centroid, such as convex ones.
+    Centroid,
+}
+
+/// A polygon projected onto the plane that best fits it, with its vertices counter-clockwise.
+struct Projected {
+    points: Vec<Point2<f64>>,
+    // The original positions, used to measure edges of non-planar polygons.
+    positions: Vec<Vector3<f64>>,
+    // The area below which a new triangle is taken to have none, relative to the size of the
+    // polygon. Orientation tests are otherwise exact.
+    tolerance: f64,
+}
+
+impl Projected {
+    /**
+    Projects a polygon onto the plane through its vertices given by Newell's method, which is
+    defined for concave and non-planar polygons and faces the way the vertices wind.
+
+    Parameters:
+    - `positions: Vec<Vector3<f64>>` - The vertices of the polygon, in order.
+    - `fallback: Vector3<f64>` - The normal used if the vertices do not define one.
+
+    Returns:
+    - `Projected` - The projected polygon.
+     */
+    fn new(positions: Vec<Vector3<f64>>, fallback: Vector3<f64>) -> Projected {
+        let count = positions.len();
+        // Positions are taken relative to the first, so that polygons far from the origin keep
+        // their precision.
+        let relative = |i: usize| positions[i % count] - positions[0];
+        let mut normal = Vector3::zeros();
+        let mut size: f64 = 0.;
+        for i in 1..count {
+            normal += relative(i).cross(&relative(i + 1));
+            size = size.max(relative(i).norm_squared());
+        }
+        if normal.norm() <= 1e-12 * size {
+            normal = fallback;
+        }
+        let normal = normal.try_normalize(0.).unwrap_or(Vector3::z());
+
+        // The axis the normal leans on least is the furthest from parallel to it.
+        let axis = Vector3::ith(normal.iamin(), 1.);
+        let u = normal.cross(&axis).normalize();
+        let v = normal.cross(&u);
+        let points: Vec<Point2<f64>> = positions
+            .iter()
+            .map(|p| Point2::new(p.dot(&u), p.dot(&v)))
+            .collect();
+
+        Projected {
+            points,
+            positions,
+            tolerance: 1e-12 * size,
+        }
+    }
+
+    /// Twice the signed area of the triangle `a`, `b`, `c`, positive when it is counter-clockwise.
+    /// The sign is exact.
+    fn area(&self, a: usize, b: usize, c: usize) -> f64 {
+        orient2d(&self.points[a], &self.points[b], &self.points[c])
+    }
+
+    /// Whether the point `p` lies inside or on the edges of the counter-clockwise triangle `t`.
+    fn contains(&self, t: [usize; 3], p: usize) -> bool {
+        let point = self.points[p];
+        if t.iter().any(|&corner| self.points[corner] == point) {
+            return false;
+        }
+        (0..3).all(|i| self.area(t[i], t[(i + 1) % 3], p) >= 0.)
+    }
+
+    /**
+    Splits the polygon into triangles by clipping ears. Polygons that are not simple may have no
+    ear, in which case the most convex corner is clipped, so that the polygon is still covered.
+
+    Returns:
+    - `Vec<[usize; 3]>` - The triangles, as indices of the polygon's vertices.
+     */
+    fn ear_clip(&self) -> Vec<[usize; 3]> {
+        let mut remaining: Vec<usize> = (0..self.points.len()).collect();
+        let mut triangles = Vec::with_capacity(remaining.len().saturating_sub(2));
+        // The search for the next ear starts where the last was found, which avoids fans of thin
+        // triangles around the first vertex.
+        let mut start = 0;
+        while remaining.len() > 3 {
+            let count = remaining.len();
+            let corner = |k: usize| {
+                [
+                    remaining[(k + count - 1) % count],
+                    remaining[k],
+                    remaining[(k + 1) % count],
+                ]
+            };
+            let ear = (0..count).map(|k| (start + k) % count).find(|&k| {
+                let t = corner(k);
+                self.area(t[0], t[1], t[2]) > self.tolerance
+                    && !remaining.iter().any(|&p| self.contains(t, p))
+            });
+            let k = ear.unwrap_or_else(|| {
+                (0..count)
+                    .max_by(|&a, &b| {
+                        let (a, b) = (corner(a), corner(b));
+                        self.area(a[0], a[1], a[2])
+                            .total_cmp(&self.area(b[0], b[1], b[2]))
+                    })
+                    .unwrap_or(0)
+            });
+            triangles.push(corner(k));
+            remaining.remove(k);
+            start = k % remaining.len();
+        }
+        if let [a, b, c] = remaining[..] {
+            triangles.push([a, b, c]);
+        }
+        triangles
+    }
+
+    /// Whether the segments `a`-`b` and `c`-`d` share a point.
+    fn intersects(&self, a: usize, b: usize, c: usize, d: usize) -> bool {
+        let side = |x, y, z| {
+            let area = self.area(x, y, z);
+            if area > 0. {
+                1
+            } else if area < 0. {
+                -1
+            } else {
+                0
+            }
+        };
+        let (abc, abd, cda, cdb) = (side(a, b, c), side(a, b, d), side(c, d, a), side(c, d, b));
+        if abc * abd < 0 && cda * cdb < 0 {
+            return true;
+        }
+        // Touching or overlapping segments only share a point if one ends on the other.
+        let on = |x: usize, y: usize, p: usize, side: i32| {
+            let (x, y, p) = (self.points[x], self.points[y], self.points[p]);
+            side == 0 && (p - x).dot(&(p - y)) <= 0.
+        };
+        on(a, b, c, abc) || on(a, b, d, abd) || on(c, d, a, cda) || on(c, d, b, cdb)
+    }
+
+    /// Whether the segment from vertex `a` to vertex `b` lies inside the polygon.
+    fn is_diagonal(&self, a: usize, b: usize) -> bool {
+        let count = self.points.len();
+        // The diagonal must leave each end into the polygon, between its two edges there.
+        let in_cone = |a: usize, b: usize| {
+            let (previous, next) = ((a + count - 1) % count, (a + 1) % count);
+            if self.area(a, next, previous) >= 0. {
+                self.area(a, b, previous) > 0. && self.area(b, a, next) > 0.
+            } else {
+                !(self.area(a, b, next) >= 0. && self.area(b, a, previous) >= 0.)
+            }
+        };
+        in_cone(a, b)
+            && in_cone(b, a)
+            && (0..count).all(|c| {
+                let d = (c + 1) % count;
+                [c, d].iter().any(|&x| x == a || x == b) || !self.intersects(a, b, c, d)
+            })
+    }
+
+    /**
+    Splits the polygon into the triangles whose diagonals have the least total length.
+
+    Returns:
+    - `Option<Vec<[usize; 3]>>` - The triangles, as indices of the polygon's vertices, or `None`
+      if the polygon has no triangulation, as when it crosses itself.
+     */
+    fn minimum_weight(&self) -> Option<Vec<[usize; 3]>> {
+        let count = self.points.len();
+        // The length of each diagonal, with edges of the polygon costing nothing and segments
+        // outside it being impossible.
+        let weight: Vec<Vec<f64>> = (0..count)
+            .map(|i| {
+                (0..count)
+                    .map(|j| {
+                        if j == i + 1 || (i == 0 && j == count - 1) {
+                            0.
+                        } else if j > i && self.is_diagonal(i, j) {
+                            (self.positions[j] - self.positions[i]).norm()
+                        } else {
+                            f64::INFINITY
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // `cost[i][j]` is the least weight of the polygon `i..=j`, cut off by the segment i-j,
+        // and `split[i][j]` the vertex its triangle on that segment has as its apex.
+        let mut cost = vec![vec![0.; count]; count];
+        let mut split = vec![vec![0; count]; count];
+        for length in 2..count {
+            for i in 0..count - length {
+                let j = i + length;
+                cost[i][j] = f64::INFINITY;
+                for k in i + 1..j {
+                    let total = cost[i][k] + cost[k][j] + weight[i][k] + weight[k][j];
+                    if total < cost[i][j] {
+                        cost[i][j] = total;
+                        split[i][j] = k;
+                    }
+                }
+            }
+        }
+        if !cost[0][count - 1].is_finite() {
+            return None;
+        }
+
+        let mut triangles = Vec::with_capacity(count - 2);
+        let mut pending = vec![(0, count - 1)];
+        while let Some((i, j)) = pending.pop() {
+            if j > i + 1 {
+                let k = split[i][j];
+                triangles.push([i, k, j]);
+                pending.push((i, k));
+                pending.push((k, j));
+            }
+        }
+        Some(triangles)
+    }
+
+    /// Whether `d` lies strictly inside the circumcircle of the counter-clockwise triangle `a`,
+    /// `b`, `c`.
+    fn in_circumcircle(&self, [a, b, c]: [usize; 3], d: usize) -> bool {
+        let [a, b, c, d] = [a, b, c, d].map(|p| &self.points[p]);
+        incircle(a, b, c, d) > 0.
+    }
+
+    /**
+    Flips the edges shared by triangles of the polygon until every edge is locally Delaunay.
+    Edges are only flipped when the two triangles form a convex quadrilateral, so the triangles
+    still cover the polygon.
+
+    Parameters:
+    - `triangles: &mut [[usize; 3]]` - The counter-clockwise triangles to improve.
+     */
+    fn make_delaunay(&self, triangles: &mut [[usize; 3]]) {
+        let count = self.points.len();
+        // Flipping never loops in exact arithmetic, but rounding could make it, so it is bounded.
+        for _ in 0..count * count {
+            let mut flipped = false;
+            'search: for t in 0..triangles.len() {
+                for u in t + 1..triangles.len() {
+                    for i in 0..3 {
+                        let (a, b, c) = (
+                            triangles[t][i],
+                            triangles[t][(i + 1) % 3],
+                            triangles[t][(i + 2) % 3],
+                        );
+                        let Some(j) = (0..3)
+                            .find(|&j| triangles[u][j] == b && triangles[u][(j + 1) % 3] == a)
+                        else {
+                            continue;
+                        };
+                        let d = triangles[u][(j + 2) % 3];
+                        if self.in_circumcircle([a, b, c], d)
+                            && self.area(a, d, c) > self.tolerance
+                            && self.area(d, b, c) > self.tolerance
+                        {
+                            triangles[t] = [a, d, c];
+                            triangles[u] = [d, b, c];
+                            flipped = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+            if !flipped {
+                break;
+            }
+        }
+    }
+
+    /**
+    Splits the polygon into triangles using `method`, which must not be `Triangulation::Centroid`.
+
+    Parameters:
+    - `method: Triangulation` - The method to use.
+
+    Returns:
+    - `Vec<[usize; 3]>` - The triangles, as indices of the polygon's vertices.
+     */
+    fn triangulate(&self, method: Triangulation) -> Vec<[usize; 3]> {
+        if self.points.len() == 3 {
+            return vec![[0, 1, 2]];
+        }
+        match method {
+            Triangulation::MinimumWeight => {
+                self.minimum_weight().unwrap_or_else(|| self.ear_clip())
+            }
+            Triangulation::Delaunay => {
+                let mut triangles = self.ear_clip();
+                self.make_delaunay(&mut triangles);
+                triangles
+            }
+            _ => self.ear_clip(),
+        }
+    }
+}
+
+/**
+Splits a polygon given without a normal into triangles by ear clipping, as `triangulate` does with
+`Triangulation::EarClipping`.
+
+Parameters:
+- `vertices: &[Point3<T>]` - The vertices the polygon refers to.
+- `polygon: &[usize]` - The indices of the polygon's vertices, in order.
+
+Returns:
+- `Vec<[usize; 3]>` - The triangles, as indices of `vertices`, winding the same way as the polygon.
+ */
+pub(crate) fn ear_clip<T: RealField + Copy>(
+    vertices: &[Point3<T>],
+    polygon: &[usize],
+) -> Vec<[usize; 3]> {
+    let positions = polygon
+        .iter()
+        .map(|&v| vertices[v].coords.map(|x| x.to_subset_unchecked()))
+        .collect();
+    Projected::new(positions, Vector3::zeros())
+        .triangulate(Triangulation::EarClipping)
+        .into_iter()
+        .map(|triangle| triangle.map(|i| polygon[i]))
+        .collect()
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Splits every face into triangles, using `method`, along with the face each triangle was cut
+    from. The triangles of a face follow one another, wind the same way as the face and take its
+    normal. Each boundary patch covers the triangles of its faces.
+
+    Parameters:
+    - `method: Triangulation` - How to split the faces.
+
+    Returns:
+    - `Result<(TriangleMesh<T>, Vec<usize>), MeshError>` - The triangulated mesh and the index of
+      the face of this mesh each of its triangles was cut from, else a `MeshError` if a face
+      references a vertex that does not exist.
+     */
+    pub fn triangulate(
+        &self,
+        method: Triangulation,
+    ) -> Result<(TriangleMesh<T>, Vec<usize>), MeshError> {
+        let mut mesh = TriangleMesh {
+            vertices: self.vertices.clone(),
+            faces: Vec::with_capacity(self.faces.len() * 2),
+            face_normals: Vec::with_capacity(self.faces.len() * 2),
+            patches: Vec::new(),
+        };
+        let mut sources = Vec::with_capacity(self.faces.len() * 2);
+        let mut first_triangles = Vec::with_capacity(self.faces.len() + 1);
+
+        for (index, (face, normal)) in self.faces.iter().zip(self.face_normals.iter()).enumerate() {
+            first_triangles.push(mesh.faces.len());
+            let mut positions: Vec<Vector3<f64>> = Vec::with_capacity(face.len());
+            for vertex in face {
+                let p = self.get_vertex(*vertex)?;
+                positions.push(p.coords.map(|x| x.to_subset_unchecked()));
+            }
+
+            if method == Triangulation::Centroid {
+                let center = positions.iter().sum::<Vector3<f64>>() / face.len() as f64;
+                let center = mesh.add_vertex(Point3::from(center.map(nalgebra::convert)));
+                for i in 0..face.len() {
+                    mesh.add_face(
+                        &[center, face[i], face[(i + 1) % face.len()]],
+                        Some(*normal),
+                    )?;
+                    sources.push(index);
+                }
+                continue;
+            }
+
+            let fallback: Vec3<f64> = normal.map(|x| x.to_subset_unchecked());
+            for [a, b, c] in Projected::new(positions, fallback).triangulate(method) {
+                mesh.add_face(&[face[a], face[b], face[c]], Some(*normal))?;
+                sources.push(index);
+            }
+        }
+        first_triangles.push(mesh.faces.len());
+
+        mesh.patches = self
+            .patches
+            .iter()
+            .map(|patch| FaceGroup {
+                name: patch.name.clone(),
+                faces: patch
+                    .faces
+                    .iter()
+                    .flat_map(|&f| first_triangles[f]..first_triangles[f + 1])
+                    .collect(),
+            })
+            .collect();
+
+        Ok((mesh, sources))
+    }
+}
@@ -0,0 +1,536 @@
+/*! Validation of the surfaces held by meshes. A surface bounds a volume cleanly when it is closed,
+2-manifold and consistently oriented, so these are checked along with the other defects that
+commonly break meshing: faces with no area, faces given twice and vertices no face uses. Faces
+found to be wound the wrong way can be turned over in place, so that every normal points out of
+the solid the surface bounds. */
+
+use std::collections::HashMap;
+
+use nalgebra::{RealField, Unit, Vector3};
+
+use super::{MeshError, PolyMesh, PolygonMesh, TriangleMesh};
+use crate::geometry::discretizer::delaunay::DPoint;
+use crate::geometry::discretizer::raycast::RayCaster;
+use crate::{Point3, UnitVec3};
+
+/// A report on whether a surface is closed, 2-manifold and consistently oriented, with the
+/// defects found in it. Edges are given as pairs of vertices in increasing order, and every list
+/// is sorted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MeshReport {
+    /// The edges used by only one face.
+    pub boundary_edges: Vec<[usize; 2]>,
+    /// The edges used by more than two faces.
+    pub non_manifold_edges: Vec<[usize; 2]>,
+    /// The vertices whose faces do not form a single fan joined by their edges, such as the tip
+    /// shared by two cones.
+    pub non_manifold_vertices: Vec<usize>,
+    /// The faces wound against the faces around them. Each shell of the surface should wind
+    /// counter-clockwise seen from outside the solid it bounds, so outer shells enclose a positive
+    /// volume and the walls of cavities, nested inside an odd number of shells, a negative one.
+    /// Every face of a shell wound the other way is flipped. In parts too flat to enclose a
+    /// volume, the winding of most faces is taken as correct.
+    pub flipped_faces: Vec<usize>,
+    /// The faces with no area, including those that use a vertex more than once.
+    pub degenerate_faces: Vec<usize>,
+    /// The faces using the same vertices as an earlier face, in any order.
+    pub duplicate_faces: Vec<usize>,
+    /// The vertices that no face uses.
+    pub unreferenced_vertices: Vec<usize>,
+    /// Whether the faces can be wound consistently, which they cannot on a Möbius strip.
+    pub orientable: bool,
+    /// The number of parts of the surface joined by edges.
+    pub component_count: usize,
+    /// The number of loops the boundary edges form.
+    pub boundary_loop_count: usize,
+    /// The Euler characteristic, V - E + F, counting only the vertices that faces use.
+    pub euler_characteristic: i64,
+    /// The number of handles of the surface, summed over its parts, if it is 2-manifold and
+    /// orientable.
+    pub genus: Option<usize>,
+}
+
+/// Disjoint sets of indices, merged by union and identified by their root.
+struct Sets(Vec<usize>);
+
+impl Sets {
+    fn new(count: usize) -> Sets {
+        Sets((0..count).collect())
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.0[i] != i {
+            self.0[i] = self.0[self.0[i]];
+            i = self.0[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.0[a] = b;
+    }
+}
+
+/// Faces wound consistently with one another, as found by `MeshReport::new`.
+struct Patch {
+    faces: Vec<usize>,
+    // The volume the patch encloses about its centre, with every face wound as the first one is,
+    // or `None` if it is too flat to enclose any.
+    volume: Option<f64>,
+}
+
+/**
+Counts the shells, patches enclosing a volume, that each shell lies inside. A shell lies inside
+another when its bounds do and rays cast from each of its vertices show them all to be inside the
+other. Shells that cross one another, as solids modelled as overlapping parts do, are not nested.
+
+Parameters:
+- `vertices: &[Point3<T>]` - The vertices of the mesh.
+- `faces: &[&[usize]]` - The faces of the mesh.
+- `patches: &[Patch]` - The patches of the mesh.
+
+Returns:
+- `Vec<usize>` - The number of shells around each patch, zero for patches that are not shells.
+ */
+fn nesting_depths<T: RealField + Copy>(
+    vertices: &[Point3<T>],
+    faces: &[&[usize]],
+    patches: &[Patch],
+) -> Vec<usize> {
+    let mut depths = vec![0; patches.len()];
+    let shells: Vec<usize> = (0..patches.len())
+        .filter(|&p| patches[p].volume.is_some())
+        .collect();
+    if shells.len() < 2 {
+        return depths;
+    }
+
+    let points: Vec<DPoint> = vertices.iter().map(|v| DPoint::from(to_f64(v))).collect();
+    let shell_vertices: Vec<Vec<usize>> = shells
+        .iter()
+        .map(|&p| {
+            let mut used: Vec<usize> = patches[p]
+                .faces
+                .iter()
+                .flat_map(|&f| faces[f].iter().copied())
+                .collect();
+            used.sort_unstable();
+            used.dedup();
+            used
+        })
+        .collect();
+    let bounds: Vec<[DPoint; 2]> = shell_vertices
+        .iter()
+        .map(|used| {
+            used.iter().fold([points[used[0]]; 2], |[min, max], &v| {
+                [min.inf(&points[v]), max.sup(&points[v])]
+            })
+        })
+        .collect();
+
+    for (s, &shell) in shells.iter().enumerate() {
+        // A fan of triangles covers the points of its polygon an odd number of times and other
+        // points an even number, so rays cross it as often, modulo two, as they cross the polygon.
+        let triangles: Vec<[usize; 3]> = patches[shell]
+            .faces
+            .iter()
+            .flat_map(|&f| {
+                let face = faces[f];
+                (1..face.len().saturating_sub(1)).map(move |i| [face[0], face[i], face[i + 1]])
+            })
+            .collect();
+        let caster = RayCaster::new(&points, &triangles);
+        for (i, &p) in shells.iter().enumerate() {
+            let [min, max] = bounds[i];
+            if i != s
+                && bounds[s][0] <= min
+                && max <= bounds[s][1]
+                && shell_vertices[i]
+                    .iter()
+                    .all(|&v| caster.contains(&points[v]))
+            {
+                depths[p] += 1;
+            }
+        }
+    }
+    depths
+}
+
+/// Converts a point to `f64`, in which areas and volumes are measured.
+pub(super) fn to_f64<T: RealField + Copy>(p: &Point3<T>) -> Vector3<f64> {
+    p.coords.map(|x| x.to_subset_unchecked())
+}
+
+/// Whether a face has no area, relative to its size. The area is half the length of the normal
+/// given by Newell's method.
+pub(super) fn has_no_area<T: RealField + Copy>(vertices: &[Point3<T>], face: &[usize]) -> bool {
+    let origin = to_f64(&vertices[face[0]]);
+    let corners: Vec<Vector3<f64>> = face
+        .iter()
+        .map(|&v| to_f64(&vertices[v]) - origin)
+        .collect();
+    let mut normal = Vector3::zeros();
+    let mut size: f64 = 0.;
+    for (i, corner) in corners.iter().enumerate() {
+        normal += corner.cross(&corners[(i + 1) % corners.len()]);
+        size = size.max(corner.norm_squared());
+    }
+    normal.norm() <= 1e-12 * size
+}
+
+/**
+Recomputes the normal of every face by Newell's method, which, unlike the normal of the first
+corner, follows the winding of concave and non-planar faces. Faces with no area keep their normal.
+
+Parameters:
+- `vertices: &[Point3<T>]` - The vertices of the mesh.
+- `faces: impl Iterator<Item = &[usize]>` - The faces of the mesh.
+- `normals: &mut [UnitVec3<T>]` - The normal of each face, to be replaced.
+ */
+fn recompute_normals<'a, T: RealField + Copy>(
+    vertices: &[Point3<T>],
+    faces: impl Iterator<Item = &'a [usize]>,
+    normals: &mut [UnitVec3<T>],
+) {
+    for (face, normal) in faces.zip(normals) {
+        let origin = to_f64(&vertices[face[0]]);
+        let corners: Vec<Vector3<f64>> = face
+            .iter()
+            .map(|&v| to_f64(&vertices[v]) - origin)
+            .collect();
+        let newell: Vector3<f64> = (0..corners.len())
+            .map(|i| corners[i].cross(&corners[(i + 1) % corners.len()]))
+            .sum();
+        if let Some(newell) = newell.try_normalize(0.) {
+            *normal = Unit::new_normalize(newell.map(nalgebra::convert));
+        }
+    }
+}
+
+impl MeshReport {
+    /**
+    Validates the surface of a mesh.
+    This method may return `MeshError` if:
+    - Vertex referenced in face out of range: `MeshError::IndexingError("Vertex not contained in
+      mesh.")`
+
+    Parameters:
+    - `mesh: &M where M: PolyMesh` - The mesh to validate.
+
+    Returns:
+    - `Result<MeshReport, MeshError>` - The report, else a `MeshError`.
+     */
+    pub(crate) fn new<M: PolyMesh>(mesh: &M) -> Result<MeshReport, MeshError> {
+        let vertices = mesh.get_vertices();
+        let faces: Vec<&[usize]> = mesh.get_faces().iter().map(|f| &f[..]).collect();
+        if faces
+            .iter()
+            .flat_map(|f| f.iter())
+            .any(|&v| v >= vertices.len())
+        {
+            return Err(MeshError::IndexingError("Vertex not contained in mesh."));
+        }
+        let mut report = MeshReport::default();
+
+        // The faces using each edge, and whether they run along it from its lower vertex.
+        let mut edges: HashMap<[usize; 2], Vec<(usize, bool)>> = HashMap::new();
+        let mut referenced = vec![false; vertices.len()];
+        let mut duplicates: HashMap<Vec<usize>, usize> = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                referenced[a] = true;
+                let b = face[(i + 1) % face.len()];
+                if a != b {
+                    edges
+                        .entry([a.min(b), a.max(b)])
+                        .or_default()
+                        .push((f, a < b));
+                }
+            }
+
+            let mut key = face.to_vec();
+            key.sort_unstable();
+            let repeated = key.windows(2).any(|w| w[0] == w[1]);
+            if duplicates.insert(key, f).is_some() {
+                report.duplicate_faces.push(f);
+            }
+            if repeated || has_no_area(vertices, face) {
+                report.degenerate_faces.push(f);
+            }
+        }
+        report.unreferenced_vertices = (0..vertices.len()).filter(|&v| !referenced[v]).collect();
+
+        // Faces are joined into parts by every edge they share, but only wound against one
+        // another, and joined into fans about their vertices, across edges used twice.
+        let mut parts = Sets::new(faces.len());
+        let mut neighbours: Vec<Vec<(usize, bool)>> = vec![Vec::new(); faces.len()];
+        let mut fan_links: HashMap<usize, Vec<[usize; 2]>> = HashMap::new();
+        let mut loops = Sets::new(vertices.len());
+        for (&edge, uses) in &edges {
+            for pair in uses.windows(2) {
+                parts.union(pair[0].0, pair[1].0);
+            }
+            match uses[..] {
+                [_] => {
+                    report.boundary_edges.push(edge);
+                    loops.union(edge[0], edge[1]);
+                }
+                [(f, f_forward), (g, g_forward)] => {
+                    // Faces running the same way along their shared edge wind against each other.
+                    let flipped = f_forward == g_forward;
+                    neighbours[f].push((g, flipped));
+                    neighbours[g].push((f, flipped));
+                    for vertex in edge {
+                        fan_links.entry(vertex).or_default().push([f, g]);
+                    }
+                }
+                _ => report.non_manifold_edges.push(edge),
+            }
+        }
+
+        // Each vertex must have its faces joined into a single fan.
+        let mut vertex_faces: Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+        for (f, face) in faces.iter().enumerate() {
+            for &v in face.iter() {
+                if vertex_faces[v].last() != Some(&f) {
+                    vertex_faces[v].push(f);
+                }
+            }
+        }
+        for (v, around) in vertex_faces.iter().enumerate() {
+            if around.len() < 2 {
+                continue;
+            }
+            let mut fans = Sets::new(around.len());
+            let local = |f: usize| around.iter().position(|&a| a == f).unwrap_or(0);
+            for &[f, g] in fan_links.get(&v).into_iter().flatten() {
+                fans.union(local(f), local(g));
+            }
+            let root = fans.find(0);
+            if (1..around.len()).any(|i| fans.find(i) != root) {
+                report.non_manifold_vertices.push(v);
+            }
+        }
+
+        // Windings are spread from one face of each orientable patch to the rest, marking the
+        // faces wound against the first.
+        report.orientable = true;
+        let mut against: Vec<Option<bool>> = vec![None; faces.len()];
+        let mut patches = Vec::new();
+        for seed in 0..faces.len() {
+            if against[seed].is_some() {
+                continue;
+            }
+            against[seed] = Some(false);
+            let mut patch = vec![seed];
+            let mut stack = vec![seed];
+            while let Some(f) = stack.pop() {
+                let own = against[f] == Some(true);
+                for &(g, flipped) in &neighbours[f] {
+                    match against[g] {
+                        None => {
+                            against[g] = Some(own != flipped);
+                            patch.push(g);
+                            stack.push(g);
+                        }
+                        Some(theirs) if theirs != (own != flipped) => report.orientable = false,
+                        Some(_) => {}
+                    }
+                }
+            }
+
+            // The volume a patch encloses about its centre is, for closed patches, the volume they
+            // bound.
+            let corners = patch.iter().flat_map(|&f| faces[f].iter());
+            let centre = corners
+                .clone()
+                .map(|&v| to_f64(&vertices[v]))
+                .sum::<Vector3<f64>>()
+                / corners.count() as f64;
+            let (mut volume, mut scale) = (0., 0.);
+            for &f in &patch {
+                let corners: Vec<Vector3<f64>> = faces[f]
+                    .iter()
+                    .map(|&v| to_f64(&vertices[v]) - centre)
+                    .collect();
+                let face_volume: f64 = (1..corners.len().saturating_sub(1))
+                    .map(|i| corners[0].dot(&corners[i].cross(&corners[i + 1])))
+                    .sum();
+                volume += if against[f] == Some(true) {
+                    -face_volume
+                } else {
+                    face_volume
+                };
+                scale += face_volume.abs();
+            }
+            patches.push(Patch {
+                faces: patch,
+                volume: Some(volume).filter(|v| v.abs() > 1e-9 * scale),
+            });
+        }
+
+        // Shells wind outward from the solid they bound, so those nested inside an odd number of
+        // others, such as the walls of cavities, enclose a negative volume. Patches too flat to
+        // enclose any volume agree with most of their faces instead.
+        let depths = nesting_depths(vertices, &faces, &patches);
+        for (patch, depth) in patches.into_iter().zip(depths) {
+            let correct = match patch.volume {
+                Some(volume) => (volume < 0.) != (depth % 2 == 1),
+                None => {
+                    let flipped = patch
+                        .faces
+                        .iter()
+                        .filter(|&&f| against[f] == Some(true))
+                        .count();
+                    2 * flipped > patch.faces.len()
+                }
+            };
+            report.flipped_faces.extend(
+                patch
+                    .faces
+                    .into_iter()
+                    .filter(|&f| against[f] != Some(correct)),
+            );
+        }
+
+        let roots: Vec<usize> = (0..faces.len()).map(|f| parts.find(f)).collect();
+        report.component_count = (0..faces.len()).filter(|&f| roots[f] == f).count();
+        let mut boundary_vertices: Vec<usize> =
+            report.boundary_edges.iter().flatten().copied().collect();
+        boundary_vertices.sort_unstable();
+        boundary_vertices.dedup();
+        report.boundary_loop_count = boundary_vertices
+            .iter()
+            .filter(|&&v| loops.find(v) == v)
+            .count();
+        report.euler_characteristic = referenced.iter().filter(|&&r| r).count() as i64
+            - edges.len() as i64
+            + faces.len() as i64;
+
+        // Each part contributes 2 - 2g - b to the Euler characteristic, with g handles and b
+        // boundary loops.
+        if report.orientable && report.is_manifold() {
+            let twice_genus = 2 * report.component_count as i64
+                - report.boundary_loop_count as i64
+                - report.euler_characteristic;
+            if twice_genus >= 0 && twice_genus % 2 == 0 {
+                report.genus = Some(twice_genus as usize / 2);
+            }
+        }
+
+        report.boundary_edges.sort_unstable();
+        report.non_manifold_edges.sort_unstable();
+        report.flipped_faces.sort_unstable();
+        Ok(report)
+    }
+
+    /// Whether every edge is used by at least two faces.
+    pub fn is_closed(&self) -> bool {
+        self.boundary_edges.is_empty()
+    }
+
+    /// Whether every edge is used by at most two faces and the faces about each vertex form a
+    /// single fan.
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty() && self.non_manifold_vertices.is_empty()
+    }
+
+    /// Whether every face is wound the same way as its neighbours, and closed parts wind
+    /// counter-clockwise seen from outside the solid they bound.
+    pub fn is_oriented(&self) -> bool {
+        self.orientable && self.flipped_faces.is_empty()
+    }
+
+    /// Whether the surface is closed, 2-manifold and consistently oriented, so that it bounds a
+    /// volume. Degenerate and duplicate faces and unreferenced vertices are reported but allowed.
+    pub fn is_valid(&self) -> bool {
+        self.is_closed() && self.is_manifold() && self.is_oriented()
+    }
+
+    /**
+    Checks that the surface bounds a volume, as `is_valid` does, giving the first problem found.
+    This method may return `MeshError` if:
+    - Boundary edges: `MeshError::InvalidSurface("Surface is not closed.")`
+    - Non-manifold edges or vertices: `MeshError::InvalidSurface("Surface is not 2-manifold.")`
+    - Flipped faces: `MeshError::InvalidSurface("Surface is not consistently oriented.")`
+
+    Returns:
+    - `Result<(), MeshError>` - Nothing if the surface is valid, else a `MeshError`.
+     */
+    pub fn check(&self) -> Result<(), MeshError> {
+        if !self.is_closed() {
+            Err(MeshError::InvalidSurface("Surface is not closed."))
+        } else if !self.is_manifold() {
+            Err(MeshError::InvalidSurface("Surface is not 2-manifold."))
+        } else if !self.is_oriented() {
+            Err(MeshError::InvalidSurface(
+                "Surface is not consistently oriented.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Winds every face the same way as the faces around it, turning each shell of the surface so that
+    its normals point out of the solid it bounds: away from it on outer shells and into the cavity
+    on the walls of cavities. Faces are turned over by reversing their vertices after the first,
+    along with their texture coordinate and vertex normal indices, and the normal of every face is
+    then recomputed. Surfaces that cannot be wound consistently, such as a Möbius strip, are left
+    with the faces found by `validate` still flipped.
+
+    Returns:
+    - `Result<Vec<usize>, MeshError>` - The faces that were turned over, else
+      `MeshError::IndexingError` if a face references a vertex that does not exist.
+     */
+    pub fn repair_orientation(&mut self) -> Result<Vec<usize>, MeshError> {
+        let flipped = MeshReport::new(self)?.flipped_faces;
+        for &f in &flipped {
+            self.faces[f][1..].reverse();
+            for indices in [
+                &mut self.face_texture_coords[f],
+                &mut self.face_vertex_normals[f],
+            ]
+            .into_iter()
+            .flatten()
+            {
+                indices[1..].reverse();
+            }
+            self.face_normals[f] = -self.face_normals[f];
+        }
+        recompute_normals(
+            &self.vertices,
+            self.faces.iter().map(|f| &f[..]),
+            &mut self.face_normals,
+        );
+        Ok(flipped)
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Winds every face the same way as the faces around it, turning each shell of the surface so that
+    its normals point out of the solid it bounds: away from it on outer shells and into the cavity
+    on the walls of cavities. The normal of every face is then recomputed.
+
+    Returns:
+    - `Result<Vec<usize>, MeshError>` - The faces that were turned over, else
+      `MeshError::IndexingError` if a face references a vertex that does not exist.
+     */
+    pub fn repair_orientation(&mut self) -> Result<Vec<usize>, MeshError> {
+        let flipped = MeshReport::new(self)?.flipped_faces;
+        for &f in &flipped {
+            self.faces[f].swap(1, 2);
+            self.face_normals[f] = -self.face_normals[f];
+        }
+        recompute_normals(
+            &self.vertices,
+            self.faces.iter().map(|f| &f[..]),
+            &mut self.face_normals,
+        );
+        Ok(flipped)
+    }
+}
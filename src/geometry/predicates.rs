@@ -0,0 +1,284 @@
+/*! Robust geometric predicates, after Shewchuk's "Adaptive Precision Floating-Point Arithmetic and
+Fast Robust Geometric Predicates". Each predicate first evaluates its determinant in plain `f64`
+arithmetic together with a bound on the rounding error. Only when the bound cannot guarantee the
+sign is the determinant re-evaluated exactly, using floating-point expansions (sums of
+non-overlapping `f64` components), so the sign returned is always exact while the common case stays
+fast. Points of the crate's `Float` type convert to `f64` exactly, so they can be tested with
+`cast::<f64>()`. */
+
+use nalgebra::{Point2, Point3};
+
+/// Half the distance between 1 and the next `f64`, which bounds the relative error of a single
+/// rounded operation.
+const EPSILON: f64 = f64::EPSILON / 2.;
+
+/// The relative error bounds of the fast evaluation of each predicate.
+const ORIENT2D_BOUND: f64 = (3. + 16. * EPSILON) * EPSILON;
+const ORIENT3D_BOUND: f64 = (7. + 56. * EPSILON) * EPSILON;
+const INCIRCLE_BOUND: f64 = (10. + 96. * EPSILON) * EPSILON;
+const INSPHERE_BOUND: f64 = (16. + 224. * EPSILON) * EPSILON;
+
+/// Returns `a + b` along with the rounding error of the sum.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let virtual_b = x - a;
+    let virtual_a = x - virtual_b;
+    (x, (a - virtual_a) + (b - virtual_b))
+}
+
+/// Returns `a + b` along with the rounding error of the sum, given `|a| >= |b|`.
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+/// Returns `a * b` along with the rounding error of the product.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// An exact sum of non-overlapping `f64` components, ordered by increasing magnitude, with zero
+/// components removed.
+#[derive(Clone, Debug)]
+struct Expansion(Vec<f64>);
+
+impl Expansion {
+    /// Returns the exact difference `a - b`.
+    fn difference(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_sum(a, -b);
+        Expansion([y, x].into_iter().filter(|&c| c != 0.).collect())
+    }
+
+    /// Returns the exact sum of two expansions, merging their components by magnitude.
+    fn add(&self, other: &Expansion) -> Expansion {
+        let mut merged = Vec::with_capacity(self.0.len() + other.0.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.0.len() || j < other.0.len() {
+            if j == other.0.len() || (i < self.0.len() && self.0[i].abs() < other.0[j].abs()) {
+                merged.push(self.0[i]);
+                i += 1;
+            } else {
+                merged.push(other.0[j]);
+                j += 1;
+            }
+        }
+        if merged.len() < 2 {
+            return Expansion(merged);
+        }
+
+        let mut components = Vec::with_capacity(merged.len());
+        let (mut q, h) = fast_two_sum(merged[1], merged[0]);
+        components.push(h);
+        for &g in &merged[2..] {
+            let (sum, h) = two_sum(q, g);
+            components.push(h);
+            q = sum;
+        }
+        components.push(q);
+        components.retain(|&c| c != 0.);
+        Expansion(components)
+    }
+
+    /// Returns the exact negation of the expansion.
+    fn neg(&self) -> Expansion {
+        Expansion(self.0.iter().map(|c| -c).collect())
+    }
+
+    /// Returns the exact difference of two expansions.
+    fn sub(&self, other: &Expansion) -> Expansion {
+        self.add(&other.neg())
+    }
+
+    /// Returns the exact product of the expansion and `b`.
+    fn scale(&self, b: f64) -> Expansion {
+        let mut components = Vec::with_capacity(2 * self.0.len());
+        let mut terms = self.0.iter();
+        let mut q = match terms.next() {
+            Some(&e) => {
+                let (product, error) = two_product(e, b);
+                components.push(error);
+                product
+            }
+            None => return Expansion(Vec::new()),
+        };
+        for &e in terms {
+            let (high, low) = two_product(e, b);
+            let (sum, error) = two_sum(q, low);
+            components.push(error);
+            let (next, error) = fast_two_sum(high, sum);
+            components.push(error);
+            q = next;
+        }
+        components.push(q);
+        components.retain(|&c| c != 0.);
+        Expansion(components)
+    }
+
+    /// Returns the exact product of two expansions.
+    fn mul(&self, other: &Expansion) -> Expansion {
+        other.0.iter().fold(Expansion(Vec::new()), |product, &b| {
+            product.add(&self.scale(b))
+        })
+    }
+
+    /// Returns an approximation of the value with the exact sign, as the largest component
+    /// dominates the others.
+    fn estimate(&self) -> f64 {
+        self.0.iter().sum()
+    }
+}
+
+/**
+Computes the orientation of the planar points `a`, `b` and `c`.
+
+Returns:
+- `f64` - Positive if `a`, `b` and `c` are in counter-clockwise order, negative if they are in
+  clockwise order and zero if they are collinear. The sign is exact.
+ */
+pub fn orient2d(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>) -> f64 {
+    let left = (a.x - c.x) * (b.y - c.y);
+    let right = (a.y - c.y) * (b.x - c.x);
+    let det = left - right;
+    if det.abs() >= ORIENT2D_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    let [acx, acy, bcx, bcy] =
+        [(a.x, c.x), (a.y, c.y), (b.x, c.x), (b.y, c.y)].map(|(p, q)| Expansion::difference(p, q));
+    acx.mul(&bcy).sub(&acy.mul(&bcx)).estimate()
+}
+
+/**
+Computes the orientation of `d` relative to the plane through `a`, `b` and `c`.
+
+Returns:
+- `f64` - Positive if `d` lies on the side `(b - a) x (c - a)` points towards, negative if it lies
+  on the other side and zero if the four points are coplanar. The sign is exact.
+ */
+pub fn orient3d(a: &Point3<f64>, b: &Point3<f64>, c: &Point3<f64>, d: &Point3<f64>) -> f64 {
+    let [ad, bd, cd] = [a - d, b - d, c - d];
+    let (bc, ca, ab) = (
+        [bd.x * cd.y, cd.x * bd.y],
+        [cd.x * ad.y, ad.x * cd.y],
+        [ad.x * bd.y, bd.x * ad.y],
+    );
+    // The determinant of the rows `a - d`, `b - d` and `c - d`, which is negated below.
+    let det = ad.z * (bc[0] - bc[1]) + bd.z * (ca[0] - ca[1]) + cd.z * (ab[0] - ab[1]);
+    let permanent = (bc[0].abs() + bc[1].abs()) * ad.z.abs()
+        + (ca[0].abs() + ca[1].abs()) * bd.z.abs()
+        + (ab[0].abs() + ab[1].abs()) * cd.z.abs();
+    if det.abs() >= ORIENT3D_BOUND * permanent {
+        return -det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| [0, 1, 2].map(|k| Expansion::difference(p[k], d[k])));
+    let minor = |p: &[Expansion; 3], q: &[Expansion; 3]| p[0].mul(&q[1]).sub(&q[0].mul(&p[1]));
+    let exact = ad[2]
+        .mul(&minor(&bd, &cd))
+        .add(&bd[2].mul(&minor(&cd, &ad)))
+        .add(&cd[2].mul(&minor(&ad, &bd)));
+    -exact.estimate()
+}
+
+/**
+Tests `d` against the circumcircle of the counter-clockwise triangle `(a, b, c)`.
+
+Returns:
+- `f64` - Positive if `d` lies strictly inside the circumcircle, negative if it lies outside and
+  zero if the four points are cocircular. The sign is exact.
+ */
+pub fn incircle(a: &Point2<f64>, b: &Point2<f64>, c: &Point2<f64>, d: &Point2<f64>) -> f64 {
+    let [ad, bd, cd] = [a - d, b - d, c - d];
+    let (bc, ca, ab) = (
+        [bd.x * cd.y, cd.x * bd.y],
+        [cd.x * ad.y, ad.x * cd.y],
+        [ad.x * bd.y, bd.x * ad.y],
+    );
+    let lift = [ad, bd, cd].map(|v| v.norm_squared());
+    let det = lift[0] * (bc[0] - bc[1]) + lift[1] * (ca[0] - ca[1]) + lift[2] * (ab[0] - ab[1]);
+    let permanent = (bc[0].abs() + bc[1].abs()) * lift[0]
+        + (ca[0].abs() + ca[1].abs()) * lift[1]
+        + (ab[0].abs() + ab[1].abs()) * lift[2];
+    if det.abs() >= INCIRCLE_BOUND * permanent {
+        return det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| [0, 1].map(|k| Expansion::difference(p[k], d[k])));
+    let lift = |p: &[Expansion; 2]| p[0].mul(&p[0]).add(&p[1].mul(&p[1]));
+    let minor = |p: &[Expansion; 2], q: &[Expansion; 2]| p[0].mul(&q[1]).sub(&q[0].mul(&p[1]));
+    lift(&ad)
+        .mul(&minor(&bd, &cd))
+        .add(&lift(&bd).mul(&minor(&cd, &ad)))
+        .add(&lift(&cd).mul(&minor(&ad, &bd)))
+        .estimate()
+}
+
+/**
+Tests `e` against the circumsphere of the tetrahedron `(a, b, c, d)`, which must be positively
+oriented in the sense of `orient3d(a, b, c, d) > 0`.
+
+Returns:
+- `f64` - Positive if `e` lies strictly inside the circumsphere, negative if it lies outside and
+  zero if the five points are cospherical. The sign is exact.
+ */
+pub fn insphere(
+    a: &Point3<f64>,
+    b: &Point3<f64>,
+    c: &Point3<f64>,
+    d: &Point3<f64>,
+    e: &Point3<f64>,
+) -> f64 {
+    let [ae, be, ce, de] = [a - e, b - e, c - e, d - e];
+    // The 2x2 minors of the x and y columns, as the pair of products they subtract.
+    let pair = |p: &nalgebra::Vector3<f64>, q: &nalgebra::Vector3<f64>| [p.x * q.y, q.x * p.y];
+    let (ab, bc, cd, da, ac, bd) = (
+        pair(&ae, &be),
+        pair(&be, &ce),
+        pair(&ce, &de),
+        pair(&de, &ae),
+        pair(&ae, &ce),
+        pair(&be, &de),
+    );
+    let minor = |[p, q]: [f64; 2]| p - q;
+    let abc = ae.z * minor(bc) - be.z * minor(ac) + ce.z * minor(ab);
+    let bcd = be.z * minor(cd) - ce.z * minor(bd) + de.z * minor(bc);
+    let cda = ce.z * minor(da) + de.z * minor(ac) + ae.z * minor(cd);
+    let dab = de.z * minor(ab) + ae.z * minor(bd) + be.z * minor(da);
+    let lift = [ae, be, ce, de].map(|v| v.norm_squared());
+    let det = (lift[3] * abc - lift[2] * dab) + (lift[1] * cda - lift[0] * bcd);
+
+    let plus = |[p, q]: [f64; 2]| p.abs() + q.abs();
+    let [az, bz, cz, dz] = [ae.z, be.z, ce.z, de.z].map(f64::abs);
+    let permanent = (plus(cd) * bz + plus(bd) * cz + plus(bc) * dz) * lift[0]
+        + (plus(da) * cz + plus(ac) * dz + plus(cd) * az) * lift[1]
+        + (plus(ab) * dz + plus(bd) * az + plus(da) * bz) * lift[2]
+        + (plus(bc) * az + plus(ac) * bz + plus(ab) * cz) * lift[3];
+    if det.abs() >= INSPHERE_BOUND * permanent {
+        return -det;
+    }
+
+    let [ae, be, ce, de] =
+        [a, b, c, d].map(|p| [0, 1, 2].map(|k| Expansion::difference(p[k], e[k])));
+    let minor = |p: &[Expansion; 3], q: &[Expansion; 3]| p[0].mul(&q[1]).sub(&q[0].mul(&p[1]));
+    let (ab, bc, cd, da, ac, bd) = (
+        minor(&ae, &be),
+        minor(&be, &ce),
+        minor(&ce, &de),
+        minor(&de, &ae),
+        minor(&ae, &ce),
+        minor(&be, &de),
+    );
+    let abc = ae[2].mul(&bc).sub(&be[2].mul(&ac)).add(&ce[2].mul(&ab));
+    let bcd = be[2].mul(&cd).sub(&ce[2].mul(&bd)).add(&de[2].mul(&bc));
+    let cda = ce[2].mul(&da).add(&de[2].mul(&ac)).add(&ae[2].mul(&cd));
+    let dab = de[2].mul(&ab).add(&ae[2].mul(&bd)).add(&be[2].mul(&da));
+    let lift =
+        [&ae, &be, &ce, &de].map(|r| r[0].mul(&r[0]).add(&r[1].mul(&r[1])).add(&r[2].mul(&r[2])));
+    let exact = lift[3]
+        .mul(&abc)
+        .sub(&lift[2].mul(&dab))
+        .add(&lift[1].mul(&cda))
+        .sub(&lift[0].mul(&bcd));
+    -exact.estimate()
+}
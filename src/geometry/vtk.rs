@@ -0,0 +1,900 @@
+/*! Writing of meshes to VTK files, for viewing in ParaView and similar tools. Surfaces can be
+written as legacy `.vtk` files or as XML `.vtp` and `.vtu` files, and tetrahedral meshes as legacy
+or `.vtu` files. Named scalar and vector fields can be attached to the points and cells of the
+mesh, and the attributes of a `PolygonMesh` or `TetrahedralMesh` are always written along with
+them. */
+
+use std::io::{Error, Write};
+use std::mem::size_of;
+
+use nalgebra::RealField;
+
+use crate::geometry::discmesh::TetrahedralMesh;
+use crate::geometry::polymesh::{
+    write_error, write_file, Attribute, AttributeType, MeshError, PolyMesh, PolygonMesh,
+    TriangleMesh,
+};
+use crate::Point3;
+
+/// The VTK cell type of a triangle.
+const VTK_TRIANGLE: f64 = 5.;
+
+/// The VTK cell type of a polygon with any number of vertices.
+const VTK_POLYGON: f64 = 7.;
+
+/// The VTK cell type of a tetrahedron.
+const VTK_TETRA: f64 = 10.;
+
+/// The encoding of a legacy VTK file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegacyVtkFormat {
+    Ascii,
+    Binary,
+}
+
+/// The encoding of the data arrays of an XML VTK file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VtkEncoding {
+    /// Values written as text inside each array.
+    Ascii,
+    /// Raw bytes encoded in base64 inside each array.
+    Base64,
+    /// Raw bytes appended after the XML, which each array points into.
+    Appended,
+}
+
+/// The values of a field, with one value for every point or for every cell of a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValues {
+    Scalars(Vec<f64>),
+    Vectors(Vec<[f64; 3]>),
+}
+
+/// A named field to attach to the points or cells of a mesh, such as a quality metric or the face
+/// normals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub values: FieldValues,
+}
+
+impl Field {
+    /// Creates a field holding a scalar for every point or cell.
+    pub fn scalars(name: &str, values: Vec<f64>) -> Field {
+        Field {
+            name: name.to_string(),
+            values: FieldValues::Scalars(values),
+        }
+    }
+
+    /// Creates a field holding a vector for every point or cell.
+    pub fn vectors(name: &str, values: Vec<[f64; 3]>) -> Field {
+        Field {
+            name: name.to_string(),
+            values: FieldValues::Vectors(values),
+        }
+    }
+}
+
+/// The fields to write along with a mesh.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VtkFields {
+    /// Fields with a value for every vertex of the mesh.
+    pub point_data: Vec<Field>,
+    /// Fields with a value for every face of a surface, or every tetrahedron of a volume mesh.
+    pub cell_data: Vec<Field>,
+}
+
+/// An array of values to write, stored as `value_type` with `components` values per point or
+/// cell.
+struct DataArray {
+    name: String,
+    value_type: AttributeType,
+    components: usize,
+    values: Vec<f64>,
+}
+
+impl DataArray {
+    /// Returns the little-endian bytes of the values.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.values.len() * self.value_type.size());
+        for &value in &self.values {
+            bytes.extend(self.value_type.to_bytes(value, false));
+        }
+        bytes
+    }
+
+    /// Returns the number of points or cells the array holds values for.
+    fn len(&self) -> usize {
+        self.values.len() / self.components
+    }
+}
+
+impl From<&Field> for DataArray {
+    fn from(field: &Field) -> DataArray {
+        let (components, values) = match &field.values {
+            FieldValues::Scalars(values) => (1, values.clone()),
+            FieldValues::Vectors(values) => (3, values.iter().flatten().copied().collect()),
+        };
+        DataArray {
+            name: field.name.clone(),
+            value_type: AttributeType::Float64,
+            components,
+            values,
+        }
+    }
+}
+
+impl From<&Attribute> for DataArray {
+    fn from(attribute: &Attribute) -> DataArray {
+        DataArray {
+            name: attribute.name.clone(),
+            value_type: attribute.value_type,
+            components: 1,
+            values: attribute.values.clone(),
+        }
+    }
+}
+
+/// A mesh laid out the way VTK files store it, with the cells flattened into a single list of
+/// vertices.
+struct VtkMesh {
+    points: DataArray,
+    // The vertices of every cell, one after the other.
+    connectivity: DataArray,
+    // The end of each cell in `connectivity`.
+    offsets: DataArray,
+    types: DataArray,
+    point_data: Vec<DataArray>,
+    cell_data: Vec<DataArray>,
+}
+
+impl VtkMesh {
+    /**
+    Lays out a mesh for writing, checking that every field has a value for each point or cell.
+    This method may return `MeshError` if:
+    - Field length differs from the point count: `MeshError::FormatError("Field length does not
+      match point count.")`
+    - Field length differs from the cell count: `MeshError::FormatError("Field length does not
+      match cell count.")`
+
+    Parameters:
+    - `points: DataArray` - The coordinates of the vertices.
+    - `cells: impl Iterator<Item = &'a [usize]>` - The vertices of each cell.
+    - `volume: bool` - Whether the cells are tetrahedra rather than polygons.
+    - `attributes: [&[Attribute]; 2]` - The vertex and face attributes of the mesh.
+    - `fields: &VtkFields` - The fields to write.
+
+    Returns:
+    - `Result<VtkMesh, MeshError>` - The laid out mesh, else a `MeshError`.
+     */
+    fn new<'a>(
+        points: DataArray,
+        cells: impl Iterator<Item = &'a [usize]>,
+        volume: bool,
+        attributes: [&[Attribute]; 2],
+        fields: &VtkFields,
+    ) -> Result<VtkMesh, MeshError> {
+        let mut connectivity = Vec::new();
+        let mut offsets = Vec::new();
+        let mut types = Vec::new();
+        for cell in cells {
+            connectivity.extend(cell.iter().map(|&v| v as f64));
+            offsets.push(connectivity.len() as f64);
+            types.push(match cell.len() {
+                _ if volume => VTK_TETRA,
+                3 => VTK_TRIANGLE,
+                _ => VTK_POLYGON,
+            });
+        }
+        let index_array = |name: &str, values: Vec<f64>| DataArray {
+            name: name.to_string(),
+            value_type: AttributeType::Int32,
+            components: 1,
+            values,
+        };
+
+        let [vertex_attributes, face_attributes] = attributes;
+        let point_data: Vec<DataArray> = vertex_attributes
+            .iter()
+            .map(DataArray::from)
+            .chain(fields.point_data.iter().map(DataArray::from))
+            .collect();
+        let cell_data: Vec<DataArray> = face_attributes
+            .iter()
+            .map(DataArray::from)
+            .chain(fields.cell_data.iter().map(DataArray::from))
+            .collect();
+        if point_data.iter().any(|array| array.len() != points.len()) {
+            return Err(MeshError::FormatError(
+                "Field length does not match point count.",
+            ));
+        }
+        if cell_data.iter().any(|array| array.len() != types.len()) {
+            return Err(MeshError::FormatError(
+                "Field length does not match cell count.",
+            ));
+        }
+
+        Ok(VtkMesh {
+            points,
+            connectivity: index_array("connectivity", connectivity),
+            offsets: index_array("offsets", offsets),
+            types: DataArray {
+                name: "types".to_string(),
+                value_type: AttributeType::Uint8,
+                components: 1,
+                values: types,
+            },
+            point_data,
+            cell_data,
+        })
+    }
+
+    /// Lays out a surface mesh.
+    fn from_surface<T: RealField + Copy, M: PolyMesh<Scalar = T>>(
+        mesh: &M,
+        attributes: [&[Attribute]; 2],
+        fields: &VtkFields,
+    ) -> Result<VtkMesh, MeshError> {
+        let points = point_array(mesh.get_vertices());
+        let cells = mesh.get_faces().iter().map(|face| &face[..]);
+        VtkMesh::new(points, cells, false, attributes, fields)
+    }
+}
+
+/// Lays out the coordinates of `vertices`, writing them as `Float32` when `T` is no wider than 32
+/// bits and as `Float64` otherwise.
+fn point_array<T: RealField + Copy>(vertices: &[Point3<T>]) -> DataArray {
+    DataArray {
+        name: "Points".to_string(),
+        value_type: if size_of::<T>() <= 4 {
+            AttributeType::Float32
+        } else {
+            AttributeType::Float64
+        },
+        components: 3,
+        values: vertices
+            .iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .map(|x| x.to_subset_unchecked())
+            .collect(),
+    }
+}
+
+/// Replaces whitespace in a name, which legacy files cannot hold.
+fn legacy_name(name: &str) -> String {
+    name.replace(|c: char| c.is_ascii_whitespace(), "_")
+}
+
+/// Returns the legacy name of a value type.
+fn legacy_type(value_type: AttributeType) -> &'static str {
+    match value_type {
+        AttributeType::Int8 => "char",
+        AttributeType::Uint8 => "unsigned_char",
+        AttributeType::Int16 => "short",
+        AttributeType::Uint16 => "unsigned_short",
+        AttributeType::Int32 => "int",
+        AttributeType::Uint32 => "unsigned_int",
+        AttributeType::Float32 => "float",
+        AttributeType::Float64 => "double",
+    }
+}
+
+/// The contents of a legacy file being written.
+struct LegacyWriter {
+    binary: bool,
+    bytes: Vec<u8>,
+}
+
+impl LegacyWriter {
+    /// Writes a line of text, which both encodings use for keywords.
+    fn text(&mut self, text: &str) {
+        self.bytes.extend(text.bytes());
+        self.bytes.push(b'\n');
+    }
+
+    /// Writes the values of one point or cell, either as a line of text or as big-endian bytes.
+    fn row(&mut self, value_type: AttributeType, values: &[f64]) {
+        if self.binary {
+            for &value in values {
+                self.bytes.extend(value_type.to_bytes(value, true));
+            }
+        } else {
+            let line: Vec<String> = values.iter().map(|&v| value_type.to_text(v)).collect();
+            self.text(&line.join(" "));
+        }
+    }
+
+    /// Finishes a list of rows, which binary files follow with a line break.
+    fn end_rows(&mut self) {
+        if self.binary {
+            self.bytes.push(b'\n');
+        }
+    }
+
+    /// Writes the values of `array`, one point or cell at a time.
+    fn values(&mut self, array: &DataArray) {
+        for chunk in array.values.chunks(array.components) {
+            self.row(array.value_type, chunk);
+        }
+        self.end_rows();
+    }
+
+    /// Writes the `POINT_DATA` or `CELL_DATA` section holding `arrays`.
+    fn data(&mut self, keyword: &str, count: usize, arrays: &[DataArray]) {
+        if arrays.is_empty() {
+            return;
+        }
+        self.text(&format!("{} {}", keyword, count));
+        for array in arrays {
+            let name = legacy_name(&array.name);
+            let value_type = legacy_type(array.value_type);
+            if array.components == 1 {
+                self.text(&format!("SCALARS {} {} 1", name, value_type));
+                self.text("LOOKUP_TABLE default");
+            } else {
+                self.text(&format!("VECTORS {} {}", name, value_type));
+            }
+            self.values(array);
+        }
+    }
+}
+
+/**
+Writes a mesh as a legacy VTK file to `output`, as `POLYDATA` or as an `UNSTRUCTURED_GRID`.
+
+Parameters:
+- `output: &mut W where W: Write` - The writer to write the file to.
+- `mesh: &VtkMesh` - The laid out mesh.
+- `polydata: bool` - Whether to write the cells as the polygons of a `POLYDATA` set.
+- `format: LegacyVtkFormat` - The encoding of the file.
+
+Returns:
+- `Result<usize, Error>` - Returns the number of bytes written if writing is successful
+  otherwise returns an `std::io::Error`.
+ */
+fn write_legacy<W: Write + ?Sized>(
+    output: &mut W,
+    mesh: &VtkMesh,
+    polydata: bool,
+    format: LegacyVtkFormat,
+) -> Result<usize, Error> {
+    let mut writer = LegacyWriter {
+        binary: format == LegacyVtkFormat::Binary,
+        bytes: Vec::new(),
+    };
+    let point_count = mesh.points.len();
+    let cell_count = mesh.types.len();
+
+    writer.text("# vtk DataFile Version 3.0");
+    writer.text("rasterflow");
+    writer.text(if writer.binary { "BINARY" } else { "ASCII" });
+    writer.text(if polydata {
+        "DATASET POLYDATA"
+    } else {
+        "DATASET UNSTRUCTURED_GRID"
+    });
+    writer.text(&format!(
+        "POINTS {} {}",
+        point_count,
+        legacy_type(mesh.points.value_type)
+    ));
+    writer.values(&mesh.points);
+
+    // Legacy files precede the vertices of each cell with their count.
+    let mut cells: Vec<Vec<f64>> = Vec::with_capacity(cell_count);
+    let mut start = 0;
+    for &end in &mesh.offsets.values {
+        let end = end as usize;
+        let mut cell = vec![(end - start) as f64];
+        cell.extend(&mesh.connectivity.values[start..end]);
+        cells.push(cell);
+        start = end;
+    }
+    writer.text(&format!(
+        "{} {} {}",
+        if polydata { "POLYGONS" } else { "CELLS" },
+        cell_count,
+        cells.iter().map(Vec::len).sum::<usize>()
+    ));
+    for cell in &cells {
+        writer.row(AttributeType::Int32, cell);
+    }
+    writer.end_rows();
+    if !polydata {
+        writer.text(&format!("CELL_TYPES {}", cell_count));
+        for &cell_type in &mesh.types.values {
+            writer.row(AttributeType::Int32, &[cell_type]);
+        }
+        writer.end_rows();
+    }
+
+    writer.data("CELL_DATA", cell_count, &mesh.cell_data);
+    writer.data("POINT_DATA", point_count, &mesh.point_data);
+
+    output.write_all(&writer.bytes)?;
+    Ok(writer.bytes.len())
+}
+
+/// Encodes `bytes` in base64, padding the end with `=`.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, &b)| group | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escapes the characters of `text` that cannot appear in an XML attribute.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the XML name of a value type.
+fn xml_type(value_type: AttributeType) -> &'static str {
+    match value_type {
+        AttributeType::Int8 => "Int8",
+        AttributeType::Uint8 => "UInt8",
+        AttributeType::Int16 => "Int16",
+        AttributeType::Uint16 => "UInt16",
+        AttributeType::Int32 => "Int32",
+        AttributeType::Uint32 => "UInt32",
+        AttributeType::Float32 => "Float32",
+        AttributeType::Float64 => "Float64",
+    }
+}
+
+/// The contents of an XML file being written, along with the data appended after it.
+struct XmlWriter {
+    encoding: VtkEncoding,
+    xml: String,
+    appended: Vec<u8>,
+}
+
+impl XmlWriter {
+    /// Writes a line of XML.
+    fn line(&mut self, line: &str) {
+        self.xml.push_str(line);
+        self.xml.push('\n');
+    }
+
+    /// Writes `array` as a `DataArray` element. Binary data is preceded by its length in bytes as
+    /// a `UInt64`, which base64 encodes separately from the data.
+    fn array(&mut self, array: &DataArray) {
+        let format = match self.encoding {
+            VtkEncoding::Ascii => "ascii",
+            VtkEncoding::Base64 => "binary",
+            VtkEncoding::Appended => "appended",
+        };
+        let tag = format!(
+            "<DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\" format=\"{}\"",
+            xml_type(array.value_type),
+            escape_xml(&array.name),
+            array.components,
+            format
+        );
+
+        match self.encoding {
+            VtkEncoding::Ascii => {
+                let values: Vec<String> = array
+                    .values
+                    .iter()
+                    .map(|&v| array.value_type.to_text(v))
+                    .collect();
+                self.line(&format!("{}>", tag));
+                self.line(&values.join(" "));
+                self.line("</DataArray>");
+            }
+            VtkEncoding::Base64 => {
+                let bytes = array.to_bytes();
+                let header = (bytes.len() as u64).to_le_bytes();
+                self.line(&format!("{}>", tag));
+                self.line(&(base64(&header) + &base64(&bytes)));
+                self.line("</DataArray>");
+            }
+            VtkEncoding::Appended => {
+                let bytes = array.to_bytes();
+                self.line(&format!("{} offset=\"{}\"/>", tag, self.appended.len()));
+                self.appended.extend((bytes.len() as u64).to_le_bytes());
+                self.appended.extend(bytes);
+            }
+        }
+    }
+
+    /// Writes the `PointData` or `CellData` element holding `arrays`.
+    fn data(&mut self, element: &str, arrays: &[DataArray]) {
+        self.line(&format!("<{}>", element));
+        for array in arrays {
+            self.array(array);
+        }
+        self.line(&format!("</{}>", element));
+    }
+}
+
+/**
+Writes a mesh as an XML VTK file to `output`, as `PolyData` or as an `UnstructuredGrid`.
+
+Parameters:
+- `output: &mut W where W: Write` - The writer to write the file to.
+- `mesh: &VtkMesh` - The laid out mesh.
+- `polydata: bool` - Whether to write the cells as the polygons of a `PolyData` set.
+- `encoding: VtkEncoding` - The encoding of the data arrays.
+
+Returns:
+- `Result<usize, Error>` - Returns the number of bytes written if writing is successful
+  otherwise returns an `std::io::Error`.
+ */
+fn write_xml<W: Write + ?Sized>(
+    output: &mut W,
+    mesh: &VtkMesh,
+    polydata: bool,
+    encoding: VtkEncoding,
+) -> Result<usize, Error> {
+    let mut writer = XmlWriter {
+        encoding,
+        xml: String::new(),
+        appended: Vec::new(),
+    };
+    let kind = if polydata {
+        "PolyData"
+    } else {
+        "UnstructuredGrid"
+    };
+
+    writer.line("<?xml version=\"1.0\"?>");
+    writer.line(&format!(
+        "<VTKFile type=\"{}\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt64\">",
+        kind
+    ));
+    writer.line(&format!("<{}>", kind));
+    let cells = if polydata {
+        format!(
+            "NumberOfVerts=\"0\" NumberOfLines=\"0\" NumberOfStrips=\"0\" NumberOfPolys=\"{}\"",
+            mesh.types.len()
+        )
+    } else {
+        format!("NumberOfCells=\"{}\"", mesh.types.len())
+    };
+    writer.line(&format!(
+        "<Piece NumberOfPoints=\"{}\" {}>",
+        mesh.points.len(),
+        cells
+    ));
+    writer.data("PointData", &mesh.point_data);
+    writer.data("CellData", &mesh.cell_data);
+    writer.line("<Points>");
+    writer.array(&mesh.points);
+    writer.line("</Points>");
+    writer.line(if polydata { "<Polys>" } else { "<Cells>" });
+    writer.array(&mesh.connectivity);
+    writer.array(&mesh.offsets);
+    if !polydata {
+        writer.array(&mesh.types);
+    }
+    writer.line(if polydata { "</Polys>" } else { "</Cells>" });
+    writer.line("</Piece>");
+    writer.line(&format!("</{}>", kind));
+
+    let mut bytes = writer.xml.into_bytes();
+    if encoding == VtkEncoding::Appended {
+        bytes.extend(b"<AppendedData encoding=\"raw\">\n_");
+        bytes.extend(writer.appended);
+        bytes.extend(b"\n</AppendedData>\n");
+    }
+    bytes.extend(b"</VTKFile>\n");
+
+    output.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+impl<T: RealField + Copy> PolygonMesh<T> {
+    /**
+    Writes a `PolygonMesh` to the legacy VTK file passed in, as `POLYDATA`. The vertex and face
+    attributes of the mesh are written as point and cell data, followed by `fields`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `format: LegacyVtkFormat` - The encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtk(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        format: LegacyVtkFormat,
+    ) -> Result<usize, MeshError> {
+        let attributes = [&self.vertex_attributes[..], &self.face_attributes[..]];
+        let mesh = VtkMesh::from_surface(self, attributes, fields)?;
+        write_file(filename, |file| write_legacy(file, &mesh, true, format))
+    }
+
+    /**
+    Writes a `PolygonMesh` to the XML `PolyData` file passed in, which is usually given the
+    `.vtp` extension. The vertex and face attributes of the mesh are written as point and cell
+    data, followed by `fields`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtp(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let attributes = [&self.vertex_attributes[..], &self.face_attributes[..]];
+        let mesh = VtkMesh::from_surface(self, attributes, fields)?;
+        write_file(filename, |file| write_xml(file, &mesh, true, encoding))
+    }
+
+    /**
+    Writes a `PolygonMesh` to the XML `UnstructuredGrid` file passed in, which is usually given
+    the `.vtu` extension. The vertex and face attributes of the mesh are written as point and
+    cell data, followed by `fields`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtu(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let attributes = [&self.vertex_attributes[..], &self.face_attributes[..]];
+        let mesh = VtkMesh::from_surface(self, attributes, fields)?;
+        write_file(filename, |file| write_xml(file, &mesh, false, encoding))
+    }
+
+    /**
+    Writes a `PolygonMesh` as a legacy VTK file to the writer passed in, as `write_vtk` does.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the file to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `format: LegacyVtkFormat` - The encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, with failures to write given as a
+      `MeshError::FileError` without a path.
+     */
+    pub(crate) fn write_vtk_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        fields: &VtkFields,
+        format: LegacyVtkFormat,
+    ) -> Result<usize, MeshError> {
+        let attributes = [&self.vertex_attributes[..], &self.face_attributes[..]];
+        let mesh = VtkMesh::from_surface(self, attributes, fields)?;
+        write_legacy(writer, &mesh, true, format).map_err(write_error)
+    }
+
+    /**
+    Writes a `PolygonMesh` as an XML VTK file to the writer passed in, as `write_vtp` does for
+    `PolyData` and `write_vtu` for an `UnstructuredGrid`.
+
+    Parameters:
+    - `writer: &mut W where W: Write` - The writer to write the file to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `polydata: bool` - Whether to write the faces as the polygons of a `PolyData` set.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if writing is successful,
+      otherwise a `MeshError` of some form, with failures to write given as a
+      `MeshError::FileError` without a path.
+     */
+    pub(crate) fn write_xml_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        fields: &VtkFields,
+        polydata: bool,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let attributes = [&self.vertex_attributes[..], &self.face_attributes[..]];
+        let mesh = VtkMesh::from_surface(self, attributes, fields)?;
+        write_xml(writer, &mesh, polydata, encoding).map_err(write_error)
+    }
+}
+
+impl<T: RealField + Copy> TriangleMesh<T> {
+    /**
+    Writes a `TriangleMesh` to the legacy VTK file passed in, as `POLYDATA`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `format: LegacyVtkFormat` - The encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtk(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        format: LegacyVtkFormat,
+    ) -> Result<usize, MeshError> {
+        let mesh = VtkMesh::from_surface(self, [&[], &[]], fields)?;
+        write_file(filename, |file| write_legacy(file, &mesh, true, format))
+    }
+
+    /**
+    Writes a `TriangleMesh` to the XML `PolyData` file passed in, which is usually given the
+    `.vtp` extension.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtp(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let mesh = VtkMesh::from_surface(self, [&[], &[]], fields)?;
+        write_file(filename, |file| write_xml(file, &mesh, true, encoding))
+    }
+
+    /**
+    Writes a `TriangleMesh` to the XML `UnstructuredGrid` file passed in, which is usually given
+    the `.vtu` extension.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or face count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtu(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let mesh = VtkMesh::from_surface(self, [&[], &[]], fields)?;
+        write_file(filename, |file| write_xml(file, &mesh, false, encoding))
+    }
+}
+
+impl<T: RealField + Copy> TetrahedralMesh<T> {
+    /// Lays out the tetrahedra of the mesh for writing, along with its attributes.
+    fn to_vtk(&self, fields: &VtkFields) -> Result<VtkMesh, MeshError> {
+        let points = point_array(&self.vertices);
+        let cells = self.tetrahedra.iter().map(|tet| &tet[..]);
+        let attributes = [&self.vertex_attributes[..], &self.cell_attributes[..]];
+        VtkMesh::new(points, cells, true, attributes, fields)
+    }
+
+    /**
+    Writes a `TetrahedralMesh` to the legacy VTK file passed in, as an `UNSTRUCTURED_GRID`. The
+    vertex and cell attributes of the mesh are written as point and cell data, followed by
+    `fields`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or tetrahedron count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh, with cell data given for
+      each tetrahedron.
+    - `format: LegacyVtkFormat` - The encoding of the file.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtk(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        format: LegacyVtkFormat,
+    ) -> Result<usize, MeshError> {
+        let mesh = self.to_vtk(fields)?;
+        write_file(filename, |file| write_legacy(file, &mesh, false, format))
+    }
+
+    /**
+    Writes a `TetrahedralMesh` to the XML `UnstructuredGrid` file passed in, which is usually
+    given the `.vtu` extension. The vertex and cell attributes of the mesh are written as point
+    and cell data, followed by `fields`.
+    This method may return `MeshError` if:
+    - Field length differs from the vertex or tetrahedron count: `MeshError::FormatError("Field length
+      does not match point count.")` or `MeshError::FormatError("Field length does not match cell
+      count.")`
+
+    Parameters:
+    - `filename: &str` - A string containing the filename to save the mesh to.
+    - `fields: &VtkFields` - The fields to write along with the mesh, with cell data given for
+      each tetrahedron.
+    - `encoding: VtkEncoding` - The encoding of the data arrays.
+
+    Returns:
+    - `Result<usize, MeshError>` - Returns the number of bytes written if file-writing is
+      successful, otherwise a `MeshError` of some form, with failures to write the file given as
+      a `MeshError::FileError` holding the `std::io::Error` that stopped it.
+     */
+    pub fn write_vtu(
+        &self,
+        filename: &str,
+        fields: &VtkFields,
+        encoding: VtkEncoding,
+    ) -> Result<usize, MeshError> {
+        let mesh = self.to_vtk(fields)?;
+        write_file(filename, |file| write_xml(file, &mesh, false, encoding))
+    }
+}
@@ -5,17 +5,24 @@ pub mod geometry;
 type Float = f32;
 type Int = i32;
 type Uint = usize;
-type Vec3 = Vector3<Float>;
-type UnitVec3 = Unit<Vec3>;
-type Point3 = nalgebra::Point3<Float>;
+type Vec3<T = Float> = Vector3<T>;
+type UnitVec3<T = Float> = Unit<Vec3<T>>;
+type Point3<T = Float> = nalgebra::Point3<T>;
 
 #[cfg(test)]
 mod tests {
     use nalgebra::{vector, Point3};
 
+    use crate::geometry::discmesh::{
+        CellGroup, CellMesh, GmshFormat, PlcFormat, TetrahedralMesh, Tetrahedron,
+    };
+    use crate::geometry::discretizer::*;
+    use crate::geometry::halfedge::HalfEdgeMesh;
+    use crate::geometry::meshio::*;
     use crate::geometry::polymesh::MeshError;
     use crate::geometry::polymesh::*;
-    use crate::Vec3;
+    use crate::geometry::vtk::*;
+    use crate::{Float, UnitVec3, Vec3};
 
     /// Test Valid OBJ files to ensure expected result is retrieved.
     #[test]
@@ -100,71 +107,2359 @@ mod tests {
         assert!(PolygonMesh::load_obj("test-files/trumpet.obj").is_ok());
     }
 
+    /// Test that loading OBJ files in parallel gives the same meshes and errors as loading them
+    /// serially.
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_obj_loader() {
+        for filename in [
+            "test-files/trumpet.obj",
+            "test-files/octahedron.obj",
+            "test-files/square-pyramid.obj",
+            "test-files/materials/duct.obj",
+        ] {
+            let serial = PolygonMesh::load_obj(filename).ok().unwrap();
+            let parallel = PolygonMesh::load_obj_parallel(filename).ok().unwrap();
+            assert_eq!(parallel.get_vertices(), serial.get_vertices());
+            assert_eq!(parallel.get_faces(), serial.get_faces());
+            assert_eq!(parallel.get_normals(), serial.get_normals());
+            assert_eq!(parallel.get_texture_coords(), serial.get_texture_coords());
+            assert_eq!(parallel.get_vertex_normals(), serial.get_vertex_normals());
+            assert_eq!(
+                parallel.get_face_texture_coords(),
+                serial.get_face_texture_coords()
+            );
+            assert_eq!(
+                parallel.get_face_vertex_normals(),
+                serial.get_face_vertex_normals()
+            );
+            assert_eq!(parallel.get_groups(), serial.get_groups());
+            assert_eq!(parallel.get_objects(), serial.get_objects());
+            assert_eq!(
+                parallel.get_smoothing_groups(),
+                serial.get_smoothing_groups()
+            );
+            assert_eq!(parallel.get_materials(), serial.get_materials());
+            assert_eq!(parallel.get_face_materials(), serial.get_face_materials());
+        }
+
+        for filename in [
+            "inconsistent-face",
+            "invalid-float",
+            "invalid-indexing",
+            "invalid-integer",
+            "invalid-prefix",
+            "invalid-relative-index",
+            "invalid-string",
+            "zero-index",
+            "non-existent-file",
+        ] {
+            let filename = format!("test-files/invalid_objs/{}.obj", filename);
+            let error = PolygonMesh::load_obj(&filename).err();
+            assert!(error.is_some());
+            assert!(PolygonMesh::load_obj_parallel(&filename).err() == error);
+        }
+        assert!(PolygonMesh::load_obj_parallel("/").err() == PolygonMesh::load_obj("/").err());
+
+        // Only the first of several bad lines in different chunks is reported.
+        let trumpet = std::fs::read_to_string("test-files/trumpet.obj").unwrap();
+        let lines: Vec<&str> = trumpet.lines().collect();
+        let middle = lines.len() / 2;
+        let broken = [
+            &lines[..middle],
+            &["f 1 2 99999"],
+            &lines[middle..],
+            &["v 1 2 x"],
+        ]
+        .concat()
+        .join("\n");
+        let path = std::env::temp_dir().join("rasterflow-broken-trumpet.obj");
+        std::fs::write(&path, broken).unwrap();
+        let path = path.to_str().unwrap();
+        let error = PolygonMesh::load_obj_parallel(path).err();
+        assert!(error.as_ref().is_some_and(|x| *x.root_cause()
+            == MeshError::IndexingError("Vertex not contained in mesh.")
+            && x.line() == Some(middle + 1)
+            && x.token() == Some("99999")));
+        assert!(error == PolygonMesh::load_obj(path).err());
+    }
+
+    /// Test reading OBJ data from memory rather than from a file.
+    #[test]
+    fn test_read_obj() {
+        let bytes = std::fs::read("test-files/trumpet.obj").unwrap();
+        let mesh = PolygonMesh::read_obj(&bytes[..]).ok().unwrap();
+        let expected = PolygonMesh::load_obj("test-files/trumpet.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertices(), expected.get_vertices());
+        assert_eq!(mesh.get_faces(), expected.get_faces());
+        assert_eq!(mesh.get_groups(), expected.get_groups());
+        assert_eq!(mesh.get_smoothing_groups(), expected.get_smoothing_groups());
+
+        // Material libraries cannot be found without a directory, so only the names are kept.
+        let text = "mtllib duct.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl steel\nf 1 2 3\n";
+        let mesh = PolygonMesh::<f64>::read_obj_with_precision(text.as_bytes())
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_material_libraries(), &["duct.mtl"]);
+        assert_eq!(mesh.get_material("steel").unwrap().name, "steel");
+        assert_eq!(mesh.get_face_materials(), &[Some(0)]);
+
+        assert!(PolygonMesh::read_obj("v 0 0 0\nbogus\n".as_bytes())
+            .err()
+            .is_some_and(|x| matches!(x.root_cause(), MeshError::FormatError(x) if *x == "Invalid file line.")
+                && x.line() == Some(2)
+                && x.path().is_none()));
+    }
+
     /// Test Invalid OBJ files and ensure errors are as expected.
     #[test]
     fn test_invalid_obj_loader() {
+        for (filename, message) in [
+            ("invalid-prefix", "Invalid file line."),
+            ("invalid-float", "Failed to parse float."),
+            ("invalid-integer", "Failed to parse integer."),
+            ("invalid-string", "Unable to process string."),
+        ] {
+            let filename = format!("test-files/invalid_objs/{}.obj", filename);
+            assert!(PolygonMesh::load_obj(&filename).err().is_some_and(
+                |x| matches!(x.root_cause(), MeshError::FormatError(x) if *x == message)
+            ));
+        }
         assert!(
-            PolygonMesh::load_obj("test-files/invalid_objs/invalid-prefix.obj")
+            PolygonMesh::load_obj("test-files/invalid_objs/non-existent-file.obj")
                 .err()
-                .map_or(
-                    false,
-                    |x| matches!(x, MeshError::FormatError(x) if x == "Invalid file line.")
+                .is_some_and(
+                    |x| matches!(x, MeshError::FileError { message, .. } if message == "File not found.")
                 )
         );
         assert!(
-            PolygonMesh::load_obj("test-files/invalid_objs/invalid-float.obj")
+            PolygonMesh::load_obj("test-files/invalid_objs/invalid-indexing.obj")
                 .err()
-                .map_or(
-                    false,
-                    |x| matches!(x, MeshError::FormatError(x) if x == "Failed to parse float.")
+                .is_some_and(
+                    |x| matches!(x.root_cause(), MeshError::IndexingError(x) if *x == "Vertex not contained in mesh.")
                 )
         );
+        assert!(PolygonMesh::load_obj("/root").err().is_some_and(
+            |x| matches!(x, MeshError::FileError { message, .. } if message == "Insufficient permissions.")
+        ));
+        assert!(PolygonMesh::load_obj("/").err().is_some_and(|x| {
+            matches!(x.root_cause(), MeshError::FileError { message, .. } if *message == "Could not read next line.")
+                && x.line() == Some(1)
+        }));
+    }
+
+    /// Test that errors give the file, line, column and token they were found at, and their cause.
+    #[test]
+    fn test_mesh_error() {
+        let error = PolygonMesh::load_obj("test-files/invalid_objs/invalid-float.obj")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.path(),
+            Some(std::path::Path::new(
+                "test-files/invalid_objs/invalid-float.obj"
+            ))
+        );
+        assert_eq!(error.line(), Some(5));
+        assert_eq!(error.column(), Some(4));
+        assert_eq!(error.token(), Some("1-"));
+        assert_eq!(error.message(), "Failed to parse float.");
+        assert_eq!(
+            error.to_string(),
+            "test-files/invalid_objs/invalid-float.obj:5:4: Failed to parse float. Found `1-`."
+        );
+
+        // The `std::io::Error` behind a failure is kept as its source.
+        let error = PolygonMesh::load_obj("test-files/invalid_objs/non-existent-file.obj")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "test-files/invalid_objs/non-existent-file.obj: File not found."
+        );
+        assert!(std::error::Error::source(&error)
+            .and_then(|e| e.downcast_ref::<std::io::Error>())
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound));
+        let error = PolygonMesh::load_obj("/").err().unwrap();
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .write_obj("test-files/missing-directory/write-octahedron.obj")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(error, MeshError::FileError { message, .. } if message == "Could not create file.")
+        );
+        // Every file writer reports the file it failed to write.
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+        let triangles = octahedron.to_triangle_mesh().ok().unwrap();
+        let filename = "test-files/missing-directory/write-octahedron";
+        for result in [
+            octahedron.write_stl(filename, StlFormat::Binary),
+            octahedron.write_ply(filename, PlyFormat::Ascii),
+            octahedron.write_mtl(filename),
+            octahedron.write_vtk(filename, &VtkFields::default(), LegacyVtkFormat::Ascii),
+            octahedron.write_vtp(filename, &VtkFields::default(), VtkEncoding::Ascii),
+            triangles.write_stl(filename, StlFormat::Ascii),
+            triangles.write_msh(filename, GmshFormat::AsciiV4),
+            triangles.write_plc(filename, PlcFormat::Poly),
+            triangles.write_vtu(filename, &VtkFields::default(), VtkEncoding::Base64),
+            MeshRegistry::default().save(&octahedron, &format!("{}.obj", filename)),
+        ] {
+            let error = result.err().unwrap();
+            assert!(matches!(error, MeshError::FileError { .. }));
+            assert!(error
+                .path()
+                .is_some_and(|p| p.starts_with("test-files/missing-directory")));
+        }
+
+        // `?` converts a `std::io::Error` into a `MeshError`.
+        fn read(filename: &str) -> Result<String, MeshError> {
+            Ok(std::fs::read_to_string(filename)?)
+        }
+        assert!(read("test-files/invalid_objs/non-existent-file.obj")
+            .is_err_and(|x| x.message() == "File not found." && x.path().is_none()));
+    }
+
+    /// Test traversing the half-edges of closed, open and non-manifold meshes.
+    #[test]
+    fn test_half_edge_mesh() {
+        // A closed pyramid with a square base: every half-edge is paired within the mesh.
+        let pyramid = PolygonMesh::load_obj("test-files/square-pyramid.obj")
+            .ok()
+            .unwrap();
+        let half_edges = HalfEdgeMesh::new(&*pyramid).ok().unwrap();
+        assert_eq!(half_edges.get_half_edge_count(), 16);
+        assert!(half_edges.is_manifold());
+        assert!(half_edges.boundary_loops().is_empty());
+        for h in 0..half_edges.get_half_edge_count() {
+            assert_eq!(half_edges.twin(half_edges.twin(h)), h);
+            assert_eq!(half_edges.prev(half_edges.next(h)), h);
+            assert_eq!(
+                half_edges.origin(half_edges.twin(h)),
+                half_edges.destination(h)
+            );
+        }
+        assert!(half_edges.face_vertices(0).eq([0, 3, 2, 1]));
+        assert!(half_edges.face_neighbors(0).all(|f| f.is_some()));
+        let mut apex: Vec<usize> = half_edges.vertex_neighbors(4).collect();
+        apex.sort_unstable();
+        assert_eq!(apex, [0, 1, 2, 3]);
+        assert_eq!(half_edges.vertex_faces(0).count(), 3);
+
+        // Without its base, the pyramid has one hole, bounded against the winding of the sides.
+        let mut open = pyramid.to_triangle_mesh().ok().unwrap();
+        open.faces.drain(..2);
+        let half_edges = HalfEdgeMesh::new(&open).ok().unwrap();
+        let loops = half_edges.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        assert!(loops[0].iter().all(|&h| half_edges.is_boundary(h)));
+        let mut hole: Vec<usize> = loops[0].iter().map(|&h| half_edges.origin(h)).collect();
+        hole.sort_unstable();
+        assert_eq!(hole, [0, 1, 2, 3]);
+        assert!(half_edges.is_boundary_vertex(0) && !half_edges.is_boundary_vertex(4));
+        // Turning about a boundary vertex starts on the boundary and reaches every face.
+        assert_eq!(half_edges.outgoing_edges(0).count(), 3);
+        assert_eq!(half_edges.vertex_faces(0).count(), 2);
+
+        // A third triangle on an edge makes it non-manifold.
+        let mut fin = open;
+        fin.vertices.push(Point3::new(0.5, -1., 0.5));
+        fin.faces.push([0, 1, 5]);
+        let half_edges = HalfEdgeMesh::new(&fin).ok().unwrap();
+        assert!(!half_edges.is_manifold());
+        assert_eq!(half_edges.get_non_manifold_edges(), &[[0, 1]]);
+        for h in 0..half_edges.get_half_edge_count() {
+            assert_eq!(half_edges.prev(half_edges.next(h)), h);
+        }
+
+        fin.faces.push([0, 1, 6]);
+        assert!(HalfEdgeMesh::new(&fin).err().is_some_and(
+            |x| matches!(x, MeshError::IndexingError(x) if x == "Vertex not contained in mesh.")
+        ));
+    }
+
+    /// Test validating closed, open, non-manifold and misoriented surfaces.
+    #[test]
+    fn test_validate() {
+        let mut octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let report = octahedron.validate().ok().unwrap();
+        assert!(report.is_valid());
+        assert!(report.check().is_ok());
+        assert_eq!(report.component_count, 1);
+        assert_eq!(report.euler_characteristic, 2);
+        assert_eq!(report.genus, Some(0));
+
+        // A torus of 4 by 3 quadrilaterals has one handle.
+        let mut torus = PolygonMesh::default();
+        for i in 0..4 {
+            for j in 0..3 {
+                let tau = std::f32::consts::TAU;
+                let (u, v) = (tau * i as Float / 4., tau * j as Float / 3.);
+                let r = 2. + v.cos();
+                torus.add_vertex(Point3::new(r * u.cos(), r * u.sin(), v.sin()));
+            }
+        }
+        for i in 0..4 {
+            for j in 0..3 {
+                let corner = |a: usize, b: usize| ((i + a) % 4) * 3 + (j + b) % 3;
+                let face = [corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)];
+                assert!(torus.add_face(&face, None).is_ok());
+            }
+        }
+        let report = torus.validate().ok().unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.euler_characteristic, 0);
+        assert_eq!(report.genus, Some(1));
+
+        // A flipped face, then a whole surface turned inside out.
+        octahedron.faces[0].swap(1, 2);
+        let report = octahedron.validate().ok().unwrap();
+        assert_eq!(report.flipped_faces, [0]);
+        assert!(report.is_closed() && report.is_manifold() && !report.is_oriented());
+        assert!(
+            report.check().err()
+                == Some(MeshError::InvalidSurface(
+                    "Surface is not consistently oriented."
+                ))
+        );
+        for face in &mut octahedron.faces[1..] {
+            face.swap(1, 2);
+        }
+        let report = octahedron.validate().ok().unwrap();
+        assert_eq!(report.flipped_faces, (0..8).collect::<Vec<_>>());
+        for face in &mut octahedron.faces {
+            face.swap(1, 2);
+        }
+
+        // Without a face, the surface has a hole, which the discretizer refuses to fill.
+        let last = octahedron.faces.pop().unwrap();
+        let report = octahedron.validate().ok().unwrap();
+        assert_eq!(report.boundary_edges.len(), 3);
+        assert_eq!(report.boundary_loop_count, 1);
+        assert_eq!(report.genus, Some(0));
+        assert!(report.is_oriented() && !report.is_closed());
+        assert!(
+            TetrahedralDiscretizer::discretize(&octahedron, &Default::default()).err()
+                == Some(MeshError::InvalidSurface("Surface is not closed."))
+        );
+        octahedron.faces.push(last);
+
+        // A repeated face, a face with no area and a vertex no face uses.
+        octahedron.faces.push(octahedron.faces[0]);
+        octahedron.vertices.push(Point3::new(2., 0., 0.));
+        octahedron.vertices.push(Point3::new(3., 0., 0.));
+        octahedron.faces.push([0, 6, 7]);
+        octahedron.vertices.push(Point3::new(5., 5., 5.));
+        let report = octahedron.validate().ok().unwrap();
+        assert_eq!(report.duplicate_faces, [8]);
+        assert_eq!(report.non_manifold_edges.len(), 3);
+        assert_eq!(report.degenerate_faces, [9]);
+        assert_eq!(report.unreferenced_vertices, [8]);
+        assert_eq!(report.genus, None);
+        assert!(report.check().err() == Some(MeshError::InvalidSurface("Surface is not closed.")));
+
+        // Two pyramids meeting only at their apex share a vertex that is not 2-manifold.
+        let mut cones = PolygonMesh::default();
+        for [x, y, z] in [
+            [0., 0., 0.],
+            [1., 0., 1.],
+            [-1., 0., 1.],
+            [0., 1., 1.],
+            [1., 0., -1.],
+            [-1., 0., -1.],
+            [0., 1., -1.],
+        ] {
+            cones.add_vertex(Point3::new(x, y, z));
+        }
+        for face in [[0, 1, 3], [0, 3, 2], [0, 2, 1], [1, 2, 3]] {
+            assert!(cones.add_face(&face, None).is_ok());
+            assert!(cones
+                .add_face(&face.map(|v| if v == 0 { 0 } else { v + 3 }), None)
+                .is_ok());
+        }
+        let report = cones.validate().ok().unwrap();
+        assert!(report.is_closed() && report.non_manifold_edges.is_empty());
+        assert_eq!(report.non_manifold_vertices, [0]);
+        assert!(
+            report.check().err() == Some(MeshError::InvalidSurface("Surface is not 2-manifold."))
+        );
+
+        // A box around a cavity bounds a solid when the wall of the cavity winds inward, into it.
+        let mut hollow = PolygonMesh::default();
+        add_cube(&mut hollow, 0., 4., false);
+        add_cube(&mut hollow, 1.5, 2.5, true);
+        let report = hollow.validate().ok().unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.component_count, 2);
+        assert_eq!(report.genus, Some(0));
+        let mesh = TetrahedralDiscretizer::discretize(
+            &hollow.to_triangle_mesh().ok().unwrap(),
+            &Default::default(),
+        )
+        .ok()
+        .unwrap();
+        assert!((total_volume(&mesh) - 63.).abs() < 1e-3);
+
+        // Wound outward, the wall of the cavity is flipped.
+        let mut hollow = PolygonMesh::default();
+        add_cube(&mut hollow, 0., 4., false);
+        add_cube(&mut hollow, 1.5, 2.5, false);
+        let report = hollow.validate().ok().unwrap();
+        assert_eq!(report.flipped_faces, (6..12).collect::<Vec<_>>());
+    }
+
+    /// Test turning faces over so that each part of a surface winds outward.
+    #[test]
+    fn test_repair_orientation() {
+        let mut octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let faces = octahedron.faces.clone();
+        let outward = |mesh: &TriangleMesh| {
+            mesh.faces
+                .iter()
+                .zip(&mesh.face_normals)
+                .all(|(face, normal)| {
+                    let centroid = face.iter().map(|&v| mesh.vertices[v].coords).sum::<Vec3>();
+                    normal.dot(&centroid) > 0.
+                })
+        };
+
+        // A few flipped faces are turned back, with their normals, and the rest left alone.
+        for f in [0, 3, 5] {
+            octahedron.faces[f].swap(1, 2);
+        }
+        octahedron.face_normals[1] = -octahedron.face_normals[1];
+        assert_eq!(octahedron.repair_orientation().ok().unwrap(), [0, 3, 5]);
+        assert_eq!(octahedron.faces, faces);
+        assert!(outward(&octahedron));
+        assert!(octahedron.validate().ok().unwrap().is_oriented());
+
+        // A surface turned inside out is turned over entirely, and repairing again changes nothing.
+        for face in &mut octahedron.faces {
+            face.swap(1, 2);
+        }
+        assert_eq!(
+            octahedron.repair_orientation().ok().unwrap(),
+            (0..8).collect::<Vec<_>>()
+        );
+        assert_eq!(octahedron.faces, faces);
+        assert!(outward(&octahedron));
+        assert!(octahedron.repair_orientation().ok().unwrap().is_empty());
+
+        // Polygons are turned over with their texture coordinates and vertex normals.
+        let mut pyramid = PolygonMesh::load_obj("test-files/square-pyramid.obj")
+            .ok()
+            .unwrap();
+        let (faces, texture_coords) = (pyramid.faces.clone(), pyramid.face_texture_coords.clone());
+        for f in 0..pyramid.faces.len() {
+            pyramid.faces[f][1..].reverse();
+            if let Some(indices) = &mut pyramid.face_texture_coords[f] {
+                indices[1..].reverse();
+            }
+        }
+        assert_eq!(pyramid.repair_orientation().ok().unwrap(), [0, 1, 2, 3, 4]);
+        assert_eq!(pyramid.faces, faces);
+        assert_eq!(pyramid.face_texture_coords, texture_coords);
+        assert!((pyramid.face_normals[0].z + 1.).abs() < 1e-6);
+        assert!(pyramid.validate().ok().unwrap().is_valid());
+
+        // Nested shells alternate, so the wall of a cavity points into it while a solid floating
+        // in the cavity points out again. A correctly wound mesh is left alone.
+        let mut nested = PolygonMesh::default();
+        add_cube(&mut nested, 0., 6., false);
+        add_cube(&mut nested, 1., 5., true);
+        add_cube(&mut nested, 2., 4., false);
+        let faces = nested.faces.clone();
+        assert!(nested.repair_orientation().ok().unwrap().is_empty());
+        assert_eq!(nested.faces, faces);
+
+        // Exported with every shell wound outward, only the wall of the cavity is turned over.
+        let mut nested = PolygonMesh::default();
+        for (min, max) in [(0., 6.), (1., 5.), (2., 4.)] {
+            add_cube(&mut nested, min, max, false);
+        }
+        assert_eq!(
+            nested.repair_orientation().ok().unwrap(),
+            (6..12).collect::<Vec<_>>()
+        );
+        assert!(nested.validate().ok().unwrap().is_valid());
+        let centre = Vec3::new(3., 3., 3.);
+        for (f, (face, normal)) in nested.faces.iter().zip(&nested.face_normals).enumerate() {
+            let outward = nested.vertices[face[0]].coords - centre;
+            assert_eq!(normal.dot(&outward) > 0., !(6..12).contains(&f));
+        }
+    }
+
+    /// Test welding nearby vertices and removing faces and vertices that add nothing.
+    #[test]
+    fn test_cleanup() {
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+
+        // A soup of separate triangles, as read from an STL file, with each corner moved slightly.
+        let mut soup = PolygonMesh::default();
+        for (f, face) in octahedron.faces.iter().enumerate() {
+            let corners = face.map(|v| {
+                let offset = Vec3::new(1., -1., 1.) * (f as Float * 1e-6);
+                soup.add_vertex(octahedron.vertices[v] + offset)
+            });
+            assert!(soup.add_face(&corners, None).is_ok());
+        }
+        // The first face again, starting from another corner, a sliver thinner than the tolerance
+        // and a vertex no face uses.
+        let first = soup.faces[0].clone();
+        assert!(soup.add_face(&[first[1], first[2], first[0]], None).is_ok());
+        let sliver = [
+            soup.add_vertex(Point3::new(0., 0., 0.)),
+            soup.add_vertex(Point3::new(1e-5, 0., 0.)),
+            soup.add_vertex(Point3::new(0., 1e-5, 0.)),
+        ];
+        assert!(soup.add_face(&sliver, None).is_ok());
+        soup.add_vertex(Point3::new(5., 5., 5.));
+        let mut soup = soup.to_triangle_mesh().ok().unwrap();
+        assert!(soup.set_patch("last", &[8]).is_ok());
+
+        let report = soup.cleanup(1e-4).ok().unwrap();
+        assert_eq!(
+            report,
+            CleanupReport {
+                welded_vertices: 20,
+                unreferenced_vertices: 2,
+                degenerate_faces: 1,
+                duplicate_faces: 1,
+                collapsed_corners: 0,
+                collapsed_polylines: 0,
+            }
+        );
+        assert_eq!(soup.vertices.len(), 6);
+        assert_eq!(soup.faces.len(), 8);
+        assert_eq!(soup.face_normals.len(), 8);
+        assert!(soup.get_patches()[0].faces.is_empty());
+        assert!(soup.validate().ok().unwrap().is_valid());
+        assert_eq!(soup.cleanup(0.).ok().unwrap(), CleanupReport::default());
+
+        // A polygon with two corners welded together loses one, keeping its texture coordinates.
+        let mut pyramid = PolygonMesh::load_obj("test-files/square-pyramid.obj")
+            .ok()
+            .unwrap();
+        let near = pyramid.add_vertex(Point3::new(1., 1e-6, 0.));
+        let between = pyramid.add_vertex(Point3::new(0.5, 0., 0.));
+        pyramid.faces[0].push(near);
+        for indices in [
+            &mut pyramid.face_texture_coords[0],
+            &mut pyramid.face_vertex_normals[0],
+        ]
+        .into_iter()
+        .flatten()
+        {
+            indices.push(indices[3]);
+        }
+        // A side flattened onto the base edge has no area.
+        pyramid.faces[1][2] = between;
+        assert!(pyramid.set_patch("side", &[2]).is_ok());
+        // A polyline along the welded edge collapses, while one through it loses a vertex.
+        assert!(pyramid.add_polyline(&[1, near]).is_ok());
+        assert!(pyramid.add_polyline(&[4, near, 1, 3]).is_ok());
+
+        let report = pyramid.cleanup(1e-4).ok().unwrap();
+        assert_eq!(report.welded_vertices, 1);
+        assert_eq!(report.unreferenced_vertices, 1);
+        assert_eq!(report.degenerate_faces, 1);
+        assert_eq!(report.collapsed_corners, 1);
+        assert_eq!(report.collapsed_polylines, 1);
+        assert_eq!(pyramid.vertices.len(), 5);
+        assert_eq!(pyramid.get_polylines(), &[vec![4, 1, 3]]);
+        assert_eq!(
+            pyramid.faces,
+            [
+                vec![0, 3, 2, 1],
+                vec![1, 2, 4],
+                vec![2, 3, 4],
+                vec![3, 0, 4]
+            ]
+        );
+        assert_eq!(pyramid.face_texture_coords[0], Some(vec![0, 3, 2, 1]));
+        assert_eq!(pyramid.face_normals.len(), 4);
+        assert_eq!(pyramid.smoothing_groups.len(), 4);
+        assert_eq!(pyramid.get_group("sides").unwrap().faces, [1, 2, 3]);
+        assert_eq!(pyramid.get_patch("side").unwrap().faces, [1]);
+    }
+
+    /// Test splitting concave and non-planar polygons into triangles with every method.
+    #[test]
+    fn test_triangulation() {
+        // A dart, whose centroid lies outside it, next to a non-planar quadrilateral.
+        let mut mesh = PolygonMesh::default();
+        for [x, y, z] in [
+            [0., 0., 0.],
+            [4., 2., 0.],
+            [0., 4., 0.],
+            [3., 2., 0.],
+            [5., 0., 0.],
+            [6., 0., 0.3],
+            [6., 1., 0.],
+        ] {
+            mesh.add_vertex(Point3::new(x, y, z));
+        }
+        let up = Some(UnitVec3::new_normalize(Vec3::z()));
+        assert!(mesh.add_face(&[0, 1, 2, 3], up).is_ok());
+        assert!(mesh.add_face(&[4, 5, 6, 1], up).is_ok());
+        assert!(mesh.set_patch("dart", &[0]).is_ok());
+
+        let area = |mesh: &TriangleMesh, face: &[usize; 3]| {
+            let [a, b, c] = face.map(|v| mesh.get_vertices()[v]);
+            (b - a).cross(&(c - a)).dot(&Vec3::z()) / 2.
+        };
+        for method in [
+            Triangulation::EarClipping,
+            Triangulation::MinimumWeight,
+            Triangulation::Delaunay,
+        ] {
+            let (triangles, sources) = mesh.triangulate(method).ok().unwrap();
+            assert_eq!(triangles.get_vertex_count(), 7);
+            assert_eq!(sources, [0, 0, 1, 1]);
+            assert_eq!(triangles.get_patch("dart").unwrap().faces, [0, 1]);
+            // The dart's triangles wind the same way and cover exactly its area of 2.
+            let areas: Vec<Float> = triangles.get_faces()[..2]
+                .iter()
+                .map(|f| area(&triangles, f))
+                .collect();
+            assert!(areas.iter().all(|&a| a > 0.));
+            assert!((areas.iter().sum::<Float>() - 2.).abs() < 1e-5);
+        }
+
+        // A fan around the centroid adds a vertex for each face, and folds over the dart.
+        let (fan, sources) = mesh.triangulate(Triangulation::Centroid).ok().unwrap();
+        assert_eq!(fan.get_vertex_count(), 9);
+        assert_eq!(sources, [0, 0, 0, 0, 1, 1, 1, 1]);
+        assert!(fan.get_faces()[..4].iter().any(|f| area(&fan, f) < 0.));
+
+        // On an elongated hexagon, the Delaunay triangles have larger smallest angles than the
+        // clipped ears, and the minimum weight diagonals are shorter.
+        let mut hexagon = PolygonMesh::default();
+        for [x, y] in [[0., 0.], [4., -1.], [8., 0.], [8., 1.], [4., 2.], [0., 1.]] {
+            hexagon.add_vertex(Point3::new(x, y, 0.));
+        }
+        assert!(hexagon.add_face(&[0, 1, 2, 3, 4, 5], up).is_ok());
+        let measure = |method| {
+            let (triangles, _) = hexagon.triangulate(method).ok().unwrap();
+            let mut smallest: Float = Float::MAX;
+            let mut length = 0.;
+            for face in triangles.get_faces() {
+                let corners = face.map(|v| triangles.get_vertices()[v]);
+                for i in 0..3 {
+                    let [a, b, c] = [0, 1, 2].map(|j| corners[(i + j) % 3]);
+                    smallest = smallest.min((b - a).angle(&(c - a)));
+                    length += (b - a).norm();
+                }
+            }
+            (smallest, length)
+        };
+        let (ears, ear_length) = measure(Triangulation::EarClipping);
+        let (delaunay, _) = measure(Triangulation::Delaunay);
+        let (_, minimum_length) = measure(Triangulation::MinimumWeight);
+        assert!(delaunay > ears);
+        assert!(minimum_length < ear_length);
+
+        // A square with a vertex halfway along each side, so that vertices are collinear along
+        // the sides and cocircular at the corners, is still covered without flat triangles.
+        let mut square = PolygonMesh::default();
+        for [x, y] in [
+            [0., 0.],
+            [1., 0.],
+            [2., 0.],
+            [2., 1.],
+            [2., 2.],
+            [1., 2.],
+            [0., 2.],
+            [0., 1.],
+        ] {
+            square.add_vertex(Point3::new(x, y, 0.));
+        }
+        assert!(square.add_face(&[0, 1, 2, 3, 4, 5, 6, 7], up).is_ok());
+        for method in [
+            Triangulation::EarClipping,
+            Triangulation::MinimumWeight,
+            Triangulation::Delaunay,
+        ] {
+            let (triangles, _) = square.triangulate(method).ok().unwrap();
+            let areas: Vec<Float> = triangles
+                .get_faces()
+                .iter()
+                .map(|f| area(&triangles, f))
+                .collect();
+            assert_eq!(areas.len(), 6);
+            assert!(areas.iter().all(|&a| a > 0.));
+            assert_eq!(areas.iter().sum::<Float>(), 4.);
+        }
+    }
+
+    /// Test loading meshes in double precision and converting between precisions.
+    #[test]
+    fn test_mesh_precision() {
+        let single = PolygonMesh::load_obj("test-files/trumpet.obj")
+            .ok()
+            .unwrap();
+        let double = PolygonMesh::<f64>::load_obj_with_precision("test-files/trumpet.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(double.get_vertex_count(), single.get_vertex_count());
+        assert_eq!(double.get_faces(), single.get_faces());
+
+        // Parsing straight into each precision agrees with converting afterwards.
+        let narrowed = double.cast::<Float>();
+        assert_eq!(narrowed.get_vertices(), single.get_vertices());
+        let widened = single.cast::<f64>();
+        for (a, b) in widened.get_vertices().iter().zip(double.get_vertices()) {
+            assert!((a - b).norm() <= 1e-6 * b.coords.norm().max(1.));
+        }
+
+        let triangles = double.to_triangle_mesh().ok().unwrap();
+        assert!(triangles
+            .get_normals()
+            .iter()
+            .all(|n| (n.norm() - 1.).abs() < 1e-12));
+        let triangles = triangles.cast::<Float>();
+        assert_eq!(
+            triangles.get_face_count(),
+            single.to_triangle_mesh().ok().unwrap().get_face_count()
+        );
+    }
+
+    #[test]
+    fn write_obj() {
+        let f = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .write_obj("test-files/write-octahedron.obj");
+
+        assert!(f.is_ok());
+        assert_eq!(f.unwrap(), 127);
+    }
+
+    /// Test loading texture coordinates, vertex normals, relative indices, groups, objects and
+    /// smoothing groups from OBJ files, and writing them back out.
+    #[test]
+    fn test_obj_attributes() {
+        let pyramid = PolygonMesh::load_obj("test-files/square-pyramid.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(pyramid.get_vertex_count(), 5);
+        assert_eq!(pyramid.get_texture_coords().len(), 5);
+        assert_eq!(pyramid.get_texture_coords()[4], vector![0.5, 0.5, 0.25]);
+        assert_eq!(pyramid.get_texture_coords()[0], vector![0., 0., 0.]);
+        assert_eq!(
+            pyramid.get_vertex_normals(),
+            &vec![vector![0., 0., -1.], vector![0., -2., 1.]]
+        );
+
+        // Negative indices count back from the last element defined before the face.
+        assert_eq!(
+            pyramid.get_faces(),
+            &vec![
+                vec![0, 3, 2, 1],
+                vec![0, 1, 4],
+                vec![1, 2, 4],
+                vec![2, 3, 4],
+                vec![3, 0, 4]
+            ]
+        );
+        assert_eq!(
+            pyramid.get_face_texture_coords(),
+            &vec![
+                Some(vec![0, 3, 2, 1]),
+                Some(vec![0, 1, 4]),
+                None,
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            pyramid.get_face_vertex_normals(),
+            &vec![Some(vec![0; 4]), None, Some(vec![1; 3]), None, None]
+        );
+
+        assert_eq!(pyramid.get_group("base").unwrap().faces, [0]);
+        assert_eq!(pyramid.get_group("bottom").unwrap().faces, [0]);
+        assert_eq!(pyramid.get_group("sides").unwrap().faces, [1, 2, 3, 4]);
+        assert_eq!(pyramid.get_objects().len(), 1);
+        assert_eq!(pyramid.get_objects()[0].name, "pyramid");
+        assert_eq!(pyramid.get_objects()[0].faces, [0, 1, 2, 3, 4]);
+        assert_eq!(
+            pyramid.get_smoothing_groups(),
+            &vec![None, Some(1), Some(1), Some(2), Some(2)]
+        );
+
+        assert!(pyramid
+            .write_obj("test-files/write-square-pyramid.obj")
+            .is_ok());
+        let mesh = PolygonMesh::load_obj("test-files/write-square-pyramid.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertices(), pyramid.get_vertices());
+        assert_eq!(mesh.get_faces(), pyramid.get_faces());
+        assert_eq!(mesh.get_texture_coords(), pyramid.get_texture_coords());
+        assert_eq!(mesh.get_vertex_normals(), pyramid.get_vertex_normals());
+        assert_eq!(
+            mesh.get_face_texture_coords(),
+            pyramid.get_face_texture_coords()
+        );
+        assert_eq!(
+            mesh.get_face_vertex_normals(),
+            pyramid.get_face_vertex_normals()
+        );
+        assert_eq!(mesh.get_groups(), pyramid.get_groups());
+        assert_eq!(mesh.get_objects(), pyramid.get_objects());
+        assert_eq!(mesh.get_smoothing_groups(), pyramid.get_smoothing_groups());
+
+        // Polylines keep the vertex of each element, and are written back as `l` statements.
+        let lines = PolygonMesh::load_obj("test-files/polylines.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(lines.get_face_count(), 1);
+        assert_eq!(lines.get_polylines(), &vec![vec![0, 1, 2], vec![0, 3]]);
+        assert!(lines.write_obj("test-files/write-polylines.obj").is_ok());
+        let mesh = PolygonMesh::load_obj("test-files/write-polylines.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertices(), lines.get_vertices());
+        assert_eq!(mesh.get_faces(), lines.get_faces());
+        assert_eq!(mesh.get_polylines(), lines.get_polylines());
+
+        for (filename, error) in [
+            (
+                "inconsistent-face.obj",
+                MeshError::FormatError("Inconsistent face element."),
+            ),
+            (
+                "invalid-relative-index.obj",
+                MeshError::IndexingError("Vertex not contained in mesh."),
+            ),
+            (
+                "zero-index.obj",
+                MeshError::IndexingError("Vertex not contained in mesh."),
+            ),
+            (
+                "short-polyline.obj",
+                MeshError::FormatError("Polyline does not have enough vertices."),
+            ),
+        ] {
+            let path = format!("test-files/invalid_objs/{}", filename);
+            assert!(PolygonMesh::load_obj(&path)
+                .err()
+                .is_some_and(|x| *x.root_cause() == error));
+        }
+    }
+
+    /// Test round-tripping STL files in both encodings, and that malformed files are rejected.
+    #[test]
+    fn test_stl() {
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+
+        for (format, filename, length) in [
+            (
+                StlFormat::Ascii,
+                "test-files/write-octahedron-ascii.stl",
+                None,
+            ),
+            (
+                StlFormat::Binary,
+                "test-files/write-octahedron.stl",
+                Some(484),
+            ),
+        ] {
+            let f = octahedron.write_stl(filename, format);
+            assert!(f.is_ok());
+            if let Some(length) = length {
+                assert_eq!(f.unwrap(), length);
+            }
+
+            // The corners of the eight facets weld back into the six vertices.
+            let mesh = TriangleMesh::load_stl(filename).ok().unwrap();
+            assert_eq!(mesh.get_vertex_count(), 6);
+            assert_eq!(mesh.get_face_count(), 8);
+            for (face, normal) in mesh.get_faces().iter().zip(mesh.get_normals()) {
+                let points = face.map(|v| mesh.get_vertices()[v]);
+                let original = octahedron
+                    .get_faces()
+                    .iter()
+                    .position(|f| f.iter().map(|&v| octahedron.get_vertices()[v]).eq(points))
+                    .unwrap();
+                assert!(
+                    (normal.into_inner() - octahedron.get_normals()[original].into_inner()).norm()
+                        < 1e-6
+                );
+            }
+
+            let polygons = PolygonMesh::load_stl(filename).ok().unwrap();
+            assert_eq!(polygons.get_face_count(), 8);
+        }
+
+        // Triangle meshes share their vertices between neighbouring facets.
+        let triangles = octahedron.to_triangle_mesh().ok().unwrap();
+        assert!(triangles
+            .write_stl("test-files/write-octahedron.stl", StlFormat::Binary)
+            .is_ok());
+        let mesh = TriangleMesh::load_stl("test-files/write-octahedron.stl")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertex_count(), triangles.get_vertex_count());
+        assert_eq!(mesh.get_face_count(), triangles.get_face_count());
+
+        // Facets whose corners weld together are kept for `cleanup` to count.
+        let mut mesh = TriangleMesh::load_stl("test-files/collapsed-facet.stl")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertex_count(), 3);
+        assert_eq!(mesh.get_face_count(), 2);
+        assert_eq!(mesh.cleanup(0.).ok().unwrap().degenerate_faces, 1);
+        assert_eq!(mesh.get_face_count(), 1);
+
+        // Concave faces are split by ear clipping, so no facet reaches outside the face.
+        let mut dart = PolygonMesh::default();
+        for [x, y] in [[0., 0.], [4., 2.], [0., 4.], [3., 2.]] {
+            dart.add_vertex(Point3::new(x, y, 0.));
+        }
+        assert!(dart
+            .add_face(&[0, 1, 2, 3], Some(UnitVec3::new_normalize(Vec3::z())))
+            .is_ok());
+        let path = std::env::temp_dir().join("rasterflow-dart.stl");
+        assert!(dart
+            .write_stl(path.to_str().unwrap(), StlFormat::Ascii)
+            .is_ok());
+        let mesh = TriangleMesh::load_stl(path.to_str().unwrap()).ok().unwrap();
+        let area: Float = mesh
+            .get_faces()
+            .iter()
+            .map(|face| {
+                let [a, b, c] = face.map(|v| mesh.get_vertices()[v]);
+                (b - a).cross(&(c - a)).norm() / 2.
+            })
+            .sum();
+        assert!((area - 2.).abs() < 1e-6);
+
+        for (filename, message, position) in [
+            (
+                "truncated-binary.stl",
+                "STL facet count does not match file size.",
+                None,
+            ),
+            ("invalid-header.stl", "Invalid STL header.", None),
+            (
+                "invalid-float.stl",
+                "Failed to parse float.",
+                Some((5, 16, "zero")),
+            ),
+            (
+                "invalid-facet.stl",
+                "Invalid STL facet.",
+                Some((7, 1, "endfacet")),
+            ),
+            (
+                "truncated-ascii.stl",
+                "Invalid STL facet.",
+                Some((6, 1, "")),
+            ),
+        ] {
+            let path = format!("test-files/invalid_stls/{}", filename);
+            let error = TriangleMesh::load_stl(&path).err().unwrap();
+            assert!(matches!(error.root_cause(), MeshError::FormatError(x) if *x == message));
+            assert_eq!(
+                error.line().zip(error.column()),
+                position.map(|(l, c, _)| (l, c))
+            );
+            assert_eq!(error.token(), position.map(|(_, _, token)| token));
+            if position.is_some() {
+                assert_eq!(error.path(), Some(std::path::Path::new(&path)));
+            }
+        }
+    }
+
+    /// Test loading the material libraries of OBJ files and tagging faces with their materials.
+    #[test]
+    fn test_obj_materials() {
+        let duct = PolygonMesh::load_obj("test-files/materials/duct.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(duct.get_material_libraries(), &["duct.mtl", "missing.mtl"]);
+
+        // Materials used without a definition are kept by name alone.
+        let names: Vec<&str> = duct
+            .get_materials()
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names, ["inlet", "wall", "outlet"]);
+        assert_eq!(
+            duct.get_face_materials(),
+            &vec![Some(0), Some(1), Some(1), Some(1), Some(1), Some(2)]
+        );
+        assert_eq!(
+            duct.get_material("outlet").unwrap(),
+            &Material {
+                name: "outlet".to_string(),
+                ..Default::default()
+            }
+        );
+
+        let wall = duct.get_material("wall").unwrap();
+        assert_eq!(wall.ambient, Some([0.1; 3]));
+        assert_eq!(wall.diffuse, Some([0.5; 3]));
+        assert_eq!(wall.shininess, Some(32.));
+        assert_eq!(wall.dissolve, Some(0.75));
+        assert_eq!(wall.optical_density, Some(1.5));
+        assert_eq!(wall.illumination, Some(2));
+        assert_eq!(
+            wall.diffuse_map.as_deref(),
+            Some("-s 2 2 1 textures/steel.png")
+        );
+
+        // Writing the mesh and its materials reproduces both.
+        assert!(duct
+            .write_mtl("test-files/materials/write-duct.mtl")
+            .is_ok());
+        assert!(
+            load_mtl("test-files/materials/write-duct.mtl")
+                .ok()
+                .as_ref()
+                == Some(duct.get_materials())
+        );
+        assert!(duct
+            .write_obj("test-files/materials/write-duct.obj")
+            .is_ok());
+        let mesh = PolygonMesh::load_obj("test-files/materials/write-duct.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_materials(), duct.get_materials());
+        assert_eq!(mesh.get_face_materials(), duct.get_face_materials());
+
+        assert!(PolygonMesh::load_obj("test-files/materials/invalid-library.obj")
+            .err()
+            .is_some_and(|x| matches!(x.root_cause(), MeshError::FormatError(x) if *x == "Material statement before newmtl.")
+                && x.token() == Some("invalid-statement.mtl")));
+    }
+
+    /// Test reading PLY files with custom properties and round-tripping them in every encoding.
+    #[test]
+    fn test_ply() {
+        let cube = PolygonMesh::load_ply("test-files/cube.ply").ok().unwrap();
+        assert_eq!(cube.get_vertex_count(), 8);
+        assert_eq!(cube.get_face_count(), 6);
+        assert_eq!(*cube.get_face(0).ok().unwrap(), vec![0, 3, 2, 1]);
+        assert_eq!(
+            cube.get_normal(0).ok().unwrap().into_inner(),
+            vector![0., 0., -1.]
+        );
+
+        // Every vertex and face property other than the geometry is kept, and other elements are
+        // skipped.
+        let names: Vec<&str> = cube
+            .get_vertex_attributes()
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(names, ["red", "green", "blue", "quality"]);
+        let red = cube.get_vertex_attribute("red").unwrap();
+        assert_eq!(red.value_type, AttributeType::Uint8);
+        assert_eq!(red.values, [255., 0., 0., 255., 255., 0., 255., 0.]);
+        let quality = cube.get_vertex_attribute("quality").unwrap();
+        assert_eq!(quality.values[7], 7.5);
+        let patch = cube.get_face_attribute("patch").unwrap();
+        assert_eq!(patch.value_type, AttributeType::Int32);
+        assert_eq!(patch.values, [1., 2., 3., 3., 3., -1.]);
+
+        for (format, filename) in [
+            (PlyFormat::Ascii, "test-files/write-cube-ascii.ply"),
+            (
+                PlyFormat::BinaryLittleEndian,
+                "test-files/write-cube-le.ply",
+            ),
+            (PlyFormat::BinaryBigEndian, "test-files/write-cube-be.ply"),
+        ] {
+            assert!(cube.write_ply(filename, format).is_ok());
+            let mesh = PolygonMesh::load_ply(filename).ok().unwrap();
+            assert_eq!(mesh.get_vertices(), cube.get_vertices());
+            assert_eq!(mesh.get_faces(), cube.get_faces());
+            assert_eq!(mesh.get_vertex_attributes(), cube.get_vertex_attributes());
+            assert_eq!(mesh.get_face_attributes(), cube.get_face_attributes());
+        }
+
+        // Double precision meshes are written with double precision coordinates.
+        let double = PolygonMesh::<f64>::load_ply_with_precision("test-files/cube.ply")
+            .ok()
+            .unwrap();
+        assert!(double
+            .write_ply(
+                "test-files/write-cube-double.ply",
+                PlyFormat::BinaryLittleEndian
+            )
+            .is_ok());
+        let mesh = PolygonMesh::<f64>::load_ply_with_precision("test-files/write-cube-double.ply")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertices(), double.get_vertices());
+
+        let mut mesh = PolygonMesh::load_ply("test-files/cube.ply").ok().unwrap();
+        assert!(mesh
+            .set_face_attribute(Attribute {
+                name: "patch".to_string(),
+                value_type: AttributeType::Int32,
+                values: vec![0.; 5],
+            })
+            .err()
+            .is_some_and(|x| matches!(x, MeshError::IndexingError(x) if x == "Attribute length does not match face count.")));
+
+        for (filename, error) in [
+            (
+                "invalid-header.ply",
+                MeshError::FormatError("Invalid PLY header."),
+            ),
+            (
+                "empty-element.ply",
+                MeshError::FormatError("PLY element has no properties."),
+            ),
+        ] {
+            let path = format!("test-files/invalid_plys/{}", filename);
+            assert!(PolygonMesh::load_ply(&path).err() == Some(error));
+        }
+
+        // Errors in an ASCII body are reported at their token.
+        for (filename, error, line, column, token) in [
+            (
+                "invalid-indexing.ply",
+                MeshError::IndexingError("Vertex not contained in mesh."),
+                13,
+                7,
+                "3",
+            ),
+            (
+                "invalid-float.ply",
+                MeshError::FormatError("Failed to parse float."),
+                11,
+                3,
+                "zero",
+            ),
+            (
+                "short-face.ply",
+                MeshError::FormatError("Face does not have enough vertices."),
+                13,
+                1,
+                "2",
+            ),
+        ] {
+            let path = format!("test-files/invalid_plys/{}", filename);
+            let error_found = PolygonMesh::load_ply(&path).err().unwrap();
+            assert_eq!(*error_found.root_cause(), error);
+            assert_eq!(error_found.line(), Some(line));
+            assert_eq!(error_found.column(), Some(column));
+            assert_eq!(error_found.token(), Some(token));
+            assert_eq!(error_found.path(), Some(std::path::Path::new(&path)));
+        }
+
+        // Errors in a binary body are reported at their record.
+        for (filename, element, record) in [
+            ("truncated-binary.ply", "face", 1),
+            ("huge-count.ply", "vertex", 1),
+        ] {
+            let path = format!("test-files/invalid_plys/{}", filename);
+            let error = PolygonMesh::load_ply(&path).err().unwrap();
+            assert_eq!(
+                *error.root_cause(),
+                MeshError::FormatError("Unexpected end of file.")
+            );
+            assert_eq!(error.record(), Some((element, record)));
+            assert_eq!(error.path(), Some(std::path::Path::new(&path)));
+            assert_eq!(
+                error.to_string(),
+                format!("{}: {} {}: Unexpected end of file.", path, element, record)
+            );
+        }
+
+        // List properties that a `PolygonMesh` cannot hold are reported on their header line.
+        for (filename, line, token) in [
+            ("list-coordinate.ply", 4, "x"),
+            ("list-attribute.ply", 9, "texcoord"),
+        ] {
+            let path = format!("test-files/invalid_plys/{}", filename);
+            let error = PolygonMesh::load_ply(&path).err().unwrap();
+            assert_eq!(
+                *error.root_cause(),
+                MeshError::FormatError("Unsupported PLY list property.")
+            );
+            assert_eq!(error.line(), Some(line));
+            assert_eq!(error.column(), Some(27));
+            assert_eq!(error.token(), Some(token));
+            assert_eq!(error.path(), Some(std::path::Path::new(&path)));
+        }
+
+        // Attributes left short by vertices pushed directly are caught before the file is created.
+        let mut mesh = PolygonMesh::load_ply("test-files/cube.ply").ok().unwrap();
+        mesh.take_mut_vertices().push(Point3::new(2., 2., 2.));
+        let path = std::env::temp_dir().join("rasterflow-short-attribute.ply");
+        let _ = std::fs::remove_file(&path);
+        assert!(
+            mesh.write_ply(path.to_str().unwrap(), PlyFormat::Ascii)
+                .err()
+                == Some(MeshError::FormatError(
+                    "Attribute length does not match vertex count."
+                ))
+        );
+        assert!(!path.exists());
+    }
+
+    /// Sums the volumes of the tetrahedra in `mesh`, asserting that none of them are inverted.
+    fn total_volume(mesh: &TetrahedralMesh) -> Float {
+        mesh.get_tetrahedra()
+            .iter()
+            .map(|t| {
+                let [a, b, c, d] = t.map(|v| mesh.get_vertices()[v]);
+                let volume = (b - a).cross(&(c - a)).dot(&(d - a)) / 6.;
+                assert!(volume > 0.);
+                volume
+            })
+            .sum()
+    }
+
+    /// Adds a cube spanning `min` to `max` along each axis to `mesh`, wound outward or inward.
+    fn add_cube(mesh: &mut PolygonMesh, min: Float, max: Float, inward: bool) {
+        let first = mesh.get_vertex_count();
+        for i in 0..8 {
+            let corner =
+                [i & 1, (i >> 1) & 1, (i >> 2) & 1].map(|b| if b == 0 { min } else { max });
+            mesh.add_vertex(Point3::from(corner));
+        }
+        for face in [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ] {
+            let mut face = face.map(|v| first + v);
+            if inward {
+                face.reverse();
+            }
+            assert!(mesh.add_face(&face, None).is_ok());
+        }
+    }
+
+    /// Test that the discretizer fills closed surfaces with positively oriented tetrahedra.
+    #[test]
+    fn test_tetrahedral_discretizer() {
+        let config = TetrahedralDiscretizerConfig::default();
+
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let mesh = TetrahedralDiscretizer::discretize(&octahedron, &config)
+            .ok()
+            .unwrap();
+        assert!(mesh.get_tetrahedron_count() > 0);
+        assert!((total_volume(&mesh) - 4. / 3.).abs() < 1e-5);
+
+        // The vertices added at face centres sample the thin parts of the trumpet more densely.
+        let (trumpet, _) = PolygonMesh::load_obj("test-files/trumpet.obj")
+            .ok()
+            .unwrap()
+            .triangulate(Triangulation::Centroid)
+            .ok()
+            .unwrap();
+        let enclosed: Float = trumpet
+            .get_faces()
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|v| trumpet.get_vertices()[v].coords);
+                a.dot(&b.cross(&c)) / 6.
+            })
+            .sum();
+        let mesh = TetrahedralDiscretizer::discretize(&trumpet, &config)
+            .ok()
+            .unwrap();
+
+        // The Delaunay tetrahedralization does not recover the surface, so tetrahedra straddling
+        // the thin parts of the trumpet are kept or dropped as a whole.
+        assert!((total_volume(&mesh) - enclosed).abs() < 0.15 * enclosed);
+    }
+
+    /// Test discretizing, measuring, loading and converting tetrahedral meshes in double precision.
+    #[test]
+    fn test_volume_precision() {
+        let octahedron = PolygonMesh::<f64>::load_obj_with_precision("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let double = TetrahedralDiscretizer::discretize(
+            &octahedron,
+            &TetrahedralDiscretizerConfig {
+                max_volume: Some(0.05),
+                ..Default::default()
+            },
+        )
+        .ok()
+        .unwrap();
+        let volume: f64 = double.cells().map(|t| t.signed_volume()).sum();
+        assert!((volume - 4. / 3.).abs() < 1e-12);
+        let quality = double.quality();
+        assert!(quality.inverted.is_empty());
+        assert!(quality.min_dihedral.min > 0. && quality.max_dihedral.max < 180.);
+
+        // The octahedron is exact in both precisions, so both give the same tetrahedra.
+        let single = TetrahedralDiscretizer::discretize(
+            &octahedron.cast::<Float>(),
+            &TetrahedralDiscretizerConfig {
+                max_volume: Some(0.05),
+                ..Default::default()
+            },
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(single.get_tetrahedra(), double.get_tetrahedra());
+        assert_eq!(double.cast::<Float>().get_vertices(), single.get_vertices());
+
+        let filename = "test-files/write-octahedron-double.msh";
+        assert!(double.write_msh(filename, GmshFormat::BinaryV4).is_ok());
+        let read = TetrahedralMesh::<f64>::load_msh_with_precision(filename)
+            .ok()
+            .unwrap();
+        assert_eq!(read.get_vertices(), double.get_vertices());
+        assert_eq!(read.get_tetrahedra(), double.get_tetrahedra());
+
+        let single = TetrahedralMesh::load_tetgen("test-files/tetgen/cube.1")
+            .ok()
+            .unwrap();
+        let read = TetrahedralMesh::<f64>::load_tetgen_with_precision("test-files/tetgen/cube.1")
+            .ok()
+            .unwrap();
+        assert_eq!(read.cast::<Float>().get_vertices(), single.get_vertices());
+        assert_eq!(read.get_tetrahedra(), single.get_tetrahedra());
+
+        let registry = MeshRegistry::<f64>::default_with_precision();
+        let loaded = registry.load("test-files/octahedron.obj").ok().unwrap();
+        assert_eq!(loaded.get_vertices(), octahedron.get_vertices());
+    }
+
+    /// Builds Schönhardt's twisted prism, which cannot be tetrahedralized without Steiner points.
+    fn schonhardt_prism() -> TriangleMesh {
+        let mut mesh = TriangleMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+            face_normals: Vec::new(),
+            patches: Vec::new(),
+        };
+        for (z, twist) in [(0., 0.), (1., std::f32::consts::FRAC_PI_6)] {
+            for i in 0..3 {
+                let angle = 2. * std::f32::consts::PI * i as Float / 3. + twist;
+                mesh.add_vertex(Point3::new(angle.cos(), angle.sin(), z));
+            }
+        }
+        for face in [
+            [0, 2, 1],
+            [3, 4, 5],
+            [0, 1, 4],
+            [0, 4, 3],
+            [1, 2, 5],
+            [1, 5, 4],
+            [2, 0, 3],
+            [2, 3, 5],
+        ] {
+            assert!(mesh.add_face(&face, None).is_ok());
+        }
+        mesh
+    }
+
+    /// Test that constrained discretization reproduces every surface face exactly.
+    #[test]
+    fn test_constrained_discretizer() {
+        let config = TetrahedralDiscretizerConfig {
+            constrained: true,
+            ..Default::default()
+        };
+
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+
+        for (surface, enclosed) in [(octahedron, 4. / 3.), (schonhardt_prism(), 0.75f32.sqrt())] {
+            let mesh = TetrahedralDiscretizer::discretize(&surface, &config)
+                .ok()
+                .unwrap();
+            assert!((total_volume(&mesh) - enclosed).abs() < 1e-5);
+
+            // Every surface face must be tiled by the boundary faces reporting it as their source,
+            // each wound the same way.
+            let mut covered: Vec<Float> = vec![0.; surface.get_face_count()];
+            for face in mesh.get_boundary_faces() {
+                let [a, b, c] = face.vertices.map(|v| mesh.get_vertices()[v]);
+                let normal = (b - a).cross(&(c - a));
+                assert!(normal.dot(surface.get_normal(face.source).ok().unwrap()) > 0.);
+                covered[face.source] += normal.norm() / 2.;
+            }
+            for (face, area) in surface.get_faces().iter().zip(covered) {
+                let [a, b, c] = face.map(|v| surface.get_vertices()[v]);
+                assert!((area - (b - a).cross(&(c - a)).norm() / 2.).abs() < 1e-5);
+            }
+        }
+
+        // Schönhardt's prism needs Steiner points, so without any the surface is not recovered and
+        // no mesh is returned in its place.
+        let config = TetrahedralDiscretizerConfig {
+            constrained: true,
+            max_recovery_points: Some(0),
+            ..Default::default()
+        };
+        assert!(
+            TetrahedralDiscretizer::discretize(&schonhardt_prism(), &config).err()
+                == Some(MeshError::RecoveryError("Surface could not be recovered."))
+        );
+    }
+
+    /// Test that boundary patches of the surface are carried into the volume mesh.
+    #[test]
+    fn test_boundary_patches() {
+        let mut octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+        assert!(octahedron.set_patch("inlet", &[0, 1, 2, 3]).is_ok());
+        assert!(octahedron.set_patch("wall", &[4, 5, 6, 7]).is_ok());
+        // Faces move out of the patch they were in.
+        assert!(octahedron.set_patch("outlet", &[3, 7]).is_ok());
+        assert_eq!(octahedron.get_patch("inlet").unwrap().faces, [0, 1, 2]);
+        assert_eq!(octahedron.get_patch("wall").unwrap().faces, [4, 5, 6]);
+        assert!(octahedron.set_patch("symmetry", &[8]).err().is_some_and(
+            |x| matches!(x, MeshError::IndexingError(x) if x == "Face not contained in mesh.")
+        ));
+
+        // Split around their centres, each polygon becomes three triangles.
+        let (fan, _) = octahedron
+            .triangulate(Triangulation::Centroid)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            fan.get_patch("outlet").unwrap().faces,
+            [9, 10, 11, 21, 22, 23]
+        );
+        // Clipping ears keeps triangles as they are.
+        let surface = octahedron.to_triangle_mesh().ok().unwrap();
+        assert_eq!(surface.get_patch("outlet").unwrap().faces, [3, 7]);
+
+        // Patches are carried over even without asking for a constrained discretization.
+        let mesh = TetrahedralDiscretizer::discretize(&surface, &Default::default())
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_patches().len(), 3);
+        let mut patched = 0;
+        for (name, polygons) in [("inlet", 3), ("wall", 3), ("outlet", 2)] {
+            let boundary = mesh.get_patch_faces(name).unwrap();
+            let area: Float = boundary
+                .iter()
+                .map(|face| {
+                    let [a, b, c] = face.vertices.map(|v| mesh.get_vertices()[v]);
+                    (b - a).cross(&(c - a)).norm() / 2.
+                })
+                .sum();
+            // Every octahedron face has an area of sqrt(3) / 2.
+            assert!((area - polygons as Float * 0.75f32.sqrt()).abs() < 1e-5);
+            assert!(boundary.iter().all(|face| surface
+                .get_patch(name)
+                .unwrap()
+                .faces
+                .contains(&face.source)));
+            patched += boundary.len();
+        }
+        assert_eq!(patched, mesh.get_boundary_faces().len());
+        assert!(mesh.get_patch("symmetry").is_none());
+
+        // Patches can also be taken from OBJ groups or materials.
+        let mut duct = PolygonMesh::load_obj("test-files/materials/duct.obj")
+            .ok()
+            .unwrap();
+        duct.patches_from_materials();
+        assert_eq!(duct.get_patch("inlet").unwrap().faces, [0]);
+        assert_eq!(duct.get_patch("wall").unwrap().faces, [1, 2, 3, 4]);
+        assert_eq!(duct.get_patch("outlet").unwrap().faces, [5]);
+        let mut pyramid = PolygonMesh::load_obj("test-files/square-pyramid.obj")
+            .ok()
+            .unwrap();
+        pyramid.patches_from_groups();
+        assert_eq!(pyramid.get_patches().len(), 3);
+        assert_eq!(pyramid.get_patch("sides").unwrap().faces, [1, 2, 3, 4]);
+    }
+
+    /// Test reading reference Gmsh files and writing tetrahedralizations back out in every
+    /// version and encoding.
+    #[test]
+    fn test_gmsh() {
+        let volume =
+            |mesh: &TetrahedralMesh| -> Float { mesh.cells().map(|tet| tet.signed_volume()).sum() };
+        for filename in [
+            "test-files/gmsh/tetrahedron-v2.msh",
+            "test-files/gmsh/tetrahedron-v4.msh",
+        ] {
+            let mesh = TetrahedralMesh::load_msh(filename).ok().unwrap();
+            // Unused nodes and repeated tetrahedra are dropped, and tetrahedra are reoriented.
+            assert_eq!(mesh.get_vertices().len(), 4);
+            assert_eq!(mesh.get_tetrahedron_count(), 1);
+            assert!((volume(&mesh) - 1. / 6.).abs() < 1e-6);
+            assert_eq!(mesh.get_boundary_faces().len(), 4);
+            assert_eq!(mesh.get_patch("base").unwrap().faces, [0]);
+            assert_eq!(mesh.get_region("solid").unwrap().cells, [0]);
+
+            let surface = TriangleMesh::load_msh(filename).ok().unwrap();
+            assert_eq!(surface.get_vertex_count(), 4);
+            assert_eq!(surface.get_face_count(), 4);
+            assert_eq!(surface.get_patch("base").unwrap().faces, [0]);
+        }
+        let v2 = TetrahedralMesh::load_msh("test-files/gmsh/tetrahedron-v2.msh")
+            .ok()
+            .unwrap();
+        assert_eq!(v2.get_patch("sides").unwrap().faces, [1, 2, 3]);
+        // Surfaces outside any physical group are not patches.
+        let v4 = TetrahedralMesh::load_msh("test-files/gmsh/tetrahedron-v4.msh")
+            .ok()
+            .unwrap();
+        assert_eq!(v4.get_patches().len(), 1);
+
+        for (filename, error) in [
+            ("unsupported-version", "Unsupported MSH version."),
+            ("invalid-header", "Invalid MSH header."),
+            ("unsupported-element", "Unsupported MSH element type."),
+            ("invalid-float", "Failed to parse float."),
+            ("shared-face", "Face shared by more than two tetrahedra."),
+            ("flat", "Tetrahedron does not have positive volume."),
+        ] {
+            assert!(TetrahedralMesh::load_msh(&format!(
+                "test-files/invalid_mshs/{}.msh",
+                filename
+            ))
+            .err()
+            .is_some_and(|x| matches!(x, MeshError::FormatError(x) if x == error)));
+        }
         assert!(
-            PolygonMesh::load_obj("test-files/invalid_objs/invalid-integer.obj")
+            TetrahedralMesh::load_msh("test-files/invalid_mshs/missing-node.msh")
                 .err()
-                .map_or(
-                    false,
-                    |x| matches!(x, MeshError::FormatError(x) if x == "Failed to parse integer.")
+                .is_some_and(
+                    |x| matches!(x, MeshError::IndexingError(x) if x == "Vertex not contained in mesh.")
                 )
         );
+
+        let mut octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+        assert!(octahedron.set_patch("top", &[0, 1, 2, 3]).is_ok());
+        assert!(octahedron.set_patch("bottom", &[4, 5, 6, 7]).is_ok());
+        let surface = octahedron.to_triangle_mesh().ok().unwrap();
+        let mut mesh = TetrahedralDiscretizer::discretize(&surface, &Default::default())
+            .ok()
+            .unwrap();
+        let upper = (0..mesh.get_tetrahedron_count())
+            .filter(|&t| {
+                mesh.get_cell(t)
+                    .ok()
+                    .unwrap()
+                    .get_points()
+                    .iter()
+                    .all(|p| p.z >= 0.)
+            })
+            .collect::<Vec<_>>();
+        mesh.regions = vec![CellGroup {
+            name: "upper".to_string(),
+            cells: upper.clone(),
+        }];
+
+        for (format, suffix) in [
+            (GmshFormat::AsciiV2, "v2-ascii"),
+            (GmshFormat::BinaryV2, "v2-binary"),
+            (GmshFormat::AsciiV4, "v4-ascii"),
+            (GmshFormat::BinaryV4, "v4-binary"),
+        ] {
+            let filename = format!("test-files/write-octahedron-{}.msh", suffix);
+            assert!(mesh.write_msh(&filename, format).is_ok());
+            let read = TetrahedralMesh::load_msh(&filename).ok().unwrap();
+            assert_eq!(read.get_vertices(), mesh.get_vertices());
+            assert_eq!(read.get_tetrahedra(), mesh.get_tetrahedra());
+            assert_eq!(
+                read.get_boundary_faces().len(),
+                mesh.get_boundary_faces().len()
+            );
+            for name in ["top", "bottom"] {
+                assert_eq!(
+                    read.get_patch_faces(name).unwrap().len(),
+                    mesh.get_patch(name).unwrap().faces.len()
+                );
+            }
+            assert_eq!(read.get_region("upper").unwrap().cells, upper);
+
+            let read = TriangleMesh::load_msh(&filename).ok().unwrap();
+            assert_eq!(read.get_face_count(), mesh.get_boundary_faces().len());
+            assert_eq!(read.get_patches().len(), 2);
+        }
+
+        // Surfaces round trip in double precision.
+        let surface = surface.cast::<f64>();
+        assert!(surface
+            .write_msh(
+                "test-files/write-octahedron-surface.msh",
+                GmshFormat::BinaryV4
+            )
+            .is_ok());
+        let read =
+            TriangleMesh::<f64>::load_msh_with_precision("test-files/write-octahedron-surface.msh")
+                .ok()
+                .unwrap();
+        assert_eq!(read.get_vertices(), surface.get_vertices());
+        assert_eq!(read.get_faces(), surface.get_faces());
+        assert_eq!(read.get_patches(), surface.get_patches());
+    }
+
+    /// Test reading and writing TetGen meshes and piecewise linear complexes.
+    #[test]
+    fn test_tetgen() {
+        let mesh = TetrahedralMesh::load_tetgen("test-files/tetgen/cube.1")
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_vertices().len(), 8);
+        assert_eq!(mesh.get_tetrahedron_count(), 5);
+        let volume: Float = mesh.cells().map(|tet| tet.signed_volume()).sum();
+        assert!((volume - 1.).abs() < 1e-6);
+        assert_eq!(mesh.get_boundary_faces().len(), 12);
+        assert_eq!(mesh.get_patches().len(), 6);
+        assert_eq!(mesh.get_patch("3").unwrap().faces, [4, 5]);
+        assert_eq!(mesh.get_region("1").unwrap().cells, [0, 1, 2, 3]);
+        assert_eq!(mesh.get_region("2").unwrap().cells, [4]);
+        let attributes = mesh.get_vertex_attributes();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].name, "attribute1");
+        assert_eq!(attributes[0].values[6], 10.);
+        assert_eq!(attributes[1].name, "marker");
+        assert_eq!(mesh.get_cell_attributes()[0].values[4], 2.);
+
+        for filename in [
+            "test-files/tetgen/cube.poly",
+            "test-files/tetgen/cube.smesh",
+        ] {
+            let surface = TriangleMesh::load_plc(filename).ok().unwrap();
+            assert_eq!(surface.get_vertex_count(), 8);
+            assert_eq!(surface.get_face_count(), 12);
+            assert_eq!(surface.get_patches().len(), 6);
+            assert_eq!(surface.get_patch("5").unwrap().faces, [0, 1]);
+        }
+
+        // Concave facets are split by ear clipping, so no triangle reaches outside the facet.
+        let dart = TriangleMesh::load_plc("test-files/tetgen/dart.smesh")
+            .ok()
+            .unwrap();
+        assert_eq!(dart.get_face_count(), 2);
+        let area: Float = dart
+            .get_faces()
+            .iter()
+            .map(|face| {
+                let [a, b, c] = face.map(|v| dart.get_vertices()[v]);
+                (b - a).cross(&(c - a)).norm() / 2.
+            })
+            .sum();
+        assert!((area - 2.).abs() < 1e-6);
+
+        for (filename, error) in [
+            ("two-dimensional", "Invalid TetGen header."),
+            ("invalid-float", "Failed to parse float."),
+            (
+                "wrong-neighbours",
+                "TetGen neighbours do not match the tetrahedra.",
+            ),
+            ("shared-face", "Face shared by more than two tetrahedra."),
+            ("flat", "Tetrahedron does not have positive volume."),
+        ] {
+            assert!(TetrahedralMesh::load_tetgen(&format!(
+                "test-files/invalid_tetgen/{}",
+                filename
+            ))
+            .err()
+            .is_some_and(|x| matches!(x, MeshError::FormatError(x) if x == error)));
+        }
         assert!(
-            PolygonMesh::load_obj("test-files/invalid_objs/invalid-string.obj")
+            TetrahedralMesh::load_tetgen("test-files/invalid_tetgen/missing-node")
                 .err()
-                .map_or(
-                    false,
-                    |x| matches!(x, MeshError::FormatError(x) if x == "Unable to process string.")
+                .is_some_and(
+                    |x| matches!(x, MeshError::IndexingError(x) if x == "Vertex not contained in mesh.")
                 )
         );
         assert!(
-            PolygonMesh::load_obj("test-files/invalid_objs/non-existent-file.obj")
+            TriangleMesh::load_plc("test-files/invalid_tetgen/facet-hole.poly")
                 .err()
-                .map_or(
-                    false,
-                    |x| matches!(x, MeshError::IOError(x) if x == "File not found.")
+                .is_some_and(
+                    |x| matches!(x, MeshError::FormatError(x) if x == "TetGen facet holes are not supported.")
                 )
         );
-        assert!(PolygonMesh::load_obj("test-files/invalid_objs/invalid-indexing.obj").err().map_or(
-            false,
-            |x| matches!(x, MeshError::IndexingError(x) if x == "Vertex not contained in mesh.")
-        ));
-        assert!(PolygonMesh::load_obj("/root").err().map_or(
-            false,
-            |x| matches!(x, MeshError::IOError(x) if x == "Insufficient permissions.")
-        ));
-        assert!(PolygonMesh::load_obj("/").err().map_or(
-            false,
-            |x| matches!(x, MeshError::IOError(x) if x == "Could not read next line.")
-        ));
+
+        assert!(mesh.write_tetgen("test-files/write-cube").is_ok());
+        let read = TetrahedralMesh::load_tetgen("test-files/write-cube")
+            .ok()
+            .unwrap();
+        assert_eq!(read.get_vertices(), mesh.get_vertices());
+        assert_eq!(read.get_tetrahedra(), mesh.get_tetrahedra());
+        assert_eq!(read.get_boundary_faces(), mesh.get_boundary_faces());
+        assert_eq!(read.get_patches(), mesh.get_patches());
+        assert_eq!(read.get_regions(), mesh.get_regions());
+        assert_eq!(read.get_vertex_attributes(), mesh.get_vertex_attributes());
+
+        // Surfaces without patches are written without markers, and regions are written when
+        // there are no cell attributes.
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let mut discretized = TetrahedralDiscretizer::discretize(&octahedron, &Default::default())
+            .ok()
+            .unwrap();
+        discretized.regions = vec![CellGroup {
+            name: "solid".to_string(),
+            cells: vec![0],
+        }];
+        assert!(discretized
+            .write_tetgen("test-files/write-octahedron")
+            .is_ok());
+        let read = TetrahedralMesh::load_tetgen("test-files/write-octahedron")
+            .ok()
+            .unwrap();
+        assert_eq!(read.get_tetrahedra(), discretized.get_tetrahedra());
+        assert_eq!(read.get_region("1").unwrap().cells, [0]);
+
+        let surface = TriangleMesh::load_plc("test-files/tetgen/cube.poly")
+            .ok()
+            .unwrap();
+        for (format, filename) in [
+            (PlcFormat::Poly, "test-files/write-cube.poly"),
+            (PlcFormat::Smesh, "test-files/write-cube.smesh"),
+        ] {
+            assert!(surface.write_plc(filename, format).is_ok());
+            let read = TriangleMesh::load_plc(filename).ok().unwrap();
+            assert_eq!(read.get_vertices(), surface.get_vertices());
+            assert_eq!(read.get_faces(), surface.get_faces());
+            assert_eq!(read.get_patches(), surface.get_patches());
+        }
     }
 
+    /// Test loading and saving meshes through a registry of formats, including a format
+    /// registered from outside the crate's own.
     #[test]
-    fn write_obj() {
-        let f = PolygonMesh::load_obj("test-files/octahedron.obj")
+    fn test_mesh_registry() {
+        let registry = MeshRegistry::default();
+        let obj = registry.load("test-files/octahedron.obj").ok().unwrap();
+        let expected = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+        assert_eq!(obj.get_vertices(), expected.get_vertices());
+        assert_eq!(obj.get_faces(), expected.get_faces());
+        let ply = registry.load("test-files/cube.ply").ok().unwrap();
+        let expected = PolygonMesh::load_ply("test-files/cube.ply").ok().unwrap();
+        assert_eq!(ply.get_faces(), expected.get_faces());
+
+        // Data without an extension, or with a misleading one, is read by its leading bytes.
+        let bytes = std::fs::read("test-files/cube.ply").unwrap();
+        for extension in [None, Some("obj"), Some("PLY")] {
+            let mesh = registry.read(&mut &bytes[..], extension).ok().unwrap();
+            assert_eq!(mesh.get_faces(), ply.get_faces());
+        }
+        assert!(registry
+            .read(&mut &b"not a mesh"[..], None)
+            .err()
+            .is_some_and(
+                |x| matches!(x, MeshError::FormatError(x) if x == "Unrecognised mesh format.")
+            ));
+
+        for filename in [
+            "test-files/write-registry.obj",
+            "test-files/write-registry.stl",
+            "test-files/write-registry.ply",
+            "test-files/write-registry.msh",
+        ] {
+            assert!(registry.save(&obj, filename).is_ok());
+            let read = registry.load(filename).ok().unwrap();
+            assert_eq!(read.get_vertex_count(), obj.get_vertex_count());
+            assert_eq!(read.get_face_count(), obj.get_face_count());
+        }
+        let msh = registry.load("test-files/write-registry.msh").ok().unwrap();
+        assert_eq!(msh.get_vertices(), obj.get_vertices());
+        assert_eq!(msh.get_faces(), obj.get_faces());
+        for filename in [
+            "test-files/write-registry.vtk",
+            "test-files/write-registry.vtp",
+            "test-files/write-registry.vtu",
+        ] {
+            assert!(registry.save(&obj, filename).is_ok());
+            // VTK files are written but not read.
+            assert!(registry.load(filename).is_err());
+        }
+        let mut bytes = Vec::new();
+        assert!(registry.write(&obj, &mut bytes, "stl").is_ok());
+        assert_eq!(
+            registry
+                .read(&mut &bytes[..], None)
+                .ok()
+                .unwrap()
+                .get_face_count(),
+            8
+        );
+        assert!(registry
+            .save(&obj, "test-files/write-registry.xyz")
+            .err()
+            .is_some_and(|x| matches!(
+                x,
+                MeshError::FormatError(x) if x == "Unsupported mesh file extension."
+            )));
+        assert!(!std::path::Path::new("test-files/write-registry.xyz").exists());
+
+        // A mesh the writer rejects leaves the file that was already there untouched.
+        let path = std::env::temp_dir().join("rasterflow-registry-kept.ply");
+        std::fs::write(&path, "kept").unwrap();
+        let mut short = registry.load("test-files/cube.ply").ok().unwrap();
+        short.take_mut_vertices().push(Point3::origin());
+        assert!(registry
+            .save(&short, path.to_str().unwrap())
+            .err()
+            .is_some_and(|x| matches!(
+                x,
+                MeshError::FormatError(x) if x == "Attribute length does not match vertex count."
+            )));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "kept");
+
+        // OBJ files fail as they do with `load_obj`, whichever loader the registry uses.
+        let filename = "test-files/invalid_objs/invalid-float.obj";
+        assert!(registry.load(filename).err() == PolygonMesh::load_obj(filename).err());
+
+        /// A format whose files hold no mesh, standing in for a format of another crate.
+        struct Empty;
+        impl MeshReader for Empty {
+            fn extensions(&self) -> &[&str] {
+                &["empty"]
+            }
+            fn detect(&self, bytes: &[u8]) -> bool {
+                bytes.starts_with(b"EMPTY")
+            }
+            fn read(
+                &self,
+                _: &mut dyn std::io::Read,
+                _: Option<&std::path::Path>,
+            ) -> Result<Box<PolygonMesh>, MeshError> {
+                Ok(Box::default())
+            }
+        }
+        impl MeshWriter for Empty {
+            fn extensions(&self) -> &[&str] {
+                &["empty", "obj"]
+            }
+            fn write(
+                &self,
+                _: &PolygonMesh,
+                writer: &mut dyn std::io::Write,
+            ) -> Result<usize, MeshError> {
+                writer
+                    .write_all(b"EMPTY\n")
+                    .map_err(|source| MeshError::FileError {
+                        message: "Could not write file.",
+                        path: None,
+                        source,
+                    })?;
+                Ok(6)
+            }
+        }
+        let mut registry = MeshRegistry::default();
+        registry.register_reader(Empty);
+        registry.register_writer(Empty);
+        assert!(registry
+            .save(&obj, "test-files/write-registry.empty")
+            .is_ok());
+        let read = registry
+            .load("test-files/write-registry.empty")
+            .ok()
+            .unwrap();
+        assert_eq!(read.get_face_count(), 0);
+        // Formats registered later take over the extensions of earlier ones.
+        let mut bytes = Vec::new();
+        assert_eq!(registry.write(&obj, &mut bytes, "obj").ok(), Some(6));
+        assert_eq!(
+            registry
+                .read(&mut &bytes[..], Some("obj"))
+                .ok()
+                .unwrap()
+                .get_face_count(),
+            0
+        );
+    }
+
+    /// Test writing surface and volume meshes with point and cell data to legacy and XML VTK
+    /// files.
+    #[test]
+    fn test_vtk() {
+        let mut octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap();
+        let heights = octahedron.get_vertices().iter().map(|p| p.z as f64);
+        assert!(octahedron
+            .set_vertex_attribute(Attribute {
+                name: "height".to_string(),
+                value_type: AttributeType::Float32,
+                values: heights.collect(),
+            })
+            .is_ok());
+        let normals = octahedron
+            .get_normals()
+            .iter()
+            .map(|n| n.map(f64::from).into());
+        let fields = VtkFields {
+            cell_data: vec![Field::vectors("face normal", normals.collect())],
+            ..Default::default()
+        };
+
+        assert!(octahedron
+            .write_vtk(
+                "test-files/write-octahedron.vtk",
+                &fields,
+                LegacyVtkFormat::Ascii
+            )
+            .is_ok());
+        let legacy = std::fs::read_to_string("test-files/write-octahedron.vtk").unwrap();
+        for line in [
+            "DATASET POLYDATA",
+            "POINTS 6 float",
+            "POLYGONS 8 32",
+            "3 1 0 4",
+            "CELL_DATA 8",
+            "VECTORS face_normal double",
+            "POINT_DATA 6",
+            "SCALARS height float 1",
+        ] {
+            assert!(legacy.lines().any(|l| l == line));
+        }
+        assert!(octahedron
+            .write_vtk(
+                "test-files/write-octahedron-binary.vtk",
+                &fields,
+                LegacyVtkFormat::Binary
+            )
+            .is_ok());
+
+        for (encoding, suffix) in [
+            (VtkEncoding::Ascii, "ascii"),
+            (VtkEncoding::Base64, "base64"),
+            (VtkEncoding::Appended, "appended"),
+        ] {
+            let filename = format!("test-files/write-octahedron-{}.vtp", suffix);
+            assert!(octahedron.write_vtp(&filename, &fields, encoding).is_ok());
+            let filename = format!("test-files/write-octahedron-{}.vtu", suffix);
+            assert!(octahedron.write_vtu(&filename, &fields, encoding).is_ok());
+        }
+        let xml = std::fs::read_to_string("test-files/write-octahedron-ascii.vtp").unwrap();
+        assert!(xml.contains("<Piece NumberOfPoints=\"6\" NumberOfVerts=\"0\" NumberOfLines=\"0\" NumberOfStrips=\"0\" NumberOfPolys=\"8\">"));
+        assert!(xml.contains("<DataArray type=\"Float64\" Name=\"face normal\" NumberOfComponents=\"3\" format=\"ascii\">"));
+        assert!(xml.contains("\n3 6 9 12 15 18 21 24\n"));
+        // The UInt64 length of the eight Int32 offsets precedes them in base64.
+        let xml = std::fs::read_to_string("test-files/write-octahedron-base64.vtp").unwrap();
+        assert!(xml.contains("\nIAAAAAAAAAA=AwAAAAYAAAAJAAAADAAAAA8AAAASAAAAFQAAABgAAAA=\n"));
+
+        // Fields must have a value for every point or cell.
+        let short = VtkFields {
+            point_data: vec![Field::scalars("short", vec![0.; 5])],
+            ..Default::default()
+        };
+        assert!(octahedron
+            .write_vtp(
+                "test-files/write-octahedron-ascii.vtp",
+                &short,
+                VtkEncoding::Ascii
+            )
+            .err()
+            .is_some_and(|x| matches!(
+                x,
+                MeshError::FormatError(x) if x == "Field length does not match point count."
+            )));
+
+        let surface = octahedron.to_triangle_mesh().ok().unwrap();
+        let mesh = TetrahedralDiscretizer::discretize(&surface, &Default::default())
+            .ok()
+            .unwrap();
+        let quality = mesh.quality();
+        let fields = VtkFields {
+            cell_data: vec![Field::scalars(
+                "radius ratio",
+                quality
+                    .cells
+                    .iter()
+                    .map(|c| c.radius_ratio as f64)
+                    .collect(),
+            )],
+            point_data: vec![Field::vectors(
+                "position",
+                mesh.get_vertices()
+                    .iter()
+                    .map(|p| p.coords.map(f64::from).into())
+                    .collect(),
+            )],
+        };
+        assert!(mesh
+            .write_vtk(
+                "test-files/write-octahedron-volume.vtk",
+                &fields,
+                LegacyVtkFormat::Ascii
+            )
+            .is_ok());
+        let legacy = std::fs::read_to_string("test-files/write-octahedron-volume.vtk").unwrap();
+        let cells = format!(
+            "CELLS {} {}",
+            mesh.get_tetrahedron_count(),
+            5 * mesh.get_tetrahedron_count()
+        );
+        assert!(legacy.lines().any(|l| l == cells));
+        assert!(legacy.lines().any(|l| l == "SCALARS radius_ratio double 1"));
+        assert!(mesh
+            .write_vtu(
+                "test-files/write-octahedron-volume.vtu",
+                &fields,
+                VtkEncoding::Appended
+            )
+            .is_ok());
+        assert!(mesh
+            .write_vtu(
+                "test-files/write-octahedron-volume.vtu",
+                &VtkFields::default(),
+                VtkEncoding::Base64
+            )
+            .is_ok());
+    }
+
+    /// Test that refinement meets the requested quality bounds without changing the volume.
+    #[test]
+    fn test_refined_discretizer() {
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
             .ok()
             .unwrap()
-            .write_obj("test-files/write-octahedron.obj");
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
 
-        assert!(f.is_ok());
-        assert_eq!(f.unwrap(), 115);
+        let config = TetrahedralDiscretizerConfig {
+            threshold_angle: 10.,
+            max_radius_edge_ratio: Some(2.),
+            max_volume: Some(0.01),
+            ..Default::default()
+        };
+        let mesh = TetrahedralDiscretizer::discretize(&octahedron, &config)
+            .ok()
+            .unwrap();
+        assert!((total_volume(&mesh) - 4. / 3.).abs() < 1e-5);
+        for t in mesh.get_tetrahedra() {
+            let p = t.map(|v| mesh.get_vertices()[v].cast::<f64>());
+            let volume = (p[1] - p[0]).cross(&(p[2] - p[0])).dot(&(p[3] - p[0])) / 6.;
+            assert!(volume <= 0.01 + 1e-6);
+
+            // The circumcenter solves |c - p0|² = |c - pk|² for k = 1, 2, 3.
+            let rows = nalgebra::Matrix3::from_rows(&[1, 2, 3].map(|k| (p[k] - p[0]).transpose()));
+            let rhs = vector![1, 2, 3].map(|k| (p[k] - p[0]).norm_squared() / 2.);
+            let radius = rows.lu().solve(&rhs).unwrap().norm();
+            let mut shortest = f64::MAX;
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    shortest = shortest.min((p[j] - p[i]).norm());
+                    let others: Vec<usize> = (0..4).filter(|&v| v != i && v != j).collect();
+                    let (k, l) = (others[0], others[1]);
+                    let axis = p[j] - p[i];
+                    let (n, m) = (axis.cross(&(p[k] - p[i])), axis.cross(&(p[l] - p[i])));
+                    let angle = (n.dot(&m) / (n.norm() * m.norm())).acos().to_degrees();
+                    assert!(angle >= 10. - 1e-3);
+                }
+            }
+            assert!(radius / shortest <= 2. + 1e-3);
+        }
+
+        // Tetrahedra centred in the region obey its tighter bound.
+        let config = TetrahedralDiscretizerConfig {
+            max_volume: Some(0.01),
+            regions: vec![VolumeRegion {
+                min: Point3::new(0., -2., -2.),
+                max: Point3::new(2., 2., 2.),
+                max_volume: 0.001,
+            }],
+            ..Default::default()
+        };
+        let mesh = TetrahedralDiscretizer::discretize(&octahedron, &config)
+            .ok()
+            .unwrap();
+        assert!((total_volume(&mesh) - 4. / 3.).abs() < 1e-5);
+        for t in mesh.get_tetrahedra() {
+            let [a, b, c, d] = t.map(|v| mesh.get_vertices()[v]);
+            let volume = (b - a).cross(&(c - a)).dot(&(d - a)) / 6.;
+            let centroid = (a.coords + b.coords + c.coords + d.coords) / 4.;
+            let bound = if centroid.x > 0. { 0.001 } else { 0.01 };
+            assert!(volume <= bound + 1e-6);
+        }
+    }
+
+    /// Test the connectivity, boundary and cell iteration of a tetrahedral mesh.
+    #[test]
+    fn test_tetrahedral_mesh() {
+        // Two tetrahedra sharing the face (1, 2, 3).
+        let vertices = vec![
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., 0., 1.),
+            Point3::new(1., 1., 1.),
+        ];
+        let mesh = TetrahedralMesh::new(vertices, vec![[0, 1, 2, 3], [4, 1, 3, 2]])
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.get_faces().len(), 7);
+        assert_eq!(mesh.get_neighbors()[0], [Some(1), None, None, None]);
+        assert_eq!(mesh.get_neighbors()[1], [Some(0), None, None, None]);
+        assert_eq!(mesh.extract_boundary_faces().len(), 6);
+
+        assert_eq!(mesh.get_cell_count(), 2);
+        assert!(mesh.get_cell(2).is_err());
+        let view = mesh.get_cell(1).ok().unwrap();
+        assert_eq!(view.get_index(), 1);
+        assert_eq!(*view.get_vertices(), [4, 1, 3, 2]);
+
+        // Tetrahedra must lie within the vertices and have positive volume.
+        assert!(
+            TetrahedralMesh::new(mesh.get_vertices().clone(), vec![[0, 1, 2, 5]]).err()
+                == Some(MeshError::IndexingError("Vertex not contained in mesh."))
+        );
+        assert!(
+            TetrahedralMesh::new(mesh.get_vertices().clone(), vec![[0, 2, 1, 3]]).err()
+                == Some(MeshError::FormatError(
+                    "Tetrahedron does not have positive volume."
+                ))
+        );
+        assert_eq!(*view.get_points()[0], Point3::new(1., 1., 1.));
+        assert!(Tetrahedron::from(view) == mesh.cells().nth(1).unwrap());
+        assert!(
+            mesh.cells().next().unwrap()
+                == Tetrahedron::new([
+                    Point3::new(0., 0., 1.),
+                    Point3::new(0., 0., 0.),
+                    Point3::new(0., 1., 0.),
+                    Point3::new(1., 0., 0.),
+                ])
+        );
+
+        // The boundary of a discretized octahedron is its surface, wound outwards.
+        let octahedron = PolygonMesh::load_obj("test-files/octahedron.obj")
+            .ok()
+            .unwrap()
+            .to_triangle_mesh()
+            .ok()
+            .unwrap();
+        let mesh = TetrahedralDiscretizer::discretize(&octahedron, &Default::default())
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.iter().count(), mesh.get_tetrahedron_count());
+        for tet in mesh.iter() {
+            for neighbor in tet.get_neighbors().iter().flatten() {
+                assert!(mesh.get_neighbors()[*neighbor].contains(&Some(tet.get_index())));
+            }
+        }
+        let mut area = 0.;
+        for face in mesh.extract_boundary_faces() {
+            let [a, b, c] = face.map(|v| mesh.get_vertices()[v]);
+            let normal = (b - a).cross(&(c - a));
+            assert!(normal.dot(&(a + b.coords + c.coords).coords) > 0.);
+            area += normal.norm() / 2.;
+        }
+        assert!((area - 4. * (3 as Float).sqrt()).abs() < 1e-5);
+    }
+
+    /// Test the geometric queries of a tetrahedron on the corner of the unit cube.
+    #[test]
+    fn test_tetrahedron_queries() {
+        let corner = Tetrahedron::new([
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., 0., 1.),
+        ]);
+        let close = |a: Float, b: Float| (a - b).abs() < 1e-5;
+
+        assert!(close(corner.signed_volume(), 1. / 6.));
+        assert_eq!(corner.centroid(), Point3::new(0.25, 0.25, 0.25));
+        assert!((corner.circumcenter().unwrap() - Point3::new(0.5, 0.5, 0.5)).norm() < 1e-5);
+        assert!(close(corner.circumradius().unwrap(), 0.75f32.sqrt()));
+
+        let slanted = 0.75f32.sqrt();
+        let areas = corner.face_areas();
+        assert!(close(areas[0], slanted) && areas[1..].iter().all(|&a| close(a, 0.5)));
+        assert!(close(corner.inradius(), 0.5 / (1.5 + slanted)));
+        let normals = corner.face_normals();
+        assert!((normals[0] - Vec3::new(1., 1., 1.).normalize()).norm() < 1e-5);
+        assert!((normals[3] - Vec3::new(0., 0., -1.)).norm() < 1e-5);
+
+        // The edges along the axes meet at right angles, the others at acos(1 / sqrt(3)).
+        let angles = corner.dihedral_angles();
+        for angle in &angles[..3] {
+            assert!(close(*angle, std::f32::consts::FRAC_PI_2));
+        }
+        for angle in &angles[3..] {
+            assert!(close(*angle, (1. / (3 as Float).sqrt()).acos()));
+        }
+
+        let weights = corner.barycentric(&Point3::new(0.1, 0.2, 0.3)).unwrap();
+        for (w, expected) in weights.iter().zip([0.4, 0.1, 0.2, 0.3]) {
+            assert!(close(*w, expected));
+        }
+        assert!(corner.contains(&Point3::new(0.1, 0.2, 0.3)));
+        assert!(!corner.contains(&Point3::new(0.5, 0.5, 0.5)));
+
+        // Reordering the corners inverts the tetrahedron without changing its shape.
+        let inverted = Tetrahedron::new([
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., 0., 1.),
+        ]);
+        assert!(close(inverted.signed_volume(), -1. / 6.));
+        assert!((inverted.face_normals()[1] - normals[0]).norm() < 1e-5);
+
+        let flat = Tetrahedron::new([
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(1., 1., 0.),
+        ]);
+        assert!(flat.circumcenter().is_none());
+        assert!(flat.barycentric(&Point3::origin()).is_none());
+        assert_eq!(flat.inradius(), 0.);
+    }
+
+    /// Test the quality report on a regular tetrahedron, a flattened one and an inverted one.
+    #[test]
+    fn test_quality_report() {
+        // A regular tetrahedron on alternate corners of the cube, and a squashed copy of it
+        // sharing the face (1, 2, 3).
+        let vertices = vec![
+            Point3::new(1., 1., 1.),
+            Point3::new(1., -1., -1.),
+            Point3::new(-1., 1., -1.),
+            Point3::new(-1., -1., 1.),
+            Point3::new(-0.5, -0.5, -0.5),
+        ];
+        let mesh = TetrahedralMesh::new(vertices, vec![[0, 2, 1, 3], [4, 1, 2, 3]])
+            .ok()
+            .unwrap();
+        let report = mesh.quality();
+        let close = |a: Float, b: Float| (a - b).abs() < 1e-4;
+
+        let regular = report.cells[0];
+        assert!(close(regular.aspect_ratio, 1.) && close(regular.radius_ratio, 1.));
+        assert!(close(regular.skewness, 0.));
+        let angle = (1. / 3 as Float).acos().to_degrees();
+        assert!(close(regular.min_dihedral, angle) && close(regular.max_dihedral, angle));
+        assert!(close(regular.volume_ratio, 8.));
+
+        let squashed = report.cells[1];
+        assert!(squashed.aspect_ratio > 1. && squashed.radius_ratio < 1.);
+        assert!(squashed.max_dihedral > 90. && squashed.skewness > 0.5);
+        assert!(report.inverted.is_empty());
+
+        assert!(close(report.radius_ratio.max, 1.));
+        assert!(close(report.volume_ratio.min, 8.));
+        assert_eq!(report.skewness.histogram.counts[0], 1);
+        assert_eq!(
+            report.min_dihedral.histogram.counts.iter().sum::<usize>(),
+            2
+        );
+
+        let mesh = TetrahedralMesh::assemble(mesh.get_vertices().clone(), vec![[0, 1, 2, 3]])
+            .ok()
+            .unwrap();
+        assert_eq!(mesh.quality().inverted, vec![0]);
+
+        let empty = TetrahedralMesh::<Float>::new(Vec::new(), Vec::new())
+            .ok()
+            .unwrap()
+            .quality();
+        assert!(empty.cells.is_empty() && empty.aspect_ratio.mean == 0.);
+    }
+
+    /// Test the predicates on points perturbed by single units in the last place away from
+    /// collinear, coplanar, cocircular and cospherical configurations, where the rounding error of
+    /// naive floating-point evaluation swamps the determinant.
+    #[test]
+    fn test_predicates() {
+        use crate::geometry::predicates::{incircle, insphere, orient2d, orient3d};
+        use nalgebra::{Point2, Point3};
+
+        let ulp = f64::EPSILON / 2.;
+        let near = |i: i32| 0.5 + i as f64 * ulp;
+        let sign = |x: f64| (x > 0.) as i32 - (x < 0.) as i32;
+        // Whether the point offset by `(i, j)` units from a corner of the square or cube lies
+        // inside (1), on (0) or outside (-1) the circle or sphere through the corners.
+        let inside = |i: i32, j: i32| match (i, j) {
+            (0, 0) => 0,
+            _ if i + j < 0 => 1,
+            _ => -1,
+        };
+
+        let (a, b) = (Point2::new(12., 12.), Point2::new(24., 24.));
+        let (c, d, e) = (
+            Point2::new(-0.5, 0.5),
+            Point2::new(-0.5, -0.5),
+            Point2::new(0.5, -0.5),
+        );
+        let (p, q, r, s) = (
+            Point3::new(0.5, 0.5, 0.5),
+            Point3::new(-0.5, -0.5, 0.5),
+            Point3::new(-0.5, 0.5, -0.5),
+            Point3::new(0.5, -0.5, -0.5),
+        );
+        assert!(orient3d(&p, &q, &r, &s) > 0.);
+
+        for i in -5..=5 {
+            for j in -5..=5 {
+                let x = Point2::new(near(i), near(j));
+                assert_eq!(sign(orient2d(&x, &a, &b)), (j - i).signum());
+                let above = orient3d(
+                    &Point3::new(12., 12., 1.),
+                    &Point3::new(24., 24., 1.),
+                    &Point3::new(24., 24., 2.),
+                    &Point3::new(near(i), near(j), 0.7),
+                );
+                assert_eq!(sign(above), (i - j).signum());
+                assert_eq!(sign(incircle(&c, &d, &e, &x)), inside(i, j));
+                let y = Point3::new(near(i), near(j), -0.5);
+                assert_eq!(sign(insphere(&p, &q, &r, &s, &y)), inside(i, j));
+            }
+        }
     }
 }